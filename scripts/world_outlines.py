@@ -0,0 +1,104 @@
+#!/usr/bin/env python3
+"""Generates assets/world_outlines.txt from Natural Earth 1:110m vectors (public domain).
+
+Usage: python3 scripts/world_outlines.py [LAND LAKES BORDERS] > assets/world_outlines.txt
+
+Without arguments the GeoJSON files of the pinned Natural Earth release are downloaded,
+otherwise the three local GeoJSON files are read instead.
+"""
+
+import json
+import sys
+import urllib.request
+
+RELEASE = "v5.1.2"
+BASE_URL = f"https://raw.githubusercontent.com/nvkelso/natural-earth-vector/{RELEASE}/geojson"
+SOURCES = {
+    "land": "ne_110m_land.geojson",
+    "lakes": "ne_110m_lakes.geojson",
+    "borders": "ne_110m_admin_0_boundary_lines_land.geojson",
+}
+DECIMALS = 2  # About a kilometre, far below a terminal cell
+
+HEADER = f"""\
+# World outlines, embedded in the binary as the default base map
+#
+# Generated by scripts/world_outlines.py from Natural Earth {RELEASE} 1:110m land, lakes and
+# admin-0 land boundary lines, https://www.naturalearthdata.com. Natural Earth is in the
+# public domain, see https://www.naturalearthdata.com/about/terms-of-use/
+#
+# One segment per `>` header (`> coast|lake|border NAME`) followed by one `LON LAT`
+# point per line in degrees. Coasts and lakes are closed rings, borders are open lines."""
+
+
+def read_file(path):
+    with open(path, encoding="utf-8") as file:
+        return json.load(file)
+
+
+def download(name):
+    with urllib.request.urlopen(f"{BASE_URL}/{name}") as response:
+        return json.load(response)
+
+
+def lines_of(geometry):
+    kind, coordinates = geometry["type"], geometry["coordinates"]
+    if kind == "LineString":
+        return [coordinates]
+    if kind in ("MultiLineString", "Polygon"):
+        return coordinates
+    if kind == "MultiPolygon":
+        return [ring for polygon in coordinates for ring in polygon]
+    raise ValueError(f"Unexpected geometry {kind}")
+
+
+def simplify(points, closed):
+    rounded = []
+    for lon, lat, *_ in points:
+        point = (round(lon, DECIMALS), round(lat, DECIMALS))
+        if not rounded or rounded[-1] != point:
+            rounded.append(point)
+    # Rings are closed by the reader, the repeated first point isn't needed
+    if closed and len(rounded) > 1 and rounded[0] == rounded[-1]:
+        rounded.pop()
+    return rounded
+
+
+def name_of(feature, fallback):
+    properties = feature.get("properties") or {}
+    for key in ("name", "NAME", "name_en"):
+        if properties.get(key):
+            return str(properties[key]).replace("\n", " ")
+    return fallback
+
+
+def segments(collection, kind, closed, fallback):
+    for index, feature in enumerate(collection["features"], start=1):
+        name = name_of(feature, f"{fallback} {index}")
+        for line in lines_of(feature["geometry"]):
+            points = simplify(line, closed)
+            if len(points) >= (3 if closed else 2):
+                yield kind, name, points
+
+
+def main(args):
+    if args and len(args) != 3:
+        sys.exit(__doc__)
+    load = read_file if args else download
+    land, lakes, borders = args or [SOURCES[key] for key in ("land", "lakes", "borders")]
+
+    out = [HEADER]
+    layers = [
+        (load(land), "coast", True, "land"),
+        (load(lakes), "lake", True, "lake"),
+        (load(borders), "border", False, "border"),
+    ]
+    for collection, kind, closed, fallback in layers:
+        for kind, name, points in segments(collection, kind, closed, fallback):
+            out.append(f"> {kind} {name}")
+            out.extend(f"{lon:g} {lat:g}" for lon, lat in points)
+    print("\n".join(out))
+
+
+if __name__ == "__main__":
+    main(sys.argv[1:])
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Duration, Utc};
+use dialoguer::Input;
+
+use crate::cli::utils;
+use crate::libs::satellite_beta_service::{self, BetaSample};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_DAYS: f64 = 365.0;
+const DEFAULT_STEP_DAYS: f64 = 1.0;
+const MAX_SAMPLES: f64 = 100_000.0;
+const MIN_STEP_DAYS: f64 = 1.0 / 86400.0; // A second
+const CHART_WIDTH: usize = 60;
+const CHART_HEIGHT: usize = 10;
+
+fn timeline(
+    satellite: &Satellite,
+    start: &DateTime<Utc>,
+    days: f64,
+    step_days: f64,
+) -> Result<Vec<BetaSample>, String> {
+    // Written so NaN fails as well
+    if !(days > 0.0 && step_days > 0.0) {
+        return Err("The range and the step have to be positive".to_string());
+    }
+    if step_days < MIN_STEP_DAYS {
+        return Err("The step has to be at least a second".to_string());
+    }
+    if days / step_days > MAX_SAMPLES {
+        return Err(format!(
+            "Too many samples, use a step above {:.3} days",
+            days / MAX_SAMPLES
+        ));
+    }
+
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let to_duration = |days: f64| Duration::milliseconds((days * 86_400_000.0) as i64);
+    Ok(satellite_beta_service::beta_timeline(
+        &elements,
+        start,
+        &(*start + to_duration(days)),
+        to_duration(step_days),
+    ))
+}
+
+fn print_table(samples: &[BetaSample]) {
+    println!(
+        "{:<16} {:>8} {:>8} {:>10} {:>12}",
+        "Date (UTC)", "RAAN", "Beta", "Eclipse %", "Eclipse min"
+    );
+    for sample in samples {
+        println!(
+            "{:<16} {:>8.2} {:>8.2} {:>10.1} {:>12.1}",
+            sample.time.format("%Y-%m-%d %H:%M"),
+            sample.raan_deg,
+            sample.beta_deg,
+            sample.eclipse_fraction * 100.0,
+            sample.eclipse_minutes
+        );
+    }
+}
+
+fn print_charts(samples: &[BetaSample]) {
+    let Some(first) = samples.first() else {
+        return;
+    };
+    let days = |sample: &BetaSample| (sample.time - first.time).num_seconds() as f64 / 86400.0;
+
+    println!();
+    utils::print_ascii_chart(
+        "Beta angle (deg) over days",
+        &samples
+            .iter()
+            .map(|s| (days(s), s.beta_deg))
+            .collect::<Vec<_>>(),
+        CHART_WIDTH,
+        CHART_HEIGHT,
+    );
+    println!();
+    utils::print_ascii_chart(
+        "Eclipse per orbit (min) over days",
+        &samples
+            .iter()
+            .map(|s| (days(s), s.eclipse_minutes))
+            .collect::<Vec<_>>(),
+        CHART_WIDTH,
+        CHART_HEIGHT,
+    );
+}
+
+fn print_seasons(samples: &[BetaSample]) {
+    let seasons = satellite_beta_service::eclipse_seasons(samples);
+    println!();
+    match seasons.as_slice() {
+        [] => println!("Full sun on every orbit over the whole range"),
+        [season]
+            if season.start == samples[0].time && season.end == samples[samples.len() - 1].time =>
+        {
+            println!(
+                "Eclipses on every orbit over the whole range, up to {:.1} min",
+                season.max_eclipse_minutes
+            )
+        }
+        _ => {
+            println!("Eclipse seasons:");
+            for season in &seasons {
+                println!(
+                    "  {} to {}  up to {:.1} min per orbit",
+                    season.start.format("%Y-%m-%d"),
+                    season.end.format("%Y-%m-%d"),
+                    season.max_eclipse_minutes
+                );
+            }
+        }
+    }
+}
+
+fn timeline_to_csv(samples: &[BetaSample]) -> String {
+    let mut csv = String::from("time,raan_deg,beta_deg,eclipse_fraction,eclipse_minutes\n");
+    for sample in samples {
+        csv.push_str(&format!(
+            "{},{:.4},{:.4},{:.5},{:.3}\n",
+            sample.time.to_rfc3339(),
+            sample.raan_deg,
+            sample.beta_deg,
+            sample.eclipse_fraction,
+            sample.eclipse_minutes
+        ));
+    }
+    csv
+}
+
+// Non-interactive mode: `cosmos_cli beta 25544 --start 2024-01-01 --days 365 --step 1`
+pub fn beta_command(args: &[String]) -> Result<(), String> {
+    let identifier = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("Missing NORAD ID or COSPAR designator")?;
+    let flags = utils::parse_flags(&args[1..])?;
+    let start = flags
+        .get("start")
+        .map_or(Ok(Utc::now()), |text| utils::parse_time(text))?;
+    let days = utils::number_flag(&flags, "days", DEFAULT_DAYS)?;
+    let step = utils::number_flag(&flags, "step", DEFAULT_STEP_DAYS)?;
+    let csv = match flags.get("format").map(String::as_str) {
+        None | Some("table") => false,
+        Some("csv") => true,
+        Some(other) => return Err(format!("Unknown format '{}' (table or csv)", other)),
+    };
+
+    let satellite = utils::resolve_one_satellite(identifier)?;
+
+    let samples = timeline(&satellite, &start, days, step)?;
+    if csv {
+        print!("{}", timeline_to_csv(&samples));
+    } else {
+        print_table(&samples);
+        print_charts(&samples);
+        print_seasons(&samples);
+    }
+
+    Ok(())
+}
+
+pub fn beta_ui(satellite: &Satellite) {
+    let days: f64 = Input::new()
+        .with_prompt("Days ahead")
+        .default(DEFAULT_DAYS)
+        .interact_text()
+        .unwrap();
+    let step: f64 = Input::new()
+        .with_prompt("Step (days)")
+        .default(DEFAULT_STEP_DAYS)
+        .interact_text()
+        .unwrap();
+
+    match timeline(satellite, &Utc::now(), days, step) {
+        Ok(samples) => {
+            print_table(&samples);
+            print_charts(&samples);
+            print_seasons(&samples);
+        }
+        Err(err) => eprintln!("{}", err),
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{Duration, Utc};
+use dialoguer::Input;
+
+use crate::cli::utils;
+use crate::libs::satellite_catalog_service;
+use crate::libs::satellite_conjunction_service::{screen_conjunctions, ScreeningOptions};
+use crate::libs::satellite_search_service::Satellite;
+
+const COARSE_STEP_SECONDS: i64 = 60;
+
+pub fn conjunction_ui(satellite: &Satellite) {
+    let hours: f64 = Input::new()
+        .with_prompt("Screening window (hours)")
+        .default(24.0)
+        .interact_text()
+        .unwrap();
+    let threshold_km: f64 = Input::new()
+        .with_prompt("Miss distance threshold (km)")
+        .default(10.0)
+        .interact_text()
+        .unwrap();
+
+    let pb = utils::display_spinner("Loading catalog...".to_string(), None);
+    let catalog = satellite_catalog_service::get_catalog();
+    let candidates: Vec<sgp4::Elements> = catalog
+        .iter()
+        .filter_map(|s| utils::try_parse_tle(&s.name, &s.line_one, &s.line_two).ok())
+        .collect();
+
+    pb.set_message(format!("Screening against {} objects...", candidates.len()));
+    let primary = utils::parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two);
+    let options = ScreeningOptions {
+        start: Utc::now(),
+        duration: Duration::seconds((hours * 3600.0) as i64),
+        threshold_km,
+        coarse_step_seconds: COARSE_STEP_SECONDS,
+    };
+    let result = screen_conjunctions(&primary, &candidates, &options);
+    pb.finish_and_clear();
+
+    match result {
+        Ok(conjunctions) if conjunctions.is_empty() => {
+            println!(
+                "No approaches closer than {:.1} km in the next {:.1} hours",
+                threshold_km, hours
+            );
+        }
+        Ok(conjunctions) => utils::print_conjunctions(&conjunctions),
+        Err(err) => eprintln!("Error screening conjunctions: {}", err),
+    }
+}
@@ -0,0 +1,15 @@
+use crate::cli::utils;
+use crate::libs::satellite_decay_service;
+use crate::libs::satellite_search_service::Satellite;
+
+pub fn decay_ui(satellite: &Satellite) {
+    let elements = utils::parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two);
+
+    clearscreen::clear().unwrap();
+    println!("Orbital decay of {}\n", satellite.name);
+
+    match satellite_decay_service::estimate_decay(&elements, &utils::history_elements(satellite)) {
+        Some(decay) => utils::print_decay_estimate(&decay),
+        None => println!("No decay estimate available (orbit too high or no drag information)"),
+    }
+}
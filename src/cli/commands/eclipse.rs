@@ -0,0 +1,146 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use dialoguer::Input;
+
+use crate::cli::theme::{self, Role};
+use crate::cli::utils;
+use crate::libs::orbit::{EclipseState, Propagator};
+use crate::libs::satellite_eclipse_service::{self, EclipsePeriod};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_DURATION_MINUTES: f64 = 1440.0;
+const MAX_DURATION_MINUTES: f64 = 60.0 * 24.0 * 30.0;
+const CSV_HEADER: &str = "norad_id,state,start,end,minutes";
+
+fn timeline(
+    satellite: &Satellite,
+    start: &DateTime<Utc>,
+    minutes: f64,
+) -> Result<Vec<EclipsePeriod>, String> {
+    if minutes <= 0.0 || minutes > MAX_DURATION_MINUTES {
+        return Err(format!(
+            "The duration has to be between 0 and {} minutes",
+            MAX_DURATION_MINUTES
+        ));
+    }
+
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let propagator = Propagator::new(elements)?;
+    let end = *start + Duration::milliseconds((minutes * 60000.0) as i64);
+    satellite_eclipse_service::eclipse_timeline(&propagator, start, &end)
+        .map_err(|e| format!("Error propagating {}: {}", satellite.name, e))
+}
+
+fn minutes(duration: Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 60000.0
+}
+
+fn print_timeline(satellite: &Satellite, periods: &[EclipsePeriod]) {
+    println!(
+        "{}",
+        theme::bold(&format!("Eclipse timeline of {}", satellite.name))
+    );
+    println!(
+        "{:<9} {:<19} {:<19} {:>10}",
+        "State", "Start", "End", "Minutes"
+    );
+    for period in periods {
+        let line = format!(
+            "{:<9} {:<19} {:<19} {:>10.2}",
+            period.state.label(),
+            period
+                .start
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            period.end.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            minutes(period.duration())
+        );
+        match period.state {
+            EclipseState::Sunlit => println!("{}", line),
+            EclipseState::Penumbra => println!("{}", theme::paint(&line, Role::Penumbra)),
+            EclipseState::Umbra => println!("{}", theme::paint(&line, Role::Umbra)),
+        }
+    }
+
+    // Share of the whole range spent in each state
+    let total: f64 = periods
+        .iter()
+        .map(|period| minutes(period.duration()))
+        .sum();
+    if total > 0.0 {
+        let share = |state: EclipseState| {
+            periods
+                .iter()
+                .filter(|period| period.state == state)
+                .map(|period| minutes(period.duration()))
+                .sum::<f64>()
+                / total
+                * 100.0
+        };
+        println!(
+            "\nSunlit {:.1}%, penumbra {:.1}%, umbra {:.1}%",
+            share(EclipseState::Sunlit),
+            share(EclipseState::Penumbra),
+            share(EclipseState::Umbra)
+        );
+    }
+}
+
+fn timeline_to_csv(satellite: &Satellite, periods: &[EclipsePeriod]) -> String {
+    let mut csv = String::new();
+    for period in periods {
+        csv.push_str(&format!(
+            "{},{},{},{},{:.3}\n",
+            satellite.id,
+            period.state.label().to_lowercase(),
+            period.start.to_rfc3339(),
+            period.end.to_rfc3339(),
+            minutes(period.duration())
+        ));
+    }
+    csv
+}
+
+// Non-interactive mode: `cosmos_cli eclipse 25544 --start "2024-06-21 00:00" --duration 1440`
+pub fn eclipse_command(args: &[String]) -> Result<(), String> {
+    let identifier = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("Missing NORAD ID or COSPAR designator")?;
+    let flags = utils::parse_flags(&args[1..])?;
+    let start = flags
+        .get("start")
+        .map_or(Ok(Utc::now()), |text| utils::parse_time(text))?;
+    let duration = utils::number_flag(&flags, "duration", DEFAULT_DURATION_MINUTES)?;
+    let csv = match flags.get("format").map(String::as_str) {
+        None | Some("table") => false,
+        Some("csv") => true,
+        Some(other) => return Err(format!("Unknown format '{}' (table or csv)", other)),
+    };
+
+    if csv {
+        println!("{}", CSV_HEADER);
+    }
+    for satellite in utils::resolve_satellites(identifier)? {
+        let periods = timeline(&satellite, &start, duration)?;
+        if csv {
+            print!("{}", timeline_to_csv(&satellite, &periods));
+        } else {
+            print_timeline(&satellite, &periods);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn eclipse_ui(satellite: &Satellite) {
+    let duration: f64 = Input::new()
+        .with_prompt("Duration (minutes)")
+        .default(DEFAULT_DURATION_MINUTES)
+        .interact_text()
+        .unwrap();
+
+    match timeline(satellite, &Utc::now(), duration) {
+        Ok(periods) => print_timeline(satellite, &periods),
+        Err(err) => eprintln!("{}", err),
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs;
+
+use chrono::{Duration, Utc};
+use dialoguer::{Input, Select};
+
+use crate::cli::{theme, utils};
+use crate::libs::satellite_ephemeris_service::{self, EphemerisRow, Frame};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_DURATION_MINUTES: f64 = 60.0;
+const DEFAULT_STEP_SECONDS: f64 = 60.0;
+
+// Non-interactive mode: `cosmos_cli ephemeris 25544 --step 10 --frame ecef --format csv`
+pub fn ephemeris_command(args: &[String]) -> Result<(), String> {
+    let identifier = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("Missing NORAD ID or COSPAR designator")?;
+    let flags = utils::parse_flags(&args[1..])?;
+
+    let satellite = utils::resolve_one_satellite(identifier)?;
+
+    let start = flags
+        .get("start")
+        .map_or(Ok(Utc::now()), |text| utils::parse_time(text))?;
+    let duration = utils::number_flag(&flags, "duration", DEFAULT_DURATION_MINUTES)?;
+    let step = utils::number_flag(&flags, "step", DEFAULT_STEP_SECONDS)?;
+    let frame = Frame::parse(flags.get("frame").map_or("teme", String::as_str))?;
+    let observer = utils::observer_from_flags(&flags)?;
+
+    let times = satellite_ephemeris_service::sample_times(
+        &start,
+        Duration::milliseconds((duration * 60000.0) as i64),
+        Duration::milliseconds((step * 1000.0) as i64),
+    )?;
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let rows = satellite_ephemeris_service::generate_ephemeris(
+        &elements,
+        &times,
+        frame,
+        observer.as_ref(),
+    )?;
+
+    let output = match flags.get("format").map(String::as_str) {
+        None | Some("table") => {
+            utils::print_ephemeris_table(&rows, frame.label());
+            return Ok(());
+        }
+        Some("csv") => satellite_ephemeris_service::ephemeris_to_csv(&rows),
+        Some("json") => satellite_ephemeris_service::ephemeris_to_json(&rows, frame),
+        Some(other) => {
+            return Err(format!(
+                "Unknown format '{}' (expected table, csv or json)",
+                other
+            ))
+        }
+    };
+
+    match flags.get("output") {
+        Some(path) => {
+            fs::write(path, output).map_err(|e| format!("Error writing {}: {}", path, e))?;
+            eprintln!("Wrote {} states to {}", rows.len(), path);
+        }
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
+
+fn export_rows(satellite: &Satellite, rows: &[EphemerisRow], frame: Frame) {
+    let formats = &["CSV", "JSON"];
+    let format = Select::with_theme(&theme::prompt_theme())
+        .with_prompt("Export format")
+        .default(0)
+        .items(&formats[..])
+        .interact()
+        .unwrap();
+    let path: String = Input::new()
+        .with_prompt("Export to")
+        .default(format!(
+            "{}_ephemeris.{}",
+            satellite.id,
+            formats[format].to_lowercase()
+        ))
+        .interact_text()
+        .unwrap();
+
+    let output = if format == 0 {
+        satellite_ephemeris_service::ephemeris_to_csv(rows)
+    } else {
+        satellite_ephemeris_service::ephemeris_to_json(rows, frame)
+    };
+    match fs::write(&path, output) {
+        Ok(_) => println!("Exported {} states to {}", rows.len(), path),
+        Err(err) => eprintln!("Error writing {}: {}", path, err),
+    }
+}
+
+pub fn ephemeris_ui(satellite: &Satellite) {
+    let minutes: f64 = Input::new()
+        .with_prompt("Duration (minutes)")
+        .default(DEFAULT_DURATION_MINUTES)
+        .interact_text()
+        .unwrap();
+    let step: f64 = Input::new()
+        .with_prompt("Step (seconds)")
+        .default(DEFAULT_STEP_SECONDS)
+        .interact_text()
+        .unwrap();
+    let frames = &["TEME", "ECEF"];
+    let frame = match Select::with_theme(&theme::prompt_theme())
+        .with_prompt("Frame")
+        .default(0)
+        .items(&frames[..])
+        .interact()
+        .unwrap()
+    {
+        0 => Frame::Teme,
+        _ => Frame::Ecef,
+    };
+
+    let observer = match utils::default_observer() {
+        Ok(observer) => observer,
+        Err(err) => {
+            eprintln!("{}", err);
+            None
+        }
+    };
+
+    let result = satellite_ephemeris_service::sample_times(
+        &Utc::now(),
+        Duration::milliseconds((minutes * 60000.0) as i64),
+        Duration::milliseconds((step * 1000.0) as i64),
+    )
+    .and_then(|times| {
+        let elements =
+            utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+        satellite_ephemeris_service::generate_ephemeris(&elements, &times, frame, observer.as_ref())
+    });
+
+    let rows = match result {
+        Ok(rows) => rows,
+        Err(err) => {
+            eprintln!("Error generating ephemeris: {}", err);
+            return;
+        }
+    };
+
+    utils::print_ephemeris_table(&rows, frame.label());
+
+    let items = &["Export", "Return"];
+    let selection = Select::with_theme(&theme::prompt_theme())
+        .default(1)
+        .items(&items[..])
+        .interact()
+        .unwrap();
+    if selection == 0 {
+        export_rows(satellite, &rows, frame);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::fs;
+
+use chrono::{Duration, Utc};
+use dialoguer::{Input, Select};
+
+use crate::cli::{theme, utils};
+use crate::libs::satellite_export_service::{self, ExportFormat, ExportOptions, SatelliteTrack};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_DURATION_MINUTES: f64 = 90.0;
+const DEFAULT_STEP_SECONDS: f64 = 30.0;
+
+fn options_from_flags(flags: &HashMap<String, String>) -> Result<ExportOptions, String> {
+    Ok(ExportOptions {
+        start: flags
+            .get("start")
+            .map_or(Ok(Utc::now()), |text| utils::parse_time(text))?,
+        duration: Duration::milliseconds(
+            (utils::number_flag(flags, "duration", DEFAULT_DURATION_MINUTES)? * 60000.0) as i64,
+        ),
+        step: Duration::milliseconds(
+            (utils::number_flag(flags, "step", DEFAULT_STEP_SECONDS)? * 1000.0) as i64,
+        ),
+        include_position: flags.contains_key("position"),
+        include_footprint: flags.contains_key("footprint"),
+    })
+}
+
+fn build_tracks(
+    satellites: &[Satellite],
+    options: &ExportOptions,
+) -> Result<Vec<SatelliteTrack>, String> {
+    satellites
+        .iter()
+        .map(|satellite| {
+            let elements =
+                utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+            satellite_export_service::build_track(&elements, options)
+                .map_err(|e| format!("Error propagating {}: {}", satellite.name, e))
+        })
+        .collect()
+}
+
+// Non-interactive mode: `cosmos_cli export 25544,20580 --format kml --duration 180 --output iss.kml`
+pub fn export_command(args: &[String]) -> Result<(), String> {
+    let identifiers = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("Missing NORAD IDs or COSPAR designators (comma separated)")?;
+    let flags = utils::parse_flags(&args[1..])?;
+    let format = ExportFormat::parse(flags.get("format").map_or("geojson", String::as_str))?;
+    let options = options_from_flags(&flags)?;
+
+    let satellites = utils::resolve_satellites(identifiers)?;
+
+    let output =
+        satellite_export_service::export_tracks(&build_tracks(&satellites, &options)?, format);
+    match flags.get("output") {
+        Some(path) => {
+            fs::write(path, output).map_err(|e| format!("Error writing {}: {}", path, e))?;
+            eprintln!("Exported {} ground tracks to {}", satellites.len(), path);
+        }
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
+
+pub fn export_ui(satellite: &Satellite) {
+    let formats = &["GeoJSON", "KML", "GPX"];
+    let format = match Select::with_theme(&theme::prompt_theme())
+        .with_prompt("Export format")
+        .default(0)
+        .items(&formats[..])
+        .interact()
+        .unwrap()
+    {
+        0 => ExportFormat::GeoJson,
+        1 => ExportFormat::Kml,
+        _ => ExportFormat::Gpx,
+    };
+
+    let minutes: f64 = Input::new()
+        .with_prompt("Duration (minutes)")
+        .default(DEFAULT_DURATION_MINUTES)
+        .interact_text()
+        .unwrap();
+    let step: f64 = Input::new()
+        .with_prompt("Step (seconds)")
+        .default(DEFAULT_STEP_SECONDS)
+        .interact_text()
+        .unwrap();
+    let extras = &[
+        "Track only",
+        "Track and current position",
+        "Track, position and footprint",
+    ];
+    let extra = Select::with_theme(&theme::prompt_theme())
+        .with_prompt("Include")
+        .default(0)
+        .items(&extras[..])
+        .interact()
+        .unwrap();
+    let path: String = Input::new()
+        .with_prompt("Export to")
+        .default(format!(
+            "{}_ground_track.{}",
+            satellite.id,
+            format.extension()
+        ))
+        .interact_text()
+        .unwrap();
+
+    let options = ExportOptions {
+        start: Utc::now(),
+        duration: Duration::milliseconds((minutes * 60000.0) as i64),
+        step: Duration::milliseconds((step * 1000.0) as i64),
+        include_position: extra >= 1,
+        include_footprint: extra == 2,
+    };
+
+    let result = build_tracks(std::slice::from_ref(satellite), &options).and_then(|tracks| {
+        let output = satellite_export_service::export_tracks(&tracks, format);
+        fs::write(&path, output).map_err(|e| format!("Error writing {}: {}", path, e))?;
+        Ok(tracks[0].points.len())
+    });
+
+    match result {
+        Ok(count) => println!("Exported {} track points to {}", count, path),
+        Err(err) => eprintln!("{}", err),
+    }
+}
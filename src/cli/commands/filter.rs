@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::process;
+
+use chrono::Utc;
+use dialoguer::{console::Term, Input, Select};
+use regex::Regex;
+
+use crate::cli::{theme, utils};
+use crate::libs::satellite_catalog_service;
+use crate::libs::satellite_filter_service::{self, CatalogFilter};
+
+use super::info::info_ui;
+
+// Flag name and prompt for every supported filter, in the order they are asked for
+const FILTER_FIELDS: [(&str, &str); 12] = [
+    ("inclination", "Inclination range in degrees (e.g. 97..99)"),
+    ("altitude", "Altitude range in km (e.g. 400..600)"),
+    ("period", "Period range in minutes (e.g. ..128)"),
+    ("eccentricity", "Eccentricity range (e.g. ..0.01)"),
+    (
+        "regime",
+        "Orbit regimes (leo, sso, meo, geo, heo, molniya, other)",
+    ),
+    ("max-age", "Maximum TLE epoch age in days"),
+    ("launch-year", "Launch year range (e.g. 2020..2023)"),
+    ("name", "Name regex (e.g. ^STARLINK)"),
+    (
+        "object-type",
+        "Object types (payload, rocket-body, debris, unknown)",
+    ),
+    ("owner", "Owners as SATCAT codes (e.g. US,PRC)"),
+    (
+        "status",
+        "Operational statuses (operational, nonoperational, partial, backup, spare, extended, decayed, unknown)",
+    ),
+    ("rcs", "RCS sizes (small, medium, large)"),
+];
+
+pub fn filter_from_flags(flags: &HashMap<String, String>) -> Result<CatalogFilter, String> {
+    let float_range = |key: &str| {
+        flags
+            .get(key)
+            .map(|text| satellite_filter_service::parse_range(text, f64::MIN, f64::MAX))
+            .transpose()
+    };
+
+    fn list<T>(
+        flags: &HashMap<String, String>,
+        key: &str,
+        parse: fn(&str) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        flags
+            .get(key)
+            .map(|text| text.split(',').map(parse).collect::<Result<Vec<_>, _>>())
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    Ok(CatalogFilter {
+        inclination: float_range("inclination")?,
+        altitude_km: float_range("altitude")?,
+        period_minutes: float_range("period")?,
+        eccentricity: float_range("eccentricity")?,
+        regimes: list(flags, "regime", satellite_filter_service::parse_regime)?,
+        max_epoch_age_days: flags
+            .get("max-age")
+            .map(|text| {
+                text.parse::<f64>()
+                    .map_err(|_| format!("Invalid maximum age '{}'", text))
+            })
+            .transpose()?,
+        launch_year: flags
+            .get("launch-year")
+            .map(|text| satellite_filter_service::parse_range(text, i32::MIN, i32::MAX))
+            .transpose()?,
+        name: flags
+            .get("name")
+            .map(|text| Regex::new(text).map_err(|e| format!("Invalid name regex: {}", e)))
+            .transpose()?,
+        object_types: list(
+            flags,
+            "object-type",
+            satellite_filter_service::parse_object_type,
+        )?,
+        owners: list(flags, "owner", |owner| Ok(owner.trim().to_string()))?,
+        ops_statuses: list(flags, "status", satellite_filter_service::parse_ops_status)?,
+        rcs_sizes: list(flags, "rcs", satellite_filter_service::parse_rcs_size)?,
+    })
+}
+
+// Non-interactive mode: `cosmos_cli filter --inclination 97..99 --regime sso`
+pub fn filter_command(args: &[String]) -> Result<(), String> {
+    let flags = utils::parse_flags(args)?;
+    let filter = filter_from_flags(&flags)?;
+
+    let catalog = utils::parse_catalog(satellite_catalog_service::get_catalog());
+    let matches = satellite_filter_service::filter_catalog(&catalog, &filter, &Utc::now());
+
+    utils::print_catalog_table(&matches);
+    println!("\n{} of {} objects match", matches.len(), catalog.len());
+    Ok(())
+}
+
+fn prompt_filter() -> CatalogFilter {
+    loop {
+        let mut flags = HashMap::new();
+        for (key, prompt) in FILTER_FIELDS {
+            let value: String = Input::new()
+                .with_prompt(format!("{} (leave empty to skip)", prompt))
+                .allow_empty(true)
+                .interact_text()
+                .unwrap();
+            if !value.trim().is_empty() {
+                flags.insert(key.to_string(), value.trim().to_string());
+            }
+        }
+
+        match filter_from_flags(&flags) {
+            Ok(filter) => return filter,
+            Err(err) => eprintln!("{}\n", err),
+        }
+    }
+}
+
+pub fn filter_ui() {
+    let filter = prompt_filter();
+
+    let pb = utils::display_spinner("Loading catalog...".to_string(), None);
+    let catalog = utils::parse_catalog(satellite_catalog_service::get_catalog());
+    let matches = satellite_filter_service::filter_catalog(&catalog, &filter, &Utc::now());
+    pb.finish_and_clear();
+
+    if matches.is_empty() {
+        println!("No satellites match the filter.");
+        utils::wait_for_enter();
+        return;
+    }
+
+    loop {
+        clearscreen::clear().unwrap();
+
+        let mut selections: Vec<String> = matches
+            .iter()
+            .map(|entry| format!("{} ({})", entry.satellite.name, entry.satellite.id))
+            .collect();
+        selections.push("Cancel".to_string());
+
+        let selection = Select::with_theme(&theme::prompt_theme())
+            .with_prompt(format!(
+                "{} of {} objects match, select one to get more information",
+                matches.len(),
+                catalog.len()
+            ))
+            .default(0)
+            .max_length(20)
+            .items(&selections)
+            .interact_on_opt(&Term::stderr());
+
+        match selection.unwrap_or_else(|_| process::exit(1)) {
+            Some(index) if index < matches.len() => info_ui(&matches[index].satellite),
+            _ => return,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::process;
+
+use dialoguer::{Input, Select};
+
+use crate::cli::{theme, utils};
+use crate::libs::satellite_history_service::{self, TimelinePoint};
+use crate::libs::satellite_search_service::Satellite;
+
+const CHART_WIDTH: usize = 60;
+const CHART_HEIGHT: usize = 8;
+
+type Series = (&'static str, fn(&TimelinePoint) -> f64);
+
+fn print_timeline(timeline: &[TimelinePoint]) {
+    let first_epoch = timeline[0].epoch;
+    let days = |point: &TimelinePoint| (point.epoch - first_epoch).num_seconds() as f64 / 86400.0;
+
+    let series: [Series; 6] = [
+        ("Inclination (deg)", |p| p.inclination),
+        ("Eccentricity", |p| p.eccentricity),
+        ("Mean Motion (revs/day)", |p| p.mean_motion),
+        ("Perigee Altitude (km)", |p| p.perigee_altitude_km),
+        ("Apogee Altitude (km)", |p| p.apogee_altitude_km),
+        ("BSTAR (1/earth radii)", |p| p.bstar),
+    ];
+
+    for (title, value) in series {
+        let points: Vec<(f64, f64)> = timeline.iter().map(|p| (days(p), value(p))).collect();
+        utils::print_ascii_chart(title, &points, CHART_WIDTH, CHART_HEIGHT);
+        println!();
+    }
+}
+
+fn export_csv(satellite: &Satellite, timeline: &[TimelinePoint]) {
+    let path: String = Input::new()
+        .with_prompt("Export to")
+        .default(format!("{}_tle_history.csv", satellite.id))
+        .interact_text()
+        .unwrap();
+
+    match fs::write(&path, satellite_history_service::timeline_to_csv(timeline)) {
+        Ok(_) => println!("Exported {} element sets to {}", timeline.len(), path),
+        Err(err) => eprintln!("Error writing {}: {}", path, err),
+    }
+}
+
+pub fn history_ui(satellite: &Satellite) {
+    let timeline = satellite_history_service::build_timeline(&utils::history_elements(satellite));
+
+    clearscreen::clear().unwrap();
+    if timeline.is_empty() {
+        println!("No stored TLE history for {}", satellite.name);
+        return;
+    }
+
+    println!(
+        "{} element sets for {} from {} to {}\n",
+        timeline.len(),
+        satellite.name,
+        timeline[0].epoch.format("%Y-%m-%d %H:%M"),
+        timeline[timeline.len() - 1].epoch.format("%Y-%m-%d %H:%M")
+    );
+    print_timeline(&timeline);
+
+    let items = &["Export CSV", "Return"];
+    let selection = Select::with_theme(&theme::prompt_theme())
+        .default(0)
+        .items(&items[..])
+        .interact()
+        .unwrap_or_else(|_| {
+            eprintln!("Failed to interact with user");
+            process::exit(1);
+        });
+
+    if selection == 0 {
+        export_csv(satellite, &timeline);
+    }
+}
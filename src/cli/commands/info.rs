@@ -1,32 +1,110 @@
 use crate::libs::satellite_search_service::Satellite;
-use crate::{cli::utils, libs::satellite_position_service};
-use dialoguer::{theme::ColorfulTheme, Select};
+use crate::{
+    cli::{theme, utils},
+    libs::satellite_position_service,
+};
+use dialoguer::Select;
 use std::process;
 
+use super::beta::beta_ui;
+use super::conjunction::conjunction_ui;
+use super::decay::decay_ui;
+use super::eclipse::eclipse_ui;
+use super::ephemeris::ephemeris_ui;
+use super::export::export_ui;
+use super::history::history_ui;
+use super::maneuver::maneuver_ui;
 use super::map::map_ui;
+use super::oem::oem_ui;
+use super::passes::passes_ui;
+use super::radio::radio_ui;
+use super::rotator::rotator_ui;
+use super::sky::sky_ui;
 
 pub fn info_ui(satellite: &Satellite) {
     let pb = utils::display_spinner("Fetching satellite position".to_string(), None);
     let (lat, lon) = satellite_position_service::get_satellite_position(satellite.id);
 
     pb.finish_and_clear();
-    clearscreen::clear().unwrap();
 
-    utils::print_satellite_info(&satellite, lat, lon);
+    loop {
+        clearscreen::clear().unwrap();
 
-    let items = &["View Map", "Return"];
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .default(0)
-        .items(&items[..])
-        .interact()
-        .unwrap_or_else(|_| {
-            eprintln!("Failed to interact with user");
-            process::exit(1);
-        });
+        utils::print_satellite_info(satellite, lat, lon);
 
-    match selection {
-        0 => map_ui(satellite.id, lat, lon), // Assuming map_ui is a function that takes a satellite and displays its map
-        1 => return,
-        _ => unreachable!(),
+        let items = &[
+            "View Map",
+            "Sky Plot",
+            "Screen Conjunctions",
+            "Decay Estimate",
+            "TLE History",
+            "Detect Maneuvers",
+            "Export Ground Track",
+            "Ephemeris Table",
+            "Export CCSDS OEM",
+            "Eclipse Timeline",
+            "Beta Angle",
+            "Pass Calendar",
+            "Track with Rotator",
+            "Doppler Tuning",
+            "Return",
+        ];
+        let selection = Select::with_theme(&theme::prompt_theme())
+            .default(0)
+            .items(&items[..])
+            .interact()
+            .unwrap_or_else(|_| {
+                eprintln!("Failed to interact with user");
+                process::exit(1);
+            });
+
+        match selection {
+            0 => map_ui(satellite),
+            1 => sky_ui(satellite), // Waits for Enter itself, it also stops the live plot
+            2 => {
+                conjunction_ui(satellite);
+                utils::wait_for_enter();
+            }
+            3 => {
+                decay_ui(satellite);
+                utils::wait_for_enter();
+            }
+            4 => {
+                history_ui(satellite);
+                utils::wait_for_enter();
+            }
+            5 => {
+                maneuver_ui(satellite);
+                utils::wait_for_enter();
+            }
+            6 => {
+                export_ui(satellite);
+                utils::wait_for_enter();
+            }
+            7 => {
+                ephemeris_ui(satellite);
+                utils::wait_for_enter();
+            }
+            8 => {
+                oem_ui(satellite);
+                utils::wait_for_enter();
+            }
+            9 => {
+                eclipse_ui(satellite);
+                utils::wait_for_enter();
+            }
+            10 => {
+                beta_ui(satellite);
+                utils::wait_for_enter();
+            }
+            11 => {
+                passes_ui(satellite);
+                utils::wait_for_enter();
+            }
+            12 => rotator_ui(satellite), // Waits for Enter itself, it also stops tracking
+            13 => radio_ui(satellite),   // Waits for Enter itself, it also stops tuning
+            14 => return,
+            _ => unreachable!(),
+        }
     }
 }
@@ -1,9 +1,9 @@
 use std::process;
 
-use dialoguer::{console::Term, theme::ColorfulTheme, Select};
+use dialoguer::{console::Term, Select};
 
 use crate::{
-    cli::utils,
+    cli::{theme, utils},
     libs::satellite_search_service::{self, Satellite},
 };
 
@@ -48,9 +48,7 @@ pub fn list_ui() {
         }
         selections.push("Cancel".to_string());
 
-        let theme = ColorfulTheme {
-            ..ColorfulTheme::default()
-        };
+        let theme = theme::prompt_theme();
 
         let selection = Select::with_theme(&theme)
             .with_prompt("Select a satellite to get more information")
@@ -1,13 +1,102 @@
-use dialoguer::{theme::ColorfulTheme, Select};
+use std::process;
 
-use super::{list::list_ui, search::search_ui};
+use dialoguer::Select;
+
+use super::{
+    beta::beta_command,
+    eclipse::eclipse_command,
+    ephemeris::ephemeris_command,
+    export::export_command,
+    filter::{filter_command, filter_ui},
+    list::list_ui,
+    oem::oem_command,
+    passes::passes_command,
+    query::{query_command, query_ui},
+    radio::radio_command,
+    rotator::rotator_command,
+    search::{lookup_ui, search_ui},
+    serve::serve_command,
+    sky::sky_command,
+    watch::watch_command,
+};
+use crate::cli::theme;
+use crate::libs::satellite_satcat_service;
+use crate::libs::satellite_search_service::parse_identifier;
+
+const USAGE: &str = "Usage: cosmos_cli [COMMAND] [OPTIONS]
+
+Runs the interactive menu when no command is given.
+
+Commands:
+  info      Open the info view of a satellite by NORAD ID or COSPAR designator
+            (e.g. 25544 or 1998-067A); `cosmos_cli 25544` works as well
+  filter    List catalog objects matching the given filters
+            --inclination MIN..MAX  --altitude MIN..MAX  --period MIN..MAX
+            --eccentricity MIN..MAX  --regime leo,sso,...  --max-age DAYS
+            --launch-year MIN..MAX  --name REGEX  --object-type payload,debris,...
+            --owner US,PRC,...  --status operational,...  --rcs small,medium,large
+  query     Run a catalog query, e.g.
+            \"select name, period where inclination > 97 and perigee_alt < 600
+            order by period desc limit 20\" [--format table|csv]
+  ephemeris Print or write an ephemeris table for one satellite
+            --start TIME  --duration MINUTES  --step SECONDS  --frame teme|ecef
+            --observer LAT,LON[,ALT_M]  --format table|csv|json  --output FILE
+  eclipse   List sunlit, penumbra and umbra periods of a satellite (Earth and Moon shadows)
+            --start TIME  --duration MINUTES (default 1440)  --format table|csv
+  beta      Beta angle and eclipse minutes per orbit over a date range, with charts
+            --start TIME  --days N (default 365)  --step DAYS  --format table|csv
+  export    Export ground tracks of one or more satellites (comma separated IDs)
+            --format geojson|kml|gpx  --start TIME  --duration MINUTES
+            --step SECONDS  --position  --footprint  --output FILE
+  oem       Write a CCSDS Orbit Ephemeris Message for one satellite
+            --frame eme2000|teme|itrf  --format kvn|xml  --start TIME
+            --stop TIME | --duration MINUTES  --step SECONDS
+            --interpolation lagrange|hermite|none  --degree N  --output FILE
+            `oem validate FILE` parses and checks an existing OEM
+  passes    Predict passes of one or more satellites (comma separated IDs)
+            --observer \"NAME=LAT,LON[,ALT_M];...\"  --days N  --horizon DEG
+            --format table|ics  --name CALENDAR_NAME  --output FILE
+  sky       Polar sky plot of the next pass with time ticks, AOS and LOS
+            --observer LAT,LON[,ALT_M]  --start TIME  --horizon DEG
+            --live (redraw with the current position until Enter)
+  rotator   Track the next passes of a satellite with a Hamlib rotctld rotator
+            --rotctld HOST:PORT  --observer LAT,LON[,ALT_M]  --min-elevation DEG
+            --max-elevation DEG (180 enables flips)  --min-azimuth DEG
+            --max-azimuth DEG (e.g. 450 for overlap)  --no-flip  --park AZ,EL
+            --pre-position SECONDS  --interval SECONDS  --tolerance DEG
+            --horizon DEG  --passes N
+            `rotator fake [--port 4533]` runs a stand-in rotctld that logs commands
+  radio     Tune a rig through Hamlib rigctld for the Doppler shift during passes
+            --downlink FREQ  --uplink FREQ  --inverting  --offset FREQ
+            --downlink-vfo VFO  --uplink-vfo VFO  --rigctld HOST:PORT
+            --observer LAT,LON[,ALT_M]  --interval SECONDS  --tolerance HZ
+            --horizon DEG  --passes N  (frequencies in Hz or e.g. 145.8M)
+            `radio fake [--port 4532]` runs a stand-in rigctld that logs commands
+  serve     Serve search, TLEs, positions, ground tracks and passes as JSON over HTTP
+            --address HOST:PORT (default 127.0.0.1:8080)  --threads N
+            --cache SECONDS (response cache lifetime, default 60)
+  watch     Alert before AOS and at AOS, TCA and LOS of the passes of a watchlist
+            (comma separated IDs, default COSMOS_WATCHLIST) until stopped
+            --observer \"NAME=LAT,LON[,ALT_M];...\"  --before MINUTES  --horizon DEG
+            --days N (prediction window)  --refresh HOURS (TLE refresh)
+            --exec CMD  --on-warning|--on-aos|--on-tca|--on-los CMD
+            --format text|ndjson  (hooks get COSMOS_EVENT, COSMOS_SATELLITE, ...)
+  satcat    Import SATCAT metadata (CelesTrak or Space-Track CSV/JSON):
+            satcat import FILE
+  help      Show this message";
 
 pub fn main_menu() {
     clearscreen::clear().unwrap(); // Clear the screen before displaying the menu
 
     loop {
-        let items = &["Search Satellites", "View List", "Exit"];
-        let selection = Select::with_theme(&ColorfulTheme::default())
+        let items = &[
+            "Search Satellites",
+            "View List",
+            "Filter Catalog",
+            "Query Catalog",
+            "Exit",
+        ];
+        let selection = Select::with_theme(&theme::prompt_theme())
             .with_prompt("Please select an option")
             .default(0)
             .items(&items[..])
@@ -23,8 +112,73 @@ pub fn main_menu() {
                 list_ui();
                 clearscreen::clear().unwrap();
             }
-            2 => break, // Exit the loop, thus exiting the program
+            2 => {
+                filter_ui();
+                clearscreen::clear().unwrap();
+            }
+            3 => {
+                query_ui();
+                clearscreen::clear().unwrap();
+            }
+            4 => break, // Exit the loop, thus exiting the program
             _ => unreachable!(),
         }
     }
 }
+
+fn info_command(args: &[String]) -> Result<(), String> {
+    let text = args
+        .first()
+        .ok_or("Missing NORAD ID or COSPAR designator")?;
+    let identifier = parse_identifier(text)
+        .ok_or_else(|| format!("'{}' is neither a NORAD ID nor a COSPAR designator", text))?;
+
+    if lookup_ui(&identifier) {
+        Ok(())
+    } else {
+        Err(format!("No satellite found for {}", identifier))
+    }
+}
+
+fn satcat_command(args: &[String]) -> Result<(), String> {
+    match args {
+        [action, path] if action == "import" => {
+            let count = satellite_satcat_service::import_satcat(path)?;
+            println!("Imported {} SATCAT records", count);
+            Ok(())
+        }
+        _ => Err("Usage: cosmos_cli satcat import FILE".to_string()),
+    }
+}
+
+// Non-interactive entry point, dispatches on the first command line argument
+pub fn run_command(args: &[String]) {
+    let result = match args[0].as_str() {
+        "info" => info_command(&args[1..]),
+        "filter" => filter_command(&args[1..]),
+        "query" => query_command(&args[1..]),
+        "ephemeris" => ephemeris_command(&args[1..]),
+        "eclipse" => eclipse_command(&args[1..]),
+        "beta" => beta_command(&args[1..]),
+        "export" => export_command(&args[1..]),
+        "oem" => oem_command(&args[1..]),
+        "passes" => passes_command(&args[1..]),
+        "sky" => sky_command(&args[1..]),
+        "rotator" => rotator_command(&args[1..]),
+        "radio" => radio_command(&args[1..]),
+        "serve" => serve_command(&args[1..]),
+        "watch" => watch_command(&args[1..]),
+        "satcat" => satcat_command(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other if parse_identifier(other).is_some() => info_command(args),
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
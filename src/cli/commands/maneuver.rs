@@ -0,0 +1,29 @@
+use crate::cli::utils;
+use crate::libs::satellite_maneuver_service;
+use crate::libs::satellite_search_service::Satellite;
+
+pub fn maneuver_ui(satellite: &Satellite) {
+    let history = utils::history_elements(satellite);
+
+    clearscreen::clear().unwrap();
+    if history.len() < 2 {
+        println!(
+            "At least two stored TLEs are needed to detect maneuvers of {}",
+            satellite.name
+        );
+        return;
+    }
+
+    let maneuvers = satellite_maneuver_service::detect_maneuvers(&history);
+    println!(
+        "Checked {} element sets of {}\n",
+        history.len(),
+        satellite.name
+    );
+
+    if maneuvers.is_empty() {
+        println!("No maneuvers detected");
+    } else {
+        utils::print_maneuvers(&maneuvers);
+    }
+}
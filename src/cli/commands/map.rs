@@ -1,102 +1,288 @@
-use crate::{cli::utils, libs::satellite_position_service};
-use crossterm::style::{Color, Stylize};
-// Import colored crate for coloring text
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+};
+
+use chrono::Duration;
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{Print, ResetColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType};
 use dialoguer::console::Term; // Import Dialoguer for enhanced UI
 
-// Function to mark a position with '∆' on the ASCII map without coloring it
-fn mark_position(image_ascii: &mut String, x: u32, y: u32, width: u32) {
-    let pos = (y * (width + 1) + x) as usize; // +1 accounts for newlines in the ASCII image
-    let marker = "∆";
-    image_ascii.replace_range(pos..pos + 1, marker);
+use crate::cli::theme::{self, Role};
+use crate::cli::utils;
+use crate::libs::satellite_search_service::Satellite;
+use crate::libs::satellite_tracking_service::{self, Tracker};
+use crate::libs::world_map::{self, Projection};
+
+// Used when the terminal size is unknown or too small to draw a map in
+const DEFAULT_WIDTH: u32 = 100;
+const DEFAULT_HEIGHT: u32 = 50;
+const MIN_WIDTH: u32 = 20;
+const MIN_HEIGHT: u32 = 10;
+const REFRESH_INTERVAL: i64 = 30; // Refresh interval in seconds
+const MAX_TRAIL: usize = 200;
+const PROMPT: &str = "Press Enter to return...";
+
+// Marker and orbit trail over a muted map, in the colors of the theme
+const MAP_ROLE: Role = Role::Muted;
+const TRAIL: Cell = Cell {
+    glyph: 'o',
+    role: Role::Path,
+};
+const MARKER: Cell = Cell {
+    glyph: '∆',
+    role: Role::Marker,
+};
+
+// Base maps by projection, width and height
+type BaseMapCache = Mutex<HashMap<(Projection, u32, u32), Arc<MapGrid>>>;
+
+// Base maps are drawn once per projection and size, then reused by every refresh and visit
+static BASE_MAPS: OnceLock<BaseMapCache> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    glyph: char,
+    role: Role,
 }
 
-fn mark_orbit_trail(image_ascii: &mut String, orbit_trail: &Vec<(u32, u32)>, width: u32) {
-    for (x, y) in orbit_trail {
-        let pos = (*y * (width + 1) + *x) as usize; // +1 accounts for newlines in the ASCII image
-        let marker = "o";
-        image_ascii.replace_range(pos..pos + 1, marker);
-    }
+// The map as rows of cells, so overlays replace whole characters whatever their width in bytes
+#[derive(Debug, Clone)]
+struct MapGrid {
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
 }
 
-// New function to colorize the map, including the marked position and orbit trail
-fn colorize_map(image_ascii: &mut String) {
-    let colored_map = image_ascii
-        .chars()
-        .map(|c| {
-            if c == '∆' {
-                // Assuming '∆' is the marker character
-                c.to_string().with(Color::Magenta).to_string() // Apply blue color to marker
-            } else if c == 'o' {
-                c.to_string().with(Color::Cyan).to_string() // Apply dark cyan color to orbit trail
-            } else {
-                c.to_string().with(Color::DarkGrey).to_string() // Apply dark grey color to the rest of the map
+impl MapGrid {
+    // Rows past the size are cut off and missing ones are left blank
+    fn from_ascii(text: &str, width: u32, height: u32) -> MapGrid {
+        let mut grid = MapGrid {
+            width,
+            height,
+            cells: vec![
+                Cell {
+                    glyph: ' ',
+                    role: MAP_ROLE,
+                };
+                (width * height) as usize
+            ],
+        };
+        for (y, line) in text.lines().enumerate() {
+            for (x, glyph) in line.chars().enumerate() {
+                grid.set(
+                    x as u32,
+                    y as u32,
+                    Cell {
+                        glyph,
+                        role: MAP_ROLE,
+                    },
+                );
             }
-        })
-        .collect::<Vec<_>>()
-        .join("");
-    *image_ascii = colored_map;
+        }
+        grid
+    }
+
+    fn set(&mut self, x: u32, y: u32, cell: Cell) {
+        if x < self.width && y < self.height {
+            self.cells[(y * self.width + x) as usize] = cell;
+        }
+    }
+
+    fn same_size(&self, other: &MapGrid) -> bool {
+        self.width == other.width && self.height == other.height
+    }
 }
 
-use std::{
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
-    thread,
-    time::Duration,
-};
+// `COSMOS_MAP_PROJECTION` picks the projection, equirectangular by default
+fn map_projection() -> Result<Projection, String> {
+    match env::var("COSMOS_MAP_PROJECTION") {
+        Ok(text) => Projection::parse(&text).ok_or_else(|| {
+            format!(
+                "Unknown map projection '{}' (expected equirectangular, mercator or miller)",
+                text
+            )
+        }),
+        Err(_) => Ok(Projection::Equirectangular),
+    }
+}
 
-pub fn map_ui(satellite_id: i32, lat: f64, lon: f64) {
-    const IMAGE_SRC: &str = "assets/world_map.jpeg";
-    const HEIGHT: u32 = 50;
-    const WIDTH: u32 = 100;
-    const REFRESH_INTERVAL: u64 = 30; // Refresh interval in seconds
-    let mut orbit_trail: Vec<(u32, u32)> = Vec::new();
+// The map fills the terminal, leaving a blank line and the prompt below it
+fn map_size() -> (u32, u32) {
+    match terminal::size() {
+        Ok((columns, rows)) if columns as u32 >= MIN_WIDTH && rows as u32 >= MIN_HEIGHT + 2 => {
+            (columns as u32, rows as u32 - 2)
+        }
+        _ => (DEFAULT_WIDTH, DEFAULT_HEIGHT),
+    }
+}
 
-    let running = Arc::new(AtomicBool::new(true));
-    let running_clone = Arc::clone(&running);
+// The embedded outlines, or the image at `COSMOS_MAP_IMAGE` stretched over the whole map (it has
+// to be drawn in the same projection for the satellite to land in the right place)
+fn base_map(projection: Projection, width: u32, height: u32) -> Result<Arc<MapGrid>, String> {
+    let cache = BASE_MAPS.get_or_init(Default::default);
+    if let Some(base) = cache.lock().unwrap().get(&(projection, width, height)) {
+        return Ok(Arc::clone(base));
+    }
 
-    thread::spawn(move || {
-        // Initial display without waiting
-        let mut image_ascii = utils::convert_image_to_ascii(IMAGE_SRC, WIDTH, HEIGHT);
+    let text = match env::var("COSMOS_MAP_IMAGE") {
+        Ok(path) => utils::convert_image_to_ascii(&path, width, height)?,
+        Err(_) => world_map::render_outlines(projection, width, height),
+    };
+    let base = Arc::new(MapGrid::from_ascii(&text, width, height));
+    cache
+        .lock()
+        .unwrap()
+        .insert((projection, width, height), Arc::clone(&base));
+    Ok(base)
+}
+
+// Queues cells in screen order, moving the cursor only past gaps and switching colors only
+// where they change (never when the theme has no colors)
+fn queue_cells(stdout: &mut io::Stdout, cells: &[(u32, u32, Cell)], width: u32) -> io::Result<()> {
+    let mut role = None;
+    let mut cursor = None;
+    for &(x, y, cell) in cells {
+        if cursor != Some((x, y)) {
+            queue!(stdout, MoveTo(x as u16, y as u16))?;
+        }
+        if role != Some(cell.role) {
+            if let Some(color) = theme::color(cell.role) {
+                queue!(stdout, SetForegroundColor(color))?;
+            }
+            role = Some(cell.role);
+        }
+        queue!(stdout, Print(cell.glyph))?;
+        cursor = (x + 1 < width).then_some((x + 1, y));
+    }
+    Ok(())
+}
 
-        let (x, y) = lat_long_to_ascii_coords(lat, lon, WIDTH, HEIGHT);
-        mark_position(&mut image_ascii, x, y, WIDTH); // Mark position first
-        colorize_map(&mut image_ascii); // Then colorize the entire map
-        orbit_trail.push((x, y));
+// Redraws the cells that differ from the frame on screen, or everything when there is none of
+// the same size (first draw, or the terminal was resized)
+fn draw_frame(frame: &MapGrid, shown: Option<&MapGrid>) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let position = |i: usize, cell: &Cell| (i as u32 % frame.width, i as u32 / frame.width, *cell);
 
-        clearscreen::clear().unwrap();
-        let term = Term::stdout();
+    match shown.filter(|shown| shown.same_size(frame)) {
+        Some(shown) => {
+            let changed = frame
+                .cells
+                .iter()
+                .zip(&shown.cells)
+                .enumerate()
+                .filter(|(_, (cell, old))| cell != old)
+                .map(|(i, (cell, _))| position(i, cell))
+                .collect::<Vec<_>>();
+            queue_cells(&mut stdout, &changed, frame.width)?;
+        }
+        None => {
+            queue!(stdout, MoveTo(0, 0), Clear(ClearType::All))?;
+            let cells = frame
+                .cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| position(i, cell))
+                .collect::<Vec<_>>();
+            queue_cells(&mut stdout, &cells, frame.width)?;
+            queue!(
+                stdout,
+                ResetColor,
+                MoveTo(0, frame.height as u16 + 1),
+                Print(PROMPT)
+            )?;
+        }
+    }
 
-        term.write_line(&image_ascii).unwrap();
-        term.write_line("\nPress Enter to return...").unwrap();
+    // Leave the cursor after the prompt, where Enter is typed
+    queue!(
+        stdout,
+        ResetColor,
+        MoveTo(PROMPT.len() as u16, frame.height as u16 + 1)
+    )?;
+    stdout.flush()
+}
+
+pub fn map_ui(satellite: &Satellite) {
+    // Positions as latitude / longitude, so the trail follows the map when the terminal is resized
+    let mut orbit_trail: Vec<(f64, f64)> = Vec::new();
+    let mut shown: Option<MapGrid> = None;
 
-        while running.load(Ordering::SeqCst) {
-            thread::sleep(Duration::from_secs(REFRESH_INTERVAL)); // Wait for the initial data to become stale
+    let setup = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)
+        .and_then(|elements| Tracker::new(vec![(satellite.clone(), elements)], None))
+        .and_then(|tracker| {
+            let projection = map_projection()?;
+            let (width, height) = map_size();
+            base_map(projection, width, height)?; // Fail before drawing anything if it can't be made
+            Ok((tracker, projection))
+        });
+    let (tracker, projection) = match setup {
+        Ok(setup) => setup,
+        Err(err) => {
+            eprintln!("{}", err);
+            utils::wait_for_enter();
+            return;
+        }
+    };
 
-            // Fetch new data here, assuming fetch_lat_lon() is implemented
-            let (new_lat, new_lon) =
-                satellite_position_service::get_satellite_position(satellite_id); // Replace with actual fetching logic
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
 
-            let mut image_ascii = utils::convert_image_to_ascii(IMAGE_SRC, WIDTH, HEIGHT);
-            let (x, y) = lat_long_to_ascii_coords(new_lat, new_lon, WIDTH, HEIGHT);
+    thread::spawn(move || {
+        // Same loop as the position stream of `serve`, the first update is drawn right away
+        satellite_tracking_service::run_tracking_loop(
+            &tracker,
+            Duration::seconds(REFRESH_INTERVAL),
+            &running,
+            |updates| {
+                let Some(update) = updates.first() else {
+                    return true; // Keep the last map if propagation failed
+                };
 
-            orbit_trail.push((x, y)); // Store the new position in the orbit trail
+                // Drain the orbit trail after a certain number of iterations to prevent memory bloat
+                orbit_trail.push((update.position.latitude, update.position.longitude));
+                if orbit_trail.len() > MAX_TRAIL {
+                    orbit_trail.drain(0..MAX_TRAIL / 4);
+                }
 
-            mark_orbit_trail(&mut image_ascii, &orbit_trail, WIDTH); // Draw the orbit trail first
-            mark_position(&mut image_ascii, x, y, WIDTH); // Add the new position to the map
-            colorize_map(&mut image_ascii); // Then colorize the entire map
+                let (width, height) = map_size();
+                let mut frame = match base_map(projection, width, height) {
+                    Ok(base) => (*base).clone(),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        return false;
+                    }
+                };
 
-            clearscreen::clear().unwrap();
-            let term = Term::stdout();
-            term.write_line(&image_ascii).unwrap(); // Print the ASCII map with the blue marker
-            term.write_line("\nPress Enter to return...").unwrap(); // Prompt to continue
+                // Overlays over the cached base, the trail first and the current position on top
+                for (latitude, longitude) in &orbit_trail {
+                    let (x, y) = projection.cell(*latitude, *longitude, width, height);
+                    frame.set(x, y, TRAIL);
+                }
+                let (x, y) = projection.cell(
+                    update.position.latitude,
+                    update.position.longitude,
+                    width,
+                    height,
+                );
+                frame.set(x, y, MARKER);
 
-            // Drain the orbit trail after a certain number of iterations to prevent memory bloat
-            if orbit_trail.len() > (WIDTH * 2) as usize {
-                orbit_trail.drain(0..(WIDTH / 2) as usize);
-            }
-        }
+                if let Err(err) = draw_frame(&frame, shown.as_ref()) {
+                    eprintln!("Error drawing the map: {}", err);
+                    return false;
+                }
+                shown = Some(frame);
+                true
+            },
+        );
     });
 
     // Separate thread for user input
@@ -104,26 +290,3 @@ pub fn map_ui(satellite_id: i32, lat: f64, lon: f64) {
     term.read_line().unwrap(); // Wait for user input to continue
     running_clone.store(false, Ordering::SeqCst);
 }
-
-pub fn lat_long_to_ascii_coords(
-    lat: f64,
-    lon: f64,
-    ascii_width: u32,
-    ascii_height: u32,
-) -> (u32, u32) {
-    // Adjust the normalization process for longitude and latitude
-    // Longitude: Convert from [-180, 180] to [0, 1`
-    let norm_lon = (lon + 180.0) / 360.0;
-    // Latitude: Convert from [-90, 90] to [0, 1], with inversion for correct orientation
-    let norm_lat = (90.0 - lat) / 180.0;
-
-    // Convert to ASCII coordinates
-    let ascii_x = (norm_lon * (ascii_width as f64)).round() as u32;
-    let ascii_y = (norm_lat * (ascii_height as f64)).round() as u32;
-
-    // Ensure the coordinates are within the bounds of the ASCII map
-    let ascii_x = ascii_x.min(ascii_width - 1);
-    let ascii_y = ascii_y.min(ascii_height - 1);
-
-    (ascii_x, ascii_y)
-}
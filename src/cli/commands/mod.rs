@@ -1,5 +1,22 @@
+pub mod beta;
+pub mod conjunction;
+pub mod decay;
+pub mod eclipse;
+pub mod ephemeris;
+pub mod export;
+pub mod filter;
+pub mod history;
 pub mod info;
 pub mod list;
 pub mod main;
+pub mod maneuver;
 pub mod map;
+pub mod oem;
+pub mod passes;
+pub mod query;
+pub mod radio;
+pub mod rotator;
 pub mod search;
+pub mod serve;
+pub mod sky;
+pub mod watch;
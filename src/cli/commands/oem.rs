@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fs;
+
+use chrono::{Duration, Utc};
+use dialoguer::{Input, Select};
+
+use crate::cli::{theme, utils};
+use crate::libs::satellite_oem_service::{self, OemEncoding, OemFrame, OemOptions};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_DURATION_MINUTES: f64 = 1440.0;
+const DEFAULT_STEP_SECONDS: f64 = 60.0;
+const DEFAULT_INTERPOLATION_DEGREE: u32 = 7;
+
+fn options_from_flags(flags: &HashMap<String, String>) -> Result<OemOptions, String> {
+    let start = flags
+        .get("start")
+        .map_or(Ok(Utc::now()), |text| utils::parse_time(text))?;
+    let stop = match flags.get("stop") {
+        Some(text) => utils::parse_time(text)?,
+        None => {
+            start
+                + Duration::milliseconds(
+                    (utils::number_flag(flags, "duration", DEFAULT_DURATION_MINUTES)? * 60000.0)
+                        as i64,
+                )
+        }
+    };
+
+    let interpolation = match flags.get("interpolation").map(String::as_str) {
+        None | Some("none") => None,
+        Some(method) => Some((
+            method.to_string(),
+            utils::number_flag(flags, "degree", DEFAULT_INTERPOLATION_DEGREE as f64)? as u32,
+        )),
+    };
+
+    Ok(OemOptions {
+        start,
+        stop,
+        step_seconds: utils::number_flag(flags, "step", DEFAULT_STEP_SECONDS)?,
+        frame: OemFrame::parse(flags.get("frame").map_or("eme2000", String::as_str))?,
+        interpolation,
+    })
+}
+
+fn generate(
+    satellite: &Satellite,
+    options: &OemOptions,
+    encoding: OemEncoding,
+) -> Result<String, String> {
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let oem = satellite_oem_service::build_oem(&elements, options)?;
+    satellite_oem_service::write_verified_oem(&oem, encoding)
+}
+
+fn validate_command(path: &str) -> Result<(), String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path, e))?;
+    let oem = satellite_oem_service::parse_oem(&contents)?;
+
+    for line in satellite_oem_service::summarize_oem(&oem) {
+        println!("{}", line);
+    }
+
+    let problems = satellite_oem_service::validate_oem(&oem);
+    if problems.is_empty() {
+        println!("{} is a valid OEM", path);
+        Ok(())
+    } else {
+        Err(problems.join("\n"))
+    }
+}
+
+// Non-interactive mode: `cosmos_cli oem 25544 --frame eme2000 --format xml --output iss.xml`
+// or `cosmos_cli oem validate iss.xml`
+pub fn oem_command(args: &[String]) -> Result<(), String> {
+    match args {
+        [action, path] if action == "validate" => return validate_command(path),
+        [first, ..] if !first.starts_with("--") => {}
+        _ => return Err("Missing NORAD ID or COSPAR designator".to_string()),
+    }
+
+    let flags = utils::parse_flags(&args[1..])?;
+    let satellite = utils::resolve_one_satellite(&args[0])?;
+
+    let options = options_from_flags(&flags)?;
+    let encoding = OemEncoding::parse(flags.get("format").map_or("kvn", String::as_str))?;
+    let output = generate(&satellite, &options, encoding)?;
+
+    match flags.get("output") {
+        Some(path) => {
+            fs::write(path, output).map_err(|e| format!("Error writing {}: {}", path, e))?;
+            eprintln!("Wrote OEM to {}", path);
+        }
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
+
+pub fn oem_ui(satellite: &Satellite) {
+    let minutes: f64 = Input::new()
+        .with_prompt("Duration (minutes)")
+        .default(DEFAULT_DURATION_MINUTES)
+        .interact_text()
+        .unwrap();
+    let step: f64 = Input::new()
+        .with_prompt("Step (seconds)")
+        .default(DEFAULT_STEP_SECONDS)
+        .interact_text()
+        .unwrap();
+    let frames = &["EME2000", "TEME", "ITRF"];
+    let frame = Select::with_theme(&theme::prompt_theme())
+        .with_prompt("Reference frame")
+        .default(0)
+        .items(&frames[..])
+        .interact()
+        .unwrap();
+    let encodings = &["KVN", "XML"];
+    let encoding = match Select::with_theme(&theme::prompt_theme())
+        .with_prompt("Encoding")
+        .default(0)
+        .items(&encodings[..])
+        .interact()
+        .unwrap()
+    {
+        0 => OemEncoding::Kvn,
+        _ => OemEncoding::Xml,
+    };
+    let path: String = Input::new()
+        .with_prompt("Export to")
+        .default(format!("{}.{}", satellite.id, encoding.extension()))
+        .interact_text()
+        .unwrap();
+
+    let start = Utc::now();
+    let result = OemFrame::parse(frames[frame]).and_then(|frame| {
+        let options = OemOptions {
+            start,
+            stop: start + Duration::milliseconds((minutes * 60000.0) as i64),
+            step_seconds: step,
+            frame,
+            interpolation: Some(("LAGRANGE".to_string(), DEFAULT_INTERPOLATION_DEGREE)),
+        };
+        generate(satellite, &options, encoding)
+    });
+
+    match result.and_then(|output| {
+        fs::write(&path, output).map_err(|e| format!("Error writing {}: {}", path, e))
+    }) {
+        Ok(_) => println!("Exported OEM to {}", path),
+        Err(err) => eprintln!("{}", err),
+    }
+}
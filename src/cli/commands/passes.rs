@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fs;
+
+use chrono::{Duration, Utc};
+use dialoguer::Input;
+
+use crate::cli::utils;
+use crate::libs::orbit::Propagator;
+use crate::libs::satellite_calendar_service;
+use crate::libs::satellite_pass_service::{self, Station, StationPass};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_DAYS: f64 = 7.0;
+const MAX_DAYS: f64 = 60.0;
+
+fn predict(
+    satellites: &[Satellite],
+    stations: &[Station],
+    days: f64,
+    horizon: f64,
+) -> Result<Vec<StationPass>, String> {
+    let targets = satellites
+        .iter()
+        .map(|satellite| {
+            let elements =
+                utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+            Ok((satellite.clone(), Propagator::new(elements)?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let start = Utc::now();
+    let end = start + Duration::milliseconds((days * 86_400_000.0) as i64);
+    let (passes, errors) =
+        satellite_pass_service::predict_station_passes(&targets, stations, &start, &end, horizon);
+    for err in errors {
+        eprintln!("{}", err);
+    }
+
+    Ok(passes)
+}
+
+fn calendar_name(satellites: &[Satellite]) -> String {
+    let names: Vec<&str> = satellites.iter().map(|s| s.name.as_str()).collect();
+    format!("{} passes", names.join(", "))
+}
+
+// Non-interactive mode: `cosmos_cli passes 25544,43017 --observer "home=52.52,13.40,34;club=48.14,11.58"
+// --days 7 --horizon 10`, with `--format ics --output passes.ics` for a calendar to import
+pub fn passes_command(args: &[String]) -> Result<(), String> {
+    let identifiers = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("Missing NORAD IDs or COSPAR designators (comma separated)")?;
+    let flags = utils::parse_flags(&args[1..])?;
+
+    let stations = utils::stations_from_flags(&flags)?;
+    if stations.is_empty() {
+        return Err(
+            "Predicting passes needs an observer (--observer or COSMOS_OBSERVER)".to_string(),
+        );
+    }
+    let days = utils::number_flag(&flags, "days", DEFAULT_DAYS)?;
+    if days <= 0.0 || days > MAX_DAYS {
+        return Err(format!("--days has to be between 0 and {}", MAX_DAYS));
+    }
+    let horizon = utils::number_flag(&flags, "horizon", 0.0)?;
+    let ics = match flags.get("format").map(String::as_str) {
+        None | Some("table") => false,
+        Some("ics") | Some("ical") => true,
+        Some(other) => return Err(format!("Unknown format '{}' (table or ics)", other)),
+    };
+
+    let satellites = utils::resolve_satellites(identifiers)?;
+    let passes = predict(&satellites, &stations, days, horizon)?;
+
+    if !ics {
+        utils::print_pass_table(&passes);
+        return Ok(());
+    }
+
+    let name = flags
+        .get("name")
+        .cloned()
+        .unwrap_or_else(|| calendar_name(&satellites));
+    let output = satellite_calendar_service::to_icalendar(&passes, &name, &Utc::now());
+    match flags.get("output") {
+        Some(path) => {
+            fs::write(path, output).map_err(|e| format!("Error writing {}: {}", path, e))?;
+            eprintln!("Exported {} passes to {}", passes.len(), path);
+        }
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
+
+pub fn passes_ui(satellite: &Satellite) {
+    let stations = match utils::stations_from_flags(&HashMap::new()) {
+        Ok(stations) if !stations.is_empty() => stations,
+        Ok(_) => {
+            eprintln!("Predicting passes needs an observer, set COSMOS_OBSERVER");
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let days: f64 = Input::new()
+        .with_prompt("Days ahead")
+        .default(DEFAULT_DAYS)
+        .interact_text()
+        .unwrap();
+    let horizon: f64 = Input::new()
+        .with_prompt("Minimum elevation (degrees)")
+        .default(0.0)
+        .interact_text()
+        .unwrap();
+    let path: String = Input::new()
+        .with_prompt(format!(
+            "Export calendar to (e.g. {}_passes.ics, empty to only list)",
+            satellite.id
+        ))
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+
+    let satellites = std::slice::from_ref(satellite);
+    let passes = match predict(satellites, &stations, days.clamp(0.0, MAX_DAYS), horizon) {
+        Ok(passes) => passes,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+    utils::print_pass_table(&passes);
+
+    if !path.trim().is_empty() {
+        let output = satellite_calendar_service::to_icalendar(
+            &passes,
+            &calendar_name(satellites),
+            &Utc::now(),
+        );
+        match fs::write(&path, output) {
+            Ok(_) => println!("Exported {} passes to {}", passes.len(), path),
+            Err(err) => eprintln!("Error writing {}: {}", path, err),
+        }
+    }
+}
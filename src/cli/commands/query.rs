@@ -0,0 +1,114 @@
+use std::process;
+
+use chrono::Utc;
+use dialoguer::{console::Term, Input, Select};
+
+use crate::cli::{theme, utils};
+use crate::libs::satellite_catalog_service;
+use crate::libs::satellite_query_service::{self, Query};
+
+use super::info::info_ui;
+
+// Non-interactive mode: `cosmos_cli query "inclination > 97 order by period desc" --format csv`
+pub fn query_command(args: &[String]) -> Result<(), String> {
+    let (text, flag_args): (Vec<String>, Vec<String>) =
+        match args.iter().position(|a| a.starts_with("--")) {
+            Some(index) => (args[..index].to_vec(), args[index..].to_vec()),
+            None => (args.to_vec(), vec![]),
+        };
+    let flags = utils::parse_flags(&flag_args)?;
+    let query = satellite_query_service::parse_query(&text.join(" "))?;
+
+    let catalog = utils::parse_catalog(satellite_catalog_service::get_catalog());
+    let result = satellite_query_service::run_query(&catalog, &query, &Utc::now())?;
+
+    match flags.get("format").map(String::as_str) {
+        None | Some("table") => {
+            utils::print_query_result(&result);
+            println!("\n{} of {} objects match", result.rows.len(), catalog.len());
+        }
+        Some("csv") => print!("{}", satellite_query_service::query_result_to_csv(&result)),
+        Some(other) => {
+            return Err(format!(
+                "Unknown format '{}' (expected table or csv)",
+                other
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt_query() -> Option<Query> {
+    loop {
+        let text: String = Input::new()
+            .with_prompt("Query (e.g. inclination > 97 and perigee_alt < 600 order by period desc)")
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        if text.trim().is_empty() {
+            return None;
+        }
+
+        match satellite_query_service::parse_query(&text) {
+            Ok(query) => return Some(query),
+            Err(err) => eprintln!("{}\n", err),
+        }
+    }
+}
+
+pub fn query_ui() {
+    let Some(query) = prompt_query() else {
+        return;
+    };
+
+    let pb = utils::display_spinner("Loading catalog...".to_string(), None);
+    let catalog = utils::parse_catalog(satellite_catalog_service::get_catalog());
+    let result = satellite_query_service::run_query(&catalog, &query, &Utc::now());
+    pb.finish_and_clear();
+
+    let result = match result {
+        Ok(result) => result,
+        Err(err) => {
+            eprintln!("{}", err);
+            utils::wait_for_enter();
+            return;
+        }
+    };
+
+    loop {
+        clearscreen::clear().unwrap();
+        utils::print_query_result(&result);
+        println!();
+
+        let mut selections: Vec<String> = result
+            .entries
+            .iter()
+            .map(|index| {
+                format!(
+                    "{} ({})",
+                    catalog[*index].satellite.name, catalog[*index].satellite.id
+                )
+            })
+            .collect();
+        selections.push("Return".to_string());
+
+        let selection = Select::with_theme(&theme::prompt_theme())
+            .with_prompt(format!(
+                "{} of {} objects match, select one to get more information",
+                result.rows.len(),
+                catalog.len()
+            ))
+            .default(0)
+            .max_length(10)
+            .items(&selections)
+            .interact_on_opt(&Term::stderr());
+
+        match selection.unwrap_or_else(|_| process::exit(1)) {
+            Some(index) if index < result.entries.len() => {
+                info_ui(&catalog[result.entries[index]].satellite)
+            }
+            _ => return,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::sync::{
+    atomic::{AtomicBool, AtomicI64, Ordering},
+    Arc,
+};
+use std::thread;
+
+use chrono::{Duration, Local, Utc};
+use dialoguer::{Confirm, Input};
+
+use crate::cli::{theme, utils};
+use crate::libs::orbit::{Geodetic, Propagator};
+use crate::libs::satellite_hamlib_service;
+use crate::libs::satellite_radio_service::{
+    self, RadioEvent, RadioOptions, Rig, Transponder, DEFAULT_RIGCTLD_ADDRESS,
+};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_INTERVAL_SECONDS: f64 = 1.0;
+const DEFAULT_TOLERANCE_HZ: f64 = 10.0;
+const DEFAULT_FAKE_PORT: u16 = 4532;
+
+// A VFO flag, `none` leaves the rig on whatever VFO is selected
+fn vfo_flag(flags: &HashMap<String, String>, key: &str, default: Option<&str>) -> Option<String> {
+    match flags.get(key).map(String::as_str) {
+        Some(vfo) if vfo.eq_ignore_ascii_case("none") => None,
+        Some(vfo) => Some(vfo.to_string()),
+        None => default.map(str::to_string),
+    }
+}
+
+fn options_from_flags(flags: &HashMap<String, String>) -> Result<(RadioOptions, f64), String> {
+    let downlink_hz = utils::parse_frequency(
+        flags
+            .get("downlink")
+            .ok_or("Missing --downlink FREQUENCY")?,
+    )?;
+    let uplink_hz = flags
+        .get("uplink")
+        .map(|text| utils::parse_frequency(text))
+        .transpose()?;
+    let offset_hz = flags
+        .get("offset")
+        .map_or(Ok(0.0), |text| utils::parse_frequency(text))?;
+
+    // Full duplex needs both VFOs, receive-only tuning stays on the current one
+    let (downlink_vfo, uplink_vfo) = if uplink_hz.is_some() {
+        (
+            vfo_flag(flags, "downlink-vfo", Some("VFOA")),
+            vfo_flag(flags, "uplink-vfo", Some("VFOB")),
+        )
+    } else {
+        (vfo_flag(flags, "downlink-vfo", None), None)
+    };
+
+    let interval = Duration::milliseconds(
+        (utils::number_flag(flags, "interval", DEFAULT_INTERVAL_SECONDS)? * 1000.0) as i64,
+    );
+    if interval <= Duration::zero() {
+        return Err("--interval has to be positive".to_string());
+    }
+
+    let options = RadioOptions {
+        transponder: Transponder {
+            downlink_hz,
+            uplink_hz,
+            inverting: flags.contains_key("inverting"),
+        },
+        downlink_vfo,
+        uplink_vfo,
+        horizon: utils::number_flag(flags, "horizon", 0.0)?,
+        interval,
+        tolerance_hz: utils::number_flag(flags, "tolerance", DEFAULT_TOLERANCE_HZ)?,
+        passes: utils::number_flag(flags, "passes", 1.0)? as usize,
+    };
+    Ok((options, offset_hz))
+}
+
+fn format_mhz(hz: f64) -> String {
+    format!("{:.6} MHz", hz / 1e6)
+}
+
+fn print_event(event: RadioEvent) {
+    match event {
+        RadioEvent::NextPass(pass) => println!(
+            "Next pass: AOS {}, TCA {} (el {:.1}°), LOS {}{}",
+            pass.aos.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            pass.tca.with_timezone(&Local).format("%H:%M:%S"),
+            pass.max_elevation,
+            pass.los.with_timezone(&Local).format("%H:%M:%S"),
+            if pass.in_progress {
+                " (in progress)"
+            } else {
+                ""
+            }
+        ),
+        RadioEvent::Tuned(range_rate, tuning) => println!(
+            "{}  range rate {:+7.3} km/s  RX {}{}",
+            Local::now().format("%H:%M:%S"),
+            range_rate,
+            format_mhz(tuning.downlink_hz),
+            tuning.uplink_hz.map_or(String::new(), |uplink| format!(
+                "  TX {}",
+                format_mhz(uplink)
+            ))
+        ),
+        RadioEvent::PassEnded(pass) => println!(
+            "LOS at {}",
+            pass.los.with_timezone(&Local).format("%H:%M:%S")
+        ),
+    }
+}
+
+// Tunes on a separate thread until done or until an empty line is entered; lines such as
+// `+500` or `-1.2k` change the manual offset meanwhile, `0` resets it
+fn run_tuning(
+    address: &str,
+    satellite: &Satellite,
+    observer: Geodetic,
+    options: RadioOptions,
+    offset_hz: f64,
+    wait_for_enter: bool,
+) -> Result<(), String> {
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let propagator = Propagator::new(elements)?;
+
+    let mut rig = Rig::connect(address)?;
+    let frequency = rig.get_frequency()?;
+    println!(
+        "Connected to rigctld at {} ({}), tuning for {}.",
+        rig.address(),
+        format_mhz(frequency),
+        satellite.name
+    );
+    println!("Enter +HZ/-HZ to shift the offset, 0 to reset it, or an empty line to stop.");
+
+    let running = Arc::new(AtomicBool::new(true));
+    let offset = Arc::new(AtomicI64::new(offset_hz.round() as i64));
+    let (running_clone, offset_clone) = (Arc::clone(&running), Arc::clone(&offset));
+    let tuner = thread::spawn(move || {
+        let result = satellite_radio_service::tune_passes(
+            &mut rig,
+            &propagator,
+            &observer,
+            &options,
+            &offset_clone,
+            &running_clone,
+            print_event,
+        );
+        rig.close();
+        running_clone.store(false, Ordering::SeqCst);
+        result
+    });
+
+    // A closed stdin (e.g. when run from a script) leaves tuning running
+    let (running_clone, offset_clone) = (Arc::clone(&running), Arc::clone(&offset));
+    let input = thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            let line = line.trim();
+            if line.is_empty() || !running_clone.load(Ordering::SeqCst) {
+                running_clone.store(false, Ordering::SeqCst);
+                break;
+            }
+
+            match utils::parse_frequency(line) {
+                Ok(0.0) => offset_clone.store(0, Ordering::SeqCst),
+                Ok(hz) => {
+                    offset_clone.fetch_add(hz.round() as i64, Ordering::SeqCst);
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    continue;
+                }
+            }
+            println!("Offset {:+} Hz", offset_clone.load(Ordering::SeqCst));
+        }
+    });
+
+    let result = tuner
+        .join()
+        .map_err(|_| "The tuning thread panicked".to_string())?;
+    if wait_for_enter && !input.is_finished() {
+        println!("\nPress Enter to return...");
+        let _ = input.join();
+    }
+
+    result
+}
+
+fn fake_command(args: &[String]) -> Result<(), String> {
+    let flags = utils::parse_flags(args)?;
+    let port = flags.get("port").map_or(Ok(DEFAULT_FAKE_PORT), |text| {
+        text.parse::<u16>()
+            .map_err(|_| format!("Invalid --port '{}'", text))
+    })?;
+
+    let listener = satellite_hamlib_service::listen_locally(port)?;
+    if let Ok(address) = listener.local_addr() {
+        println!("Fake rigctld listening on {}", address);
+    }
+    satellite_radio_service::serve_fake_rigctld(listener, |line| {
+        println!("{}  {}", Utc::now().format("%H:%M:%S%.3f"), line)
+    })
+}
+
+// Non-interactive mode: `cosmos_cli radio 43017 --downlink 145.96M --uplink 435.25M --inverting`
+// or `cosmos_cli radio fake --port 4532` for a stand-in rigctld that logs what it is sent
+pub fn radio_command(args: &[String]) -> Result<(), String> {
+    match args.first() {
+        Some(action) if action == "fake" => return fake_command(&args[1..]),
+        Some(first) if !first.starts_with("--") => {}
+        _ => return Err("Missing NORAD ID or COSPAR designator".to_string()),
+    }
+
+    let flags = utils::parse_flags(&args[1..])?;
+    let satellite = utils::resolve_one_satellite(&args[0])?;
+
+    let observer = utils::observer_from_flags(&flags)?
+        .ok_or("Doppler tuning needs an observer (--observer or COSMOS_OBSERVER)")?;
+    let (options, offset_hz) = options_from_flags(&flags)?;
+    let address = flags
+        .get("rigctld")
+        .map_or(DEFAULT_RIGCTLD_ADDRESS, String::as_str);
+
+    run_tuning(address, &satellite, observer, options, offset_hz, false)
+}
+
+pub fn radio_ui(satellite: &Satellite) {
+    let observer = match utils::default_observer() {
+        Ok(Some(observer)) => observer,
+        Ok(None) => {
+            eprintln!("Doppler tuning needs an observer, set COSMOS_OBSERVER");
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let address: String = Input::new()
+        .with_prompt("rigctld address")
+        .default(DEFAULT_RIGCTLD_ADDRESS.to_string())
+        .interact_text()
+        .unwrap();
+    let downlink: String = Input::new()
+        .with_prompt("Downlink frequency (e.g. 145.8M)")
+        .interact_text()
+        .unwrap();
+    let uplink: String = Input::new()
+        .with_prompt("Uplink frequency (empty for receive only)")
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+    let inverting = !uplink.trim().is_empty()
+        && Confirm::with_theme(&theme::prompt_theme())
+            .with_prompt("Inverting transponder?")
+            .default(false)
+            .interact()
+            .unwrap();
+
+    let mut flags = HashMap::from([("downlink".to_string(), downlink)]);
+    if !uplink.trim().is_empty() {
+        flags.insert("uplink".to_string(), uplink);
+    }
+    if inverting {
+        flags.insert("inverting".to_string(), "true".to_string());
+    }
+
+    let result = options_from_flags(&flags).and_then(|(options, offset_hz)| {
+        run_tuning(&address, satellite, observer, options, offset_hz, true)
+    });
+    if let Err(err) = result {
+        eprintln!("{}", err);
+    }
+}
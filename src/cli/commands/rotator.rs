@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+
+use chrono::{Duration, Local, Utc};
+use dialoguer::{Confirm, Input};
+
+use crate::cli::{theme, utils};
+use crate::libs::orbit::{Geodetic, Propagator};
+use crate::libs::satellite_hamlib_service;
+use crate::libs::satellite_rotator_service::{
+    self, Rotator, RotatorLimits, TrackingEvent, TrackingOptions, DEFAULT_ROTCTLD_ADDRESS,
+};
+use crate::libs::satellite_search_service::Satellite;
+
+const DEFAULT_PRE_POSITION_SECONDS: f64 = 120.0;
+const DEFAULT_INTERVAL_SECONDS: f64 = 1.0;
+const DEFAULT_TOLERANCE_DEGREES: f64 = 0.5;
+const DEFAULT_FAKE_PORT: u16 = 4533;
+
+fn parse_park(text: &str) -> Result<(f64, f64), String> {
+    let values: Vec<Option<f64>> = text
+        .split(',')
+        .map(|part| part.trim().parse::<f64>().ok())
+        .collect();
+    match values.as_slice() {
+        [Some(azimuth), Some(elevation)] => Ok((*azimuth, *elevation)),
+        _ => Err(format!("Invalid park position '{}' (expected AZ,EL)", text)),
+    }
+}
+
+fn options_from_flags(flags: &HashMap<String, String>) -> Result<TrackingOptions, String> {
+    let defaults = RotatorLimits::default();
+    let limits = RotatorLimits {
+        min_azimuth: utils::number_flag(flags, "min-azimuth", defaults.min_azimuth)?,
+        max_azimuth: utils::number_flag(flags, "max-azimuth", defaults.max_azimuth)?,
+        min_elevation: utils::number_flag(flags, "min-elevation", defaults.min_elevation)?,
+        max_elevation: utils::number_flag(flags, "max-elevation", defaults.max_elevation)?,
+    };
+    if limits.max_azimuth - limits.min_azimuth < 360.0 {
+        return Err("The azimuth range has to cover at least 360 degrees".to_string());
+    }
+    if limits.min_elevation >= limits.max_elevation {
+        return Err("--min-elevation has to be below --max-elevation".to_string());
+    }
+
+    let seconds = |key: &str, default: f64| -> Result<Duration, String> {
+        Ok(Duration::milliseconds(
+            (utils::number_flag(flags, key, default)? * 1000.0) as i64,
+        ))
+    };
+    let interval = seconds("interval", DEFAULT_INTERVAL_SECONDS)?;
+    if interval <= Duration::zero() {
+        return Err("--interval has to be positive".to_string());
+    }
+
+    Ok(TrackingOptions {
+        limits,
+        allow_flip: !flags.contains_key("no-flip"),
+        park: flags.get("park").map(|text| parse_park(text)).transpose()?,
+        horizon: utils::number_flag(flags, "horizon", 0.0)?,
+        pre_position: seconds("pre-position", DEFAULT_PRE_POSITION_SECONDS)?,
+        interval,
+        tolerance: utils::number_flag(flags, "tolerance", DEFAULT_TOLERANCE_DEGREES)?,
+        passes: utils::number_flag(flags, "passes", 1.0)? as usize,
+    })
+}
+
+fn print_event(event: TrackingEvent) {
+    match event {
+        TrackingEvent::NextPass(pass, mode) => println!(
+            "Next pass: AOS {} (az {:.1}°), TCA {} (az {:.1}° el {:.1}°), LOS {} (az {:.1}°), {} pointing{}",
+            pass.aos.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            pass.aos_azimuth,
+            pass.tca.with_timezone(&Local).format("%H:%M:%S"),
+            pass.tca_azimuth,
+            pass.max_elevation,
+            pass.los.with_timezone(&Local).format("%H:%M:%S"),
+            pass.los_azimuth,
+            mode.label(),
+            if pass.in_progress { " (in progress)" } else { "" }
+        ),
+        TrackingEvent::PrePositioned(azimuth, elevation) => println!(
+            "Pre-positioned to az {:.1}° el {:.1}° for AOS",
+            azimuth, elevation
+        ),
+        TrackingEvent::Pointed(look, azimuth, elevation) => println!(
+            "{}  sat az {:7.2}° el {:6.2}°  range {:8.1} km  ->  P {:.1} {:.1}",
+            Local::now().format("%H:%M:%S"),
+            look.azimuth,
+            look.elevation,
+            look.range_km,
+            azimuth,
+            elevation
+        ),
+        TrackingEvent::PassEnded(pass) => println!(
+            "LOS at {}",
+            pass.los.with_timezone(&Local).format("%H:%M:%S")
+        ),
+        TrackingEvent::Parked(Some((azimuth, elevation))) => {
+            println!("Parked at az {:.1}° el {:.1}°", azimuth, elevation)
+        }
+        TrackingEvent::Parked(None) => println!("Parked"),
+    }
+}
+
+// Connects and tracks on a separate thread until done or until Enter is pressed. With
+// `wait_for_enter` a run that ends by itself still waits for Enter before returning.
+fn run_tracking(
+    address: &str,
+    satellite: &Satellite,
+    observer: Geodetic,
+    options: TrackingOptions,
+    wait_for_enter: bool,
+) -> Result<(), String> {
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let propagator = Propagator::new(elements)?;
+
+    let mut rotator = Rotator::connect(address)?;
+    let (azimuth, elevation) = rotator.get_position()?;
+    println!(
+        "Connected to rotctld at {} (az {:.1}° el {:.1}°), tracking {}. Press Enter to stop and park.",
+        rotator.address(),
+        azimuth,
+        elevation,
+        satellite.name
+    );
+
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    let tracker = thread::spawn(move || {
+        let result = satellite_rotator_service::track_passes(
+            &mut rotator,
+            &propagator,
+            &observer,
+            &options,
+            &running_clone,
+            print_event,
+        );
+        rotator.close();
+        running_clone.store(false, Ordering::SeqCst);
+        result
+    });
+
+    // Stop on Enter; a closed stdin (e.g. when run from a script) leaves tracking running
+    let running_clone = Arc::clone(&running);
+    let input = thread::spawn(move || {
+        let mut line = String::new();
+        if matches!(io::stdin().lock().read_line(&mut line), Ok(read) if read > 0) {
+            running_clone.store(false, Ordering::SeqCst);
+        }
+    });
+
+    let result = tracker
+        .join()
+        .map_err(|_| "The tracking thread panicked".to_string())?;
+    if wait_for_enter && !input.is_finished() {
+        println!("\nPress Enter to return...");
+        let _ = input.join();
+    }
+
+    result
+}
+
+fn fake_command(args: &[String]) -> Result<(), String> {
+    let flags = utils::parse_flags(args)?;
+    let port = flags.get("port").map_or(Ok(DEFAULT_FAKE_PORT), |text| {
+        text.parse::<u16>()
+            .map_err(|_| format!("Invalid --port '{}'", text))
+    })?;
+
+    let listener = satellite_hamlib_service::listen_locally(port)?;
+    if let Ok(address) = listener.local_addr() {
+        println!("Fake rotctld listening on {}", address);
+    }
+    satellite_rotator_service::serve_fake_rotctld(listener, |line| {
+        println!("{}  {}", Utc::now().format("%H:%M:%S%.3f"), line)
+    })
+}
+
+// Non-interactive mode: `cosmos_cli rotator 25544 --rotctld localhost:4533 --park 0,90`
+// or `cosmos_cli rotator fake --port 4533` for a stand-in rotctld that logs what it is sent
+pub fn rotator_command(args: &[String]) -> Result<(), String> {
+    match args.first() {
+        Some(action) if action == "fake" => return fake_command(&args[1..]),
+        Some(first) if !first.starts_with("--") => {}
+        _ => return Err("Missing NORAD ID or COSPAR designator".to_string()),
+    }
+
+    let flags = utils::parse_flags(&args[1..])?;
+    let satellite = utils::resolve_one_satellite(&args[0])?;
+
+    let observer = utils::observer_from_flags(&flags)?
+        .ok_or("Rotator tracking needs an observer (--observer or COSMOS_OBSERVER)")?;
+    let options = options_from_flags(&flags)?;
+    let address = flags
+        .get("rotctld")
+        .map_or(DEFAULT_ROTCTLD_ADDRESS, String::as_str);
+
+    run_tracking(address, &satellite, observer, options, false)
+}
+
+pub fn rotator_ui(satellite: &Satellite) {
+    let observer = match utils::default_observer() {
+        Ok(Some(observer)) => observer,
+        Ok(None) => {
+            eprintln!("Rotator tracking needs an observer, set COSMOS_OBSERVER");
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+
+    let address: String = Input::new()
+        .with_prompt("rotctld address")
+        .default(DEFAULT_ROTCTLD_ADDRESS.to_string())
+        .interact_text()
+        .unwrap();
+    let min_elevation: f64 = Input::new()
+        .with_prompt("Minimum elevation (degrees)")
+        .default(0.0)
+        .interact_text()
+        .unwrap();
+    let max_elevation: f64 = Input::new()
+        .with_prompt("Maximum elevation (degrees, 180 for flip-capable rotators)")
+        .default(90.0)
+        .interact_text()
+        .unwrap();
+    let park: String = Input::new()
+        .with_prompt("Park position (AZ,EL, empty for the rotator's own)")
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+    let allow_flip = max_elevation >= 180.0
+        && Confirm::with_theme(&theme::prompt_theme())
+            .with_prompt("Flip over the top for passes crossing north?")
+            .default(true)
+            .interact()
+            .unwrap();
+
+    let park = match park.trim() {
+        "" => None,
+        text => match parse_park(text) {
+            Ok(position) => Some(position),
+            Err(err) => {
+                eprintln!("{}", err);
+                return;
+            }
+        },
+    };
+
+    let options = TrackingOptions {
+        limits: RotatorLimits {
+            min_elevation,
+            max_elevation,
+            ..RotatorLimits::default()
+        },
+        allow_flip,
+        park,
+        horizon: 0.0,
+        pre_position: Duration::milliseconds((DEFAULT_PRE_POSITION_SECONDS * 1000.0) as i64),
+        interval: Duration::milliseconds((DEFAULT_INTERVAL_SECONDS * 1000.0) as i64),
+        tolerance: DEFAULT_TOLERANCE_DEGREES,
+        passes: 1,
+    };
+
+    if let Err(err) = run_tracking(&address, satellite, observer, options, true) {
+        eprintln!("{}", err);
+    }
+}
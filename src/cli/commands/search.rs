@@ -1,19 +1,239 @@
 use std::collections::HashMap;
+use std::io::{self, Write};
 use std::process;
 
-use dialoguer::{console::Term, theme::ColorfulTheme, Input, Select};
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::queue;
+use crossterm::terminal::{self, Clear, ClearType};
+use dialoguer::{console::Term, Select};
 
+use crate::cli::theme::{self, Role};
 use crate::cli::utils;
-use crate::libs::satellite_search_service::{get_satellites, Satellite};
+use crate::libs::satellite_catalog_service;
+use crate::libs::satellite_index_service::{SearchHit, SearchIndex};
+use crate::libs::satellite_search_service::{
+    get_satellites, lookup_satellites, parse_identifier, Satellite, SatelliteIdentifier,
+};
 
 use super::info::info_ui;
 
+const LIVE_RESULTS: usize = 15;
+
+enum LiveSearchOutcome {
+    Selected(Satellite),
+    Identifier(SatelliteIdentifier),
+    Online(String),
+    Cancelled,
+}
+
+fn render_live_search(
+    query: &str,
+    direct: Option<&SatelliteIdentifier>,
+    hits: &[SearchHit],
+    selected: usize,
+    index_size: usize,
+) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    queue!(stdout, MoveTo(0, 0), Clear(ClearType::All))?;
+
+    // Raw mode needs explicit carriage returns
+    write!(
+        stdout,
+        "{} {}\r\n",
+        theme::bold("Search satellites:"),
+        query
+    )?;
+    write!(
+        stdout,
+        "{}\r\n\r\n",
+        theme::paint(
+            &format!(
+                "{} objects indexed · ↑/↓ select · Enter open · Tab search online · Esc cancel",
+                index_size
+            ),
+            Role::Muted
+        )
+    )?;
+
+    if hits.is_empty() && direct.is_none() && !query.is_empty() {
+        write!(
+            stdout,
+            "  No local matches, press Enter to search online\r\n"
+        )?;
+    }
+
+    // A recognised identifier gets its own row above the local hits
+    let rows = direct
+        .map(|identifier| format!("Look up {}", identifier))
+        .into_iter()
+        .chain(hits.iter().map(|hit| {
+            format!(
+                "{} ({}) {}",
+                hit.satellite.name,
+                hit.satellite.id,
+                hit.designator.as_deref().unwrap_or_default()
+            )
+        }));
+
+    for (i, line) in rows.enumerate() {
+        if i == selected {
+            write!(
+                stdout,
+                "{} {}\r\n",
+                theme::paint(">", Role::Highlight),
+                theme::paint(&line, Role::Highlight)
+            )?;
+        } else {
+            write!(stdout, "  {}\r\n", line)?;
+        }
+    }
+
+    // Leave the cursor at the end of the query line
+    queue!(stdout, MoveTo(19 + query.chars().count() as u16, 0))?;
+    stdout.flush()
+}
+
+// Re-ranks the local index on every key press
+fn live_search(index: &SearchIndex) -> io::Result<LiveSearchOutcome> {
+    let mut query = String::new();
+    let mut selected = 0;
+
+    terminal::enable_raw_mode()?;
+    let outcome = loop {
+        let hits = index.search(&query, LIVE_RESULTS);
+        // Typed NORAD IDs and designators can be looked up even if they aren't indexed
+        let direct = parse_identifier(&query).filter(|identifier| {
+            !hits
+                .first()
+                .is_some_and(|hit| identifier.matches(hit.satellite))
+        });
+        let offset = usize::from(direct.is_some());
+        selected = selected.min((hits.len() + offset).saturating_sub(1));
+        render_live_search(
+            &query,
+            direct.as_ref(),
+            &hits,
+            selected,
+            index.entry_count(),
+        )?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key.code {
+            KeyCode::Esc => break LiveSearchOutcome::Cancelled,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break LiveSearchOutcome::Cancelled
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Tab if !query.trim().is_empty() => {
+                break LiveSearchOutcome::Online(query.trim().to_string())
+            }
+            KeyCode::Enter if selected < offset => {
+                if let Some(identifier) = direct {
+                    break LiveSearchOutcome::Identifier(identifier);
+                }
+            }
+            KeyCode::Enter => match hits.get(selected - offset) {
+                Some(hit) => break LiveSearchOutcome::Selected(hit.satellite.clone()),
+                None if !query.trim().is_empty() => {
+                    break LiveSearchOutcome::Online(query.trim().to_string())
+                }
+                None => {}
+            },
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+
+    Ok(outcome)
+}
+
 pub fn search_ui() {
-    let search_query: String = Input::new()
-        .with_prompt("Enter the name of the satellite to search for")
-        .interact_text()
-        .unwrap();
+    let pb = utils::display_spinner("Loading catalog...".to_string(), None);
+    let index = SearchIndex::build(satellite_catalog_service::get_catalog());
+    pb.finish_and_clear();
 
+    loop {
+        let outcome = live_search(&index).unwrap_or_else(|_| {
+            let _ = terminal::disable_raw_mode();
+            eprintln!("Failed to interact with user");
+            process::exit(1);
+        });
+        clearscreen::clear().unwrap();
+
+        match outcome {
+            LiveSearchOutcome::Selected(satellite) => info_ui(&satellite),
+            LiveSearchOutcome::Identifier(identifier) => {
+                if !lookup_ui(&identifier) {
+                    println!("No satellite found for {}", identifier);
+                    utils::wait_for_enter();
+                }
+            }
+            LiveSearchOutcome::Online(query) => online_search_ui(query),
+            LiveSearchOutcome::Cancelled => return,
+        }
+    }
+}
+
+/**
+Looks a satellite up by NORAD ID or COSPAR designator and opens its info view
+
+# Arguments
+* `identifier` - The identifier recognised from the user's input
+# Returns
+* Whether anything was found
+*/
+pub fn lookup_ui(identifier: &SatelliteIdentifier) -> bool {
+    let pb = utils::display_spinner(format!("Looking up {}...", identifier), None);
+    let satellites = lookup_satellites(identifier);
+    pb.finish_and_clear();
+
+    match satellites.len() {
+        0 => return false,
+        1 => {
+            info_ui(&satellites[0]);
+            return true;
+        }
+        _ => {}
+    }
+
+    // A launch designator (e.g. 1998-067) matches every piece of that launch
+    loop {
+        clearscreen::clear().unwrap();
+
+        let mut selections: Vec<String> = satellites
+            .iter()
+            .map(|s| format!("{} ({})", s.name, s.id))
+            .collect();
+        selections.push("Cancel".to_string());
+
+        let selection = Select::with_theme(&theme::prompt_theme())
+            .with_prompt(format!("{} objects match {}", satellites.len(), identifier))
+            .default(0)
+            .max_length(20)
+            .items(&selections)
+            .interact_on_opt(&Term::stderr());
+
+        match selection.unwrap_or_else(|_| process::exit(1)) {
+            Some(index) if index < satellites.len() => info_ui(&satellites[index]),
+            _ => return true,
+        }
+    }
+}
+
+// Name search against the TLE API, for objects that aren't in the local catalog
+fn online_search_ui(search_query: String) {
     let mut current_page = 1;
     let page_size = 10; // Define how many items you want per page
     let mut cache: HashMap<i32, Vec<Satellite>> = HashMap::new();
@@ -65,9 +285,7 @@ pub fn search_ui() {
 
         selections.push("Cancel".to_string());
 
-        let theme = ColorfulTheme {
-            ..ColorfulTheme::default()
-        };
+        let theme = theme::prompt_theme();
 
         let selection = Select::with_theme(&theme)
             .with_prompt("Select a satellite to get more information or navigate pages")
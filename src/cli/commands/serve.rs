@@ -0,0 +1,746 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc, Mutex, OnceLock,
+};
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::cli::utils;
+use crate::libs::orbit::{self, Geodetic, Propagator};
+use crate::libs::satellite_catalog_service;
+use crate::libs::satellite_export_service::{self, ExportFormat, ExportOptions};
+use crate::libs::satellite_index_service::{self, SearchIndex};
+use crate::libs::satellite_pass_service;
+use crate::libs::satellite_satcat_service;
+use crate::libs::satellite_search_service::{self, Satellite};
+use crate::libs::satellite_track_service;
+use crate::libs::satellite_tracking_service::{self, Tracker, TrackingUpdate};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_THREADS: usize = 4;
+const DEFAULT_CACHE_SECONDS: u64 = 60;
+const SATELLITE_CACHE_SECONDS: u64 = 3600; // TLEs are only updated a few times a day
+const MAX_CACHE_ENTRIES: usize = 1000; // Per cache, so arbitrary query strings can't fill memory
+
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+const MAX_SEARCH_LIMIT: usize = 100;
+const DEFAULT_TRACK_MINUTES: f64 = 90.0;
+const DEFAULT_TRACK_STEP_SECONDS: f64 = 30.0;
+const MAX_TRACK_MINUTES: f64 = 10080.0; // A week
+const MAX_TRACK_POINTS: f64 = 10000.0;
+const DEFAULT_PASS_DAYS: f64 = 1.0;
+const MAX_PASS_DAYS: f64 = 14.0;
+const DEFAULT_STREAM_INTERVAL_SECONDS: f64 = 1.0;
+const MIN_STREAM_INTERVAL_SECONDS: f64 = 0.1;
+const MAX_STREAM_SATELLITES: usize = 50;
+const MAX_STREAMS: usize = 64; // Each open stream has a thread of its own
+
+const ENDPOINTS: &[&str] = &[
+    "GET /health",
+    "GET /search?q=NAME[&limit=N]",
+    "GET /satellites/{id}",
+    "GET /satellites/{id}/tle",
+    "GET /satellites/{id}/position[?time=TIME][&observer=LAT,LON[,ALT_M]]",
+    "GET /satellites/{id}/track[?start=TIME][&duration=MINUTES][&step=SECONDS][&format=json|geojson|kml|gpx]",
+    "GET /satellites/{id}/passes[?observer=LAT,LON[,ALT_M]][&start=TIME][&days=N][&min_elevation=DEG]",
+    "GET /stream?ids=ID[,ID...][&interval=SECONDS][&observer=LAT,LON[,ALT_M]] (Server-Sent Events)",
+];
+
+// Every error is answered as `{"error": {"status": 400, "message": "..."}}`
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> ApiError {
+        ApiError {
+            status: 400,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> ApiError {
+        ApiError {
+            status: 404,
+            message: message.into(),
+        }
+    }
+
+    fn internal(message: impl Into<String>) -> ApiError {
+        ApiError {
+            status: 500,
+            message: message.into(),
+        }
+    }
+
+    fn unavailable(message: impl Into<String>) -> ApiError {
+        ApiError {
+            status: 503,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Reply {
+    Json(Value),
+    Document(&'static str, String), // Content type and body, for KML / GPX tracks
+}
+
+type ApiResult = Result<Reply, ApiError>;
+
+// Entries expire after a fixed time; expired ones are dropped whenever something is added, and
+// the oldest one when the cache is full
+struct Cache<T: Clone> {
+    ttl: StdDuration,
+    capacity: usize,
+    entries: Mutex<HashMap<String, (Instant, T)>>,
+}
+
+impl<T: Clone> Cache<T> {
+    fn new(ttl: StdDuration, capacity: usize) -> Cache<T> {
+        Cache {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get_or_try_insert<E>(
+        &self,
+        key: &str,
+        compute: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if let Some((stored, value)) = self.entries.lock().unwrap().get(key) {
+            if stored.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        // Computed without holding the lock, two racing requests may both compute
+        let value = compute()?;
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+        if entries.len() >= self.capacity && !entries.contains_key(key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key.to_string(), (Instant::now(), value.clone()));
+        Ok(value)
+    }
+}
+
+struct Api {
+    responses: Cache<Reply>,
+    satellites: Cache<Satellite>,
+    index: OnceLock<SearchIndex>,
+    streams: AtomicUsize, // Open streams
+}
+
+// Query string values with percent escapes and `+` decoded
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let escaped = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escaped {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_url(url: &str) -> (Vec<String>, BTreeMap<String, String>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect();
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+
+    (segments, params)
+}
+
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn number_param(
+    params: &BTreeMap<String, String>,
+    key: &str,
+    default: f64,
+    range: (f64, f64),
+) -> Result<f64, ApiError> {
+    let value = match params.get(key) {
+        Some(text) => text
+            .parse::<f64>()
+            .map_err(|_| ApiError::bad_request(format!("Invalid {} '{}'", key, text)))?,
+        None => default,
+    };
+    if !value.is_finite() || value < range.0 || value > range.1 {
+        return Err(ApiError::bad_request(format!(
+            "{} has to be between {} and {}",
+            key, range.0, range.1
+        )));
+    }
+    Ok(value)
+}
+
+fn time_param(params: &BTreeMap<String, String>, key: &str) -> Result<DateTime<Utc>, ApiError> {
+    params
+        .get(key)
+        .map_or(Ok(Utc::now()), |text| utils::parse_time(text))
+        .map_err(ApiError::bad_request)
+}
+
+// The `observer` parameter, falling back to `COSMOS_OBSERVER`
+fn observer_param(params: &BTreeMap<String, String>) -> Result<Option<Geodetic>, ApiError> {
+    match params.get("observer") {
+        Some(text) => utils::parse_observer(text)
+            .map(Some)
+            .map_err(ApiError::bad_request),
+        None => utils::default_observer().map_err(ApiError::internal),
+    }
+}
+
+fn look_json(look: &orbit::LookAngles) -> Value {
+    json!({
+        "azimuth": look.azimuth,
+        "elevation": look.elevation,
+        "range_km": look.range_km,
+        "range_rate_km_s": look.range_rate_km_s,
+    })
+}
+
+fn summary_json(satellite: &Satellite) -> Value {
+    json!({
+        "norad_id": satellite.id,
+        "name": satellite.name,
+        "designator": satellite_index_service::designator_from_tle(&satellite.line_one),
+    })
+}
+
+impl Api {
+    fn new(cache_seconds: u64) -> Api {
+        Api {
+            responses: Cache::new(StdDuration::from_secs(cache_seconds), MAX_CACHE_ENTRIES),
+            satellites: Cache::new(
+                StdDuration::from_secs(SATELLITE_CACHE_SECONDS),
+                MAX_CACHE_ENTRIES,
+            ),
+            index: OnceLock::new(),
+            streams: AtomicUsize::new(0),
+        }
+    }
+
+    // Takes one of the stream slots, given back with `close_stream` when the client disconnects
+    fn reserve_stream(&self) -> Result<(), ApiError> {
+        if self.streams.fetch_add(1, Ordering::SeqCst) >= MAX_STREAMS {
+            self.close_stream();
+            return Err(ApiError::unavailable(format!(
+                "Too many open streams (at most {}), try again later",
+                MAX_STREAMS
+            )));
+        }
+        Ok(())
+    }
+
+    fn close_stream(&self) {
+        self.streams.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn handle(
+        &self,
+        method: &Method,
+        segments: &[&str],
+        params: &BTreeMap<String, String>,
+    ) -> ApiResult {
+        if *method != Method::Get {
+            return Err(ApiError {
+                status: 405,
+                message: format!("Method {} is not allowed, only GET", method),
+            });
+        }
+
+        match segments {
+            [] => Ok(Reply::Json(json!({ "endpoints": ENDPOINTS }))),
+            ["health"] => Ok(Reply::Json(json!({ "status": "ok" }))),
+            // Positions are cheap and only meaningful fresh, everything else goes through the cache
+            ["satellites", id, "position"] => self.position(id, params),
+            _ => {
+                let key = format!(
+                    "{}?{}",
+                    segments.join("/"),
+                    params
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect::<Vec<_>>()
+                        .join("&")
+                );
+                self.responses.get_or_try_insert(&key, || match segments {
+                    ["search"] => self.search(params),
+                    ["satellites", id] => self.satellite(id),
+                    ["satellites", id, "tle"] => self.tle(id),
+                    ["satellites", id, "track"] => self.track(id, params),
+                    ["satellites", id, "passes"] => self.passes(id, params),
+                    _ => Err(ApiError::not_found(format!(
+                        "No endpoint at /{}",
+                        segments.join("/")
+                    ))),
+                })
+            }
+        }
+    }
+
+    fn resolve(&self, text: &str) -> Result<Satellite, ApiError> {
+        let identifier = satellite_search_service::parse_identifier(text).ok_or_else(|| {
+            ApiError::bad_request(format!(
+                "'{}' is neither a NORAD ID nor a COSPAR designator",
+                text
+            ))
+        })?;
+
+        self.satellites
+            .get_or_try_insert(&identifier.to_string(), || {
+                let mut found = satellite_search_service::lookup_satellites(&identifier);
+                match found.len() {
+                    0 => Err(ApiError::not_found(format!(
+                        "No satellite found for {}",
+                        identifier
+                    ))),
+                    1 => Ok(found.remove(0)),
+                    count => Err(ApiError::bad_request(utils::ambiguous_identifier(
+                        text, count,
+                    ))),
+                }
+            })
+    }
+
+    // Validates a stream subscription up front, so errors still get a normal JSON response
+    fn open_stream(
+        &self,
+        params: &BTreeMap<String, String>,
+    ) -> Result<(Tracker, Duration), ApiError> {
+        let ids = params
+            .get("ids")
+            .filter(|ids| !ids.trim().is_empty())
+            .ok_or_else(|| ApiError::bad_request("Missing ids (comma separated NORAD IDs)"))?;
+        let interval = number_param(
+            params,
+            "interval",
+            DEFAULT_STREAM_INTERVAL_SECONDS,
+            (MIN_STREAM_INTERVAL_SECONDS, 3600.0),
+        )?;
+        let observer = observer_param(params)?;
+
+        let ids: Vec<&str> = ids.split(',').map(str::trim).collect();
+        if ids.len() > MAX_STREAM_SATELLITES {
+            return Err(ApiError::bad_request(format!(
+                "A stream is limited to {} satellites",
+                MAX_STREAM_SATELLITES
+            )));
+        }
+
+        let targets = ids
+            .iter()
+            .map(|id| {
+                let satellite = self.resolve(id)?;
+                let (elements, _) = self.propagator(&satellite)?;
+                Ok((satellite, elements))
+            })
+            .collect::<Result<Vec<_>, ApiError>>()?;
+        let tracker = Tracker::new(targets, observer).map_err(ApiError::internal)?;
+
+        Ok((tracker, Duration::milliseconds((interval * 1000.0) as i64)))
+    }
+
+    fn propagator(&self, satellite: &Satellite) -> Result<(sgp4::Elements, Propagator), ApiError> {
+        let elements =
+            utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)
+                .map_err(ApiError::internal)?;
+        let propagator = Propagator::new(elements.clone()).map_err(ApiError::internal)?;
+        Ok((elements, propagator))
+    }
+
+    fn search(&self, params: &BTreeMap<String, String>) -> ApiResult {
+        let query = params
+            .get("q")
+            .filter(|query| !query.trim().is_empty())
+            .ok_or_else(|| ApiError::bad_request("Missing q"))?;
+        let limit = number_param(
+            params,
+            "limit",
+            DEFAULT_SEARCH_LIMIT as f64,
+            (1.0, MAX_SEARCH_LIMIT as f64),
+        )? as usize;
+
+        let index = self
+            .index
+            .get_or_init(|| SearchIndex::build(satellite_catalog_service::get_catalog()));
+        let mut results: Vec<Value> = index
+            .search(query, limit)
+            .iter()
+            .map(|hit| summary_json(hit.satellite))
+            .collect();
+
+        // Like the interactive search, fall back to the TLE API for anything not in the catalog
+        if results.is_empty() {
+            let (satellites, _) =
+                satellite_search_service::get_satellites(Some(query), Some(1), Some(limit as i32));
+            results = satellites.iter().map(summary_json).collect();
+        }
+
+        Ok(Reply::Json(json!({ "query": query, "results": results })))
+    }
+
+    fn satellite(&self, id: &str) -> ApiResult {
+        let satellite = self.resolve(id)?;
+        let (elements, _) = self.propagator(&satellite)?;
+        let derived = orbit::derive_parameters(&elements, &Utc::now());
+
+        let mut value = summary_json(&satellite);
+        value["tle"] = json!([satellite.line_one, satellite.line_two]);
+        value["epoch"] = json!(timestamp(&orbit::epoch_of(&elements)));
+        value["elements"] = json!({
+            "inclination": elements.inclination,
+            "raan": elements.right_ascension,
+            "eccentricity": elements.eccentricity,
+            "argument_of_perigee": elements.argument_of_perigee,
+            "mean_anomaly": elements.mean_anomaly,
+            "mean_motion": elements.mean_motion,
+        });
+        value["derived"] = json!({
+            "semi_major_axis_km": derived.semi_major_axis_km,
+            "perigee_altitude_km": derived.perigee_altitude_km,
+            "apogee_altitude_km": derived.apogee_altitude_km,
+            "period_minutes": derived.period_minutes,
+            "epoch_age_days": derived.epoch_age_days,
+            "regime": derived.regime.label(),
+        });
+        if let Some(record) = satellite_satcat_service::get_record(satellite.id) {
+            value["satcat"] = json!(record);
+        }
+
+        Ok(Reply::Json(value))
+    }
+
+    fn tle(&self, id: &str) -> ApiResult {
+        let satellite = self.resolve(id)?;
+        Ok(Reply::Json(json!({
+            "norad_id": satellite.id,
+            "name": satellite.name,
+            "line1": satellite.line_one,
+            "line2": satellite.line_two,
+        })))
+    }
+
+    fn position(&self, id: &str, params: &BTreeMap<String, String>) -> ApiResult {
+        let time = time_param(params, "time")?;
+        let observer = observer_param(params)?;
+        let satellite = self.resolve(id)?;
+        let (_, propagator) = self.propagator(&satellite)?;
+
+        let state = propagator.state_at(&time).map_err(ApiError::internal)?;
+        let geodetic = orbit::teme_to_geodetic(&state.position, &time);
+
+        let mut value = summary_json(&satellite);
+        value["time"] = json!(timestamp(&time));
+        value["latitude"] = json!(geodetic.latitude);
+        value["longitude"] = json!(geodetic.longitude);
+        value["altitude_km"] = json!(geodetic.altitude_km);
+        value["position_km"] = json!(state.position);
+        value["velocity_km_s"] = json!(state.velocity);
+        value["sunlit"] = json!(orbit::is_sunlit(&state.position, &time));
+        value["eclipse"] = json!(orbit::eclipse_state(&state.position, &time)
+            .label()
+            .to_lowercase());
+        if let Some(observer) = observer {
+            value["look"] = look_json(&orbit::look_angles(&observer, &state, &time));
+        }
+
+        Ok(Reply::Json(value))
+    }
+
+    fn track(&self, id: &str, params: &BTreeMap<String, String>) -> ApiResult {
+        let start = time_param(params, "start")?;
+        let minutes = number_param(
+            params,
+            "duration",
+            DEFAULT_TRACK_MINUTES,
+            (1.0, MAX_TRACK_MINUTES),
+        )?;
+        let step = number_param(params, "step", DEFAULT_TRACK_STEP_SECONDS, (1.0, 3600.0))?;
+        if minutes * 60.0 / step > MAX_TRACK_POINTS {
+            return Err(ApiError::bad_request(format!(
+                "A track is limited to {} points, use a larger step",
+                MAX_TRACK_POINTS
+            )));
+        }
+        let format = params.get("format").map_or("json", String::as_str);
+        let export_format = match format {
+            "json" => None,
+            other => Some(ExportFormat::parse(other).map_err(ApiError::bad_request)?),
+        };
+
+        let satellite = self.resolve(id)?;
+        let (elements, _) = self.propagator(&satellite)?;
+        let duration = Duration::milliseconds((minutes * 60000.0) as i64);
+        let step = Duration::milliseconds((step * 1000.0) as i64);
+
+        let format = match export_format {
+            Some(format) => format,
+            None => {
+                let points: Vec<Value> =
+                    satellite_track_service::ground_track(&elements, &start, duration, step)
+                        .map_err(ApiError::internal)?
+                        .iter()
+                        .map(|point| {
+                            json!({
+                                "time": timestamp(&point.time),
+                                "latitude": point.position.latitude,
+                                "longitude": point.position.longitude,
+                                "altitude_km": point.position.altitude_km,
+                            })
+                        })
+                        .collect();
+                let mut value = summary_json(&satellite);
+                value["points"] = json!(points);
+                return Ok(Reply::Json(value));
+            }
+        };
+
+        let options = ExportOptions {
+            start,
+            duration,
+            step,
+            include_position: false,
+            include_footprint: false,
+        };
+        let track = satellite_export_service::build_track(&elements, &options)
+            .map_err(ApiError::internal)?;
+        let document = satellite_export_service::export_tracks(&[track], format);
+
+        Ok(match format {
+            ExportFormat::GeoJson => Reply::Json(
+                serde_json::from_str(&document).map_err(|e| ApiError::internal(e.to_string()))?,
+            ),
+            ExportFormat::Kml => Reply::Document("application/vnd.google-earth.kml+xml", document),
+            ExportFormat::Gpx => Reply::Document("application/gpx+xml", document),
+        })
+    }
+
+    fn passes(&self, id: &str, params: &BTreeMap<String, String>) -> ApiResult {
+        let observer = observer_param(params)?.ok_or_else(|| {
+            ApiError::bad_request(
+                "Missing observer (LAT,LON[,ALT_M]) and COSMOS_OBSERVER is not set",
+            )
+        })?;
+        let start = time_param(params, "start")?;
+        let days = number_param(params, "days", DEFAULT_PASS_DAYS, (0.0, MAX_PASS_DAYS))?;
+        let min_elevation = number_param(params, "min_elevation", 0.0, (-5.0, 90.0))?;
+        let satellite = self.resolve(id)?;
+        let (_, propagator) = self.propagator(&satellite)?;
+
+        let end = start + Duration::milliseconds((days * 86_400_000.0) as i64);
+        let passes = satellite_pass_service::predict_passes(
+            &propagator,
+            &observer,
+            &start,
+            &end,
+            min_elevation,
+        )
+        .map_err(ApiError::internal)?;
+
+        let passes: Vec<Value> = passes
+            .iter()
+            .map(|pass| {
+                json!({
+                    "aos": timestamp(&pass.aos),
+                    "tca": timestamp(&pass.tca),
+                    "los": timestamp(&pass.los),
+                    "duration_seconds": (pass.los - pass.aos).num_seconds(),
+                    "aos_azimuth": pass.aos_azimuth,
+                    "tca_azimuth": pass.tca_azimuth,
+                    "los_azimuth": pass.los_azimuth,
+                    "max_elevation": pass.max_elevation,
+                    "in_progress": pass.in_progress,
+                })
+            })
+            .collect();
+
+        let mut value = summary_json(&satellite);
+        value["observer"] = json!({
+            "latitude": observer.latitude,
+            "longitude": observer.longitude,
+            "altitude_km": observer.altitude_km,
+        });
+        value["passes"] = json!(passes);
+        Ok(Reply::Json(value))
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn respond(request: Request, result: ApiResult) {
+    if let Err(err) = &result {
+        eprintln!(
+            "{} {} -> {} {}",
+            request.method(),
+            request.url(),
+            err.status,
+            err.message
+        );
+    }
+
+    let (status, content_type, body) = match result {
+        Ok(Reply::Json(value)) => (200, "application/json", value.to_string()),
+        Ok(Reply::Document(content_type, body)) => (200, content_type, body),
+        Err(err) => (
+            err.status,
+            "application/json",
+            json!({ "error": { "status": err.status, "message": err.message } }).to_string(),
+        ),
+    };
+
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", content_type))
+        .with_header(header("Access-Control-Allow-Origin", "*"));
+    if let Err(err) = request.respond(response) {
+        eprintln!("Error sending response: {}", err);
+    }
+}
+
+fn update_json(update: &TrackingUpdate) -> Value {
+    let mut value = json!({
+        "norad_id": update.norad_id,
+        "name": update.name,
+        "time": timestamp(&update.time),
+        "latitude": update.position.latitude,
+        "longitude": update.position.longitude,
+        "altitude_km": update.position.altitude_km,
+        "sunlit": update.sunlit,
+        "eclipse": update.eclipse.label().to_lowercase(),
+    });
+    if let Some(look) = &update.look {
+        value["look"] = look_json(look);
+    }
+    value
+}
+
+// Sends one `position` event per satellite and update until the client disconnects
+fn stream_updates(request: Request, tracker: Tracker, interval: Duration) {
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+                Connection: close\r\nAccess-Control-Allow-Origin: *\r\n\r\n";
+    if writer.write_all(head.as_bytes()).is_err() {
+        return;
+    }
+
+    let running = AtomicBool::new(true);
+    satellite_tracking_service::run_tracking_loop(&tracker, interval, &running, |updates| {
+        let events: String = updates
+            .iter()
+            .map(|update| format!("event: position\ndata: {}\n\n", update_json(update)))
+            .collect();
+        writer.write_all(events.as_bytes()).is_ok() && writer.flush().is_ok()
+    });
+}
+
+// Non-interactive mode: `cosmos_cli serve --address 0.0.0.0:8080 --threads 8 --cache 120`
+pub fn serve_command(args: &[String]) -> Result<(), String> {
+    let flags = utils::parse_flags(args)?;
+    let address = flags.get("address").map_or(DEFAULT_ADDRESS, String::as_str);
+    let threads = flags.get("threads").map_or(Ok(DEFAULT_THREADS), |text| {
+        text.parse::<usize>()
+            .ok()
+            .filter(|threads| *threads > 0)
+            .ok_or(format!("Invalid --threads '{}'", text))
+    })?;
+    let cache_seconds = flags
+        .get("cache")
+        .map_or(Ok(DEFAULT_CACHE_SECONDS), |text| {
+            text.parse::<u64>()
+                .map_err(|_| format!("Invalid --cache '{}'", text))
+        })?;
+
+    let server = Arc::new(
+        Server::http(address).map_err(|e| format!("Error listening on {}: {}", address, e))?,
+    );
+    let api = Arc::new(Api::new(cache_seconds));
+    println!("Serving the JSON API on http://{}", address);
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let (server, api) = (Arc::clone(&server), Arc::clone(&api));
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let (segments, params) = parse_url(request.url());
+                    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+                    // Streams get a thread of their own so they don't hold up a worker
+                    if *request.method() == Method::Get && segments == ["stream"] {
+                        match api
+                            .open_stream(&params)
+                            .and_then(|stream| api.reserve_stream().map(|_| stream))
+                        {
+                            Ok((tracker, interval)) => {
+                                let api = Arc::clone(&api);
+                                thread::spawn(move || {
+                                    stream_updates(request, tracker, interval);
+                                    api.close_stream();
+                                });
+                            }
+                            Err(err) => respond(request, Err(err)),
+                        }
+                        continue;
+                    }
+
+                    let result = api.handle(request.method(), &segments, &params);
+                    respond(request, result);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+
+use chrono::{DateTime, Duration, Local, Utc};
+use dialoguer::console::Term;
+
+use crate::cli::theme::Role;
+use crate::cli::utils;
+use crate::libs::orbit::{Geodetic, LookAngles, Propagator};
+use crate::libs::satellite_pass_service::{self, Pass};
+use crate::libs::satellite_search_service::Satellite;
+use crate::libs::satellite_tracking_service::{self, Tracker};
+
+const RADIUS_ROWS: usize = 12;
+const PATH_STEP_SECONDS: i64 = 10;
+const MAX_TICKS: i64 = 12;
+const RING_ELEVATIONS: [f64; 3] = [0.0, 30.0, 60.0];
+const LIVE_INTERVAL_SECONDS: i64 = 1;
+
+// A pass with its azimuth / elevation path
+type PlannedPass = (Pass, Vec<(DateTime<Utc>, LookAngles)>);
+
+// Azimuth around the circle with north up and east right, elevation as the distance from the
+// rim (horizon) to the centre (zenith); columns are doubled since cells are about twice as high as wide
+struct SkyPlot {
+    grid: Vec<Vec<char>>,
+}
+
+impl SkyPlot {
+    fn new() -> SkyPlot {
+        let mut plot = SkyPlot {
+            grid: vec![vec![' '; 4 * RADIUS_ROWS + 5]; 2 * RADIUS_ROWS + 3],
+        };
+
+        for elevation in RING_ELEVATIONS {
+            for step in 0..360 {
+                plot.put(step as f64, elevation, '.');
+            }
+        }
+        let (row, column) = plot.cell(0.0, 90.0);
+        plot.grid[row][column] = '+';
+
+        let last_row = plot.grid.len() - 1;
+        let last_column = plot.grid[0].len() - 1;
+        plot.grid[0][column] = 'N';
+        plot.grid[last_row][column] = 'S';
+        plot.grid[row][0] = 'W';
+        plot.grid[row][last_column] = 'E';
+        plot
+    }
+
+    fn cell(&self, azimuth: f64, elevation: f64) -> (usize, usize) {
+        let radius = (90.0 - elevation.clamp(0.0, 90.0)) / 90.0 * RADIUS_ROWS as f64;
+        let (sin, cos) = azimuth.to_radians().sin_cos();
+        let row = (RADIUS_ROWS + 1) as f64 - radius * cos;
+        let column = (2 * RADIUS_ROWS + 2) as f64 + 2.0 * radius * sin;
+        (row.round() as usize, column.round() as usize)
+    }
+
+    fn put(&mut self, azimuth: f64, elevation: f64, marker: char) {
+        let (row, column) = self.cell(azimuth, elevation);
+        self.grid[row][column] = marker;
+    }
+
+    fn render(&self) -> String {
+        let text = self
+            .grid
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Same approach as the map: draw with plain characters, then color by character
+        utils::colorize_ascii(
+            &text,
+            &[
+                ('*', Role::Path),
+                ('o', Role::Tick),
+                ('A', Role::Rise),
+                ('L', Role::Set),
+                ('∆', Role::Marker),
+                ('N', Role::Label),
+                ('E', Role::Label),
+                ('S', Role::Label),
+                ('W', Role::Label),
+            ],
+            Role::Muted,
+        )
+    }
+}
+
+// Ticks at whole multiples of a minute interval that keeps them readable
+fn tick_minutes(pass: &Pass) -> i64 {
+    let minutes = (pass.los - pass.aos).num_minutes();
+    ((minutes + MAX_TICKS - 1) / MAX_TICKS).max(1)
+}
+
+fn draw_pass(
+    pass: &Pass,
+    path: &[(DateTime<Utc>, LookAngles)],
+    current: Option<&LookAngles>,
+) -> String {
+    let mut plot = SkyPlot::new();
+    for (_, look) in path {
+        plot.put(look.azimuth, look.elevation, '*');
+    }
+
+    let interval = tick_minutes(pass);
+    let mut previous_slot = None;
+    for (time, look) in path {
+        let slot = time.timestamp() / 60 / interval;
+        if previous_slot.is_some_and(|previous| previous != slot) {
+            plot.put(look.azimuth, look.elevation, 'o');
+        }
+        previous_slot = Some(slot);
+    }
+
+    plot.put(pass.aos_azimuth, 0.0, 'A');
+    plot.put(pass.los_azimuth, 0.0, 'L');
+    if let Some(look) = current.filter(|look| look.elevation >= 0.0) {
+        plot.put(look.azimuth, look.elevation, '∆');
+    }
+
+    let local = |time: &DateTime<Utc>| time.with_timezone(&Local).format("%H:%M:%S").to_string();
+    format!(
+        "{}\n\nA AOS {} az {:.0}°   TCA {} az {:.0}° el {:.1}°   L LOS {} az {:.0}°\n\
+         o every {} min   rings at 0°, 30° and 60° elevation",
+        plot.render(),
+        local(&pass.aos),
+        pass.aos_azimuth,
+        local(&pass.tca),
+        pass.tca_azimuth,
+        pass.max_elevation,
+        local(&pass.los),
+        pass.los_azimuth,
+        interval
+    )
+}
+
+fn predict(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    from: &DateTime<Utc>,
+    horizon: f64,
+) -> Result<PlannedPass, String> {
+    let pass = satellite_pass_service::next_pass(propagator, observer, from, horizon)?;
+    let path = satellite_pass_service::pass_path(
+        propagator,
+        observer,
+        &pass,
+        Duration::seconds(PATH_STEP_SECONDS),
+    )?;
+    Ok((pass, path))
+}
+
+// Redraws every second with the satellite on its arc until Enter is pressed, moving on to the
+// next pass after LOS
+fn run_live(satellite: &Satellite, observer: Geodetic, horizon: f64) -> Result<(), String> {
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let propagator = Propagator::new(elements.clone())?;
+    let tracker = Tracker::new(vec![(satellite.clone(), elements)], Some(observer))?;
+    let mut planned = predict(&propagator, &observer, &Utc::now(), horizon)?;
+
+    let name = satellite.name.clone();
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    thread::spawn(move || {
+        satellite_tracking_service::run_tracking_loop(
+            &tracker,
+            Duration::seconds(LIVE_INTERVAL_SECONDS),
+            &running,
+            |updates| {
+                let Some(update) = updates.first() else {
+                    return true;
+                };
+                if update.time > planned.0.los {
+                    match predict(&propagator, &observer, &update.time, horizon) {
+                        Ok(next) => planned = next,
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            return false;
+                        }
+                    }
+                }
+
+                let (pass, path) = &planned;
+                let status = match &update.look {
+                    Some(look) if update.time >= pass.aos => format!(
+                        "az {:.1}°  el {:.1}°  range {:.0} km",
+                        look.azimuth, look.elevation, look.range_km
+                    ),
+                    _ => format!("AOS in {} min", (pass.aos - update.time).num_minutes() + 1),
+                };
+
+                clearscreen::clear().unwrap();
+                let term = Term::stdout();
+                term.write_line(&format!("{}  {}\n", name, status)).unwrap();
+                term.write_line(&draw_pass(pass, path, update.look.as_ref()))
+                    .unwrap();
+                term.write_line("\nPress Enter to return...").unwrap();
+                true
+            },
+        );
+    });
+
+    Term::stdout().read_line().unwrap();
+    running_clone.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+// Non-interactive mode: `cosmos_cli sky 25544 --observer 52.52,13.40` plots the next pass,
+// `--live` keeps redrawing with the satellite's current position
+pub fn sky_command(args: &[String]) -> Result<(), String> {
+    let identifier = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or("Missing NORAD ID or COSPAR designator")?;
+    let flags = utils::parse_flags(&args[1..])?;
+    let observer = utils::observer_from_flags(&flags)?
+        .ok_or("A sky plot needs an observer (--observer or COSMOS_OBSERVER)")?;
+    let horizon = utils::number_flag(&flags, "horizon", 0.0)?;
+    let start = flags
+        .get("start")
+        .map_or(Ok(Utc::now()), |text| utils::parse_time(text))?;
+
+    let satellite = utils::resolve_one_satellite(identifier)?;
+
+    if flags.contains_key("live") {
+        return run_live(&satellite, observer, horizon);
+    }
+
+    let elements = utils::try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)?;
+    let (pass, path) = predict(&Propagator::new(elements)?, &observer, &start, horizon)?;
+    println!(
+        "{} on {}\n",
+        satellite.name,
+        pass.aos.with_timezone(&Local).format("%Y-%m-%d")
+    );
+    println!("{}", draw_pass(&pass, &path, None));
+    Ok(())
+}
+
+pub fn sky_ui(satellite: &Satellite) {
+    let result = match utils::default_observer() {
+        Ok(Some(observer)) => run_live(satellite, observer, 0.0),
+        Ok(None) => Err("A sky plot needs an observer, set COSMOS_OBSERVER".to_string()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        utils::wait_for_enter();
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::env;
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+
+use chrono::{DateTime, Duration, Local, SecondsFormat, Utc};
+use serde_json::json;
+
+use crate::cli::utils;
+use crate::libs::satellite_watch_service::{self, Alert, AlertKind, WatchEvent, WatchOptions};
+
+const DEFAULT_LEAD_MINUTES: f64 = 10.0;
+const DEFAULT_LOOKAHEAD_DAYS: f64 = 1.0;
+const DEFAULT_REFRESH_HOURS: f64 = 6.0;
+
+fn options_from_flags(flags: &HashMap<String, String>) -> Result<WatchOptions, String> {
+    let minutes = |value: f64| Duration::milliseconds((value * 60_000.0) as i64);
+    let lead = minutes(utils::number_flag(flags, "before", DEFAULT_LEAD_MINUTES)?);
+    let lookahead = minutes(utils::number_flag(flags, "days", DEFAULT_LOOKAHEAD_DAYS)? * 1440.0);
+    let refresh = minutes(utils::number_flag(flags, "refresh", DEFAULT_REFRESH_HOURS)? * 60.0);
+
+    if lead < Duration::zero() {
+        return Err("--before can't be negative".to_string());
+    }
+    if lookahead <= Duration::zero() || refresh <= Duration::zero() {
+        return Err("--days and --refresh have to be positive".to_string());
+    }
+
+    Ok(WatchOptions {
+        horizon: utils::number_flag(flags, "horizon", 0.0)?,
+        lead,
+        lookahead,
+        refresh,
+    })
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// The pass details handed to hook commands
+fn alert_env(alert: &Alert) -> Vec<(&'static str, String)> {
+    let location = &alert.station.location;
+    vec![
+        ("COSMOS_EVENT", alert.kind.label().to_string()),
+        ("COSMOS_EVENT_TIME", format_time(&alert.time)),
+        ("COSMOS_NORAD_ID", alert.satellite.id.to_string()),
+        ("COSMOS_SATELLITE", alert.satellite.name.clone()),
+        ("COSMOS_STATION", alert.station.name.clone()),
+        (
+            "COSMOS_STATION_LOCATION",
+            format!(
+                "{},{},{}",
+                location.latitude,
+                location.longitude,
+                location.altitude_km * 1000.0
+            ),
+        ),
+        ("COSMOS_AOS", format_time(&alert.pass.aos)),
+        ("COSMOS_TCA", format_time(&alert.pass.tca)),
+        ("COSMOS_LOS", format_time(&alert.pass.los)),
+        (
+            "COSMOS_AOS_AZIMUTH",
+            format!("{:.1}", alert.pass.aos_azimuth),
+        ),
+        (
+            "COSMOS_TCA_AZIMUTH",
+            format!("{:.1}", alert.pass.tca_azimuth),
+        ),
+        (
+            "COSMOS_LOS_AZIMUTH",
+            format!("{:.1}", alert.pass.los_azimuth),
+        ),
+        (
+            "COSMOS_MAX_ELEVATION",
+            format!("{:.1}", alert.pass.max_elevation),
+        ),
+    ]
+}
+
+fn alert_json(alert: &Alert) -> serde_json::Value {
+    json!({
+        "event": alert.kind.label(),
+        "time": format_time(&alert.time),
+        "norad_id": alert.satellite.id,
+        "name": alert.satellite.name,
+        "station": alert.station.name,
+        "latitude": alert.station.location.latitude,
+        "longitude": alert.station.location.longitude,
+        "aos": format_time(&alert.pass.aos),
+        "tca": format_time(&alert.pass.tca),
+        "los": format_time(&alert.pass.los),
+        "aos_azimuth": alert.pass.aos_azimuth,
+        "tca_azimuth": alert.pass.tca_azimuth,
+        "los_azimuth": alert.pass.los_azimuth,
+        "max_elevation": alert.pass.max_elevation,
+    })
+}
+
+fn print_alert(alert: &Alert) {
+    let what = match alert.kind {
+        AlertKind::Warning => format!(
+            "rises in {} min",
+            (alert.pass.aos - Utc::now()).num_minutes().max(0) + 1
+        ),
+        AlertKind::Aos => format!("AOS at az {:.0}°", alert.pass.aos_azimuth),
+        AlertKind::Tca => format!(
+            "TCA at az {:.0}°, el {:.1}°",
+            alert.pass.tca_azimuth, alert.pass.max_elevation
+        ),
+        AlertKind::Los => format!("LOS at az {:.0}°", alert.pass.los_azimuth),
+    };
+    println!(
+        "{}  {} over {}: {} (AOS {}, LOS {}, max el {:.1}°)",
+        alert.time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+        alert.satellite.name,
+        alert.station.name,
+        what,
+        alert.pass.aos.with_timezone(&Local).format("%H:%M:%S"),
+        alert.pass.los.with_timezone(&Local).format("%H:%M:%S"),
+        alert.pass.max_elevation
+    );
+}
+
+// Runs a hook through the shell without waiting for it, a failure is only reported
+fn run_hook(command: &str, alert: &Alert) {
+    #[cfg(windows)]
+    let mut process = {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    };
+    #[cfg(not(windows))]
+    let mut process = {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+
+    match process.envs(alert_env(alert)).spawn() {
+        Ok(mut child) => {
+            let command = command.to_string();
+            thread::spawn(move || match child.wait() {
+                Ok(status) if !status.success() => {
+                    eprintln!("Hook '{}' exited with {}", command, status)
+                }
+                Err(err) => eprintln!("Error waiting for hook '{}': {}", command, err),
+                _ => {}
+            });
+        }
+        Err(err) => eprintln!("Error running hook '{}': {}", command, err),
+    }
+}
+
+// Non-interactive mode: `cosmos_cli watch 25544,43017 --observer "home=52.52,13.40,34"
+// --before 10 --on-aos "notify-send \"$COSMOS_SATELLITE rising\""`, or `--format ndjson`
+// to write every alert to stdout as a JSON line
+pub fn watch_command(args: &[String]) -> Result<(), String> {
+    let (watchlist, flags) = match args.first() {
+        Some(first) if !first.starts_with("--") => (first.clone(), utils::parse_flags(&args[1..])?),
+        _ => (
+            env::var("COSMOS_WATCHLIST")
+                .map_err(|_| "Missing NORAD IDs to watch (or set COSMOS_WATCHLIST)")?,
+            utils::parse_flags(args)?,
+        ),
+    };
+
+    let stations = utils::stations_from_flags(&flags)?;
+    if stations.is_empty() {
+        return Err(
+            "Watching passes needs an observer (--observer or COSMOS_OBSERVER)".to_string(),
+        );
+    }
+
+    let options = options_from_flags(&flags)?;
+    let ndjson = match flags.get("format").map(String::as_str) {
+        None | Some("text") => false,
+        Some("ndjson") => true,
+        Some(other) => return Err(format!("Unknown format '{}' (text or ndjson)", other)),
+    };
+    let hook = |kind: AlertKind| {
+        flags
+            .get(&format!("on-{}", kind.label()))
+            .or(flags.get("exec"))
+    };
+
+    // Status messages go to stderr in NDJSON mode so stdout only carries alerts
+    let status = |message: String| {
+        if ndjson {
+            eprintln!("{}", message)
+        } else {
+            println!("{}", message)
+        }
+    };
+
+    status(format!(
+        "Watching {} from {}",
+        watchlist,
+        stations
+            .iter()
+            .map(|station| station.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    let running = AtomicBool::new(true);
+    satellite_watch_service::watch_passes(
+        &stations,
+        &options,
+        &running,
+        || {
+            utils::resolve_satellites(&watchlist)?
+                .into_iter()
+                .map(|satellite| {
+                    let elements = utils::try_parse_tle(
+                        &satellite.name,
+                        &satellite.line_one,
+                        &satellite.line_two,
+                    )?;
+                    Ok((satellite, elements))
+                })
+                .collect()
+        },
+        |event| match event {
+            WatchEvent::Predicted(count, until) => status(format!(
+                "{} upcoming events until {}",
+                count,
+                until.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            )),
+            WatchEvent::TleUpdated(satellite) => status(format!(
+                "New TLE for {} (epoch {}), predicting again",
+                satellite.name,
+                satellite.line_one.get(18..32).unwrap_or("?").trim()
+            )),
+            WatchEvent::Alert(alert) => {
+                if ndjson {
+                    println!("{}", alert_json(&alert));
+                } else {
+                    print_alert(&alert);
+                }
+                if let Some(command) = hook(alert.kind) {
+                    run_hook(command, &alert);
+                }
+            }
+            WatchEvent::Error(err) => eprintln!("{}", err),
+        },
+    )
+}
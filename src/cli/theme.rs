@@ -0,0 +1,139 @@
+use std::env;
+use std::sync::OnceLock;
+
+use crossterm::style::{Color, Stylize};
+use dialoguer::console::{self, style, Style};
+use dialoguer::theme::ColorfulTheme;
+
+// What a color is used for, so every screen picks it up from the same theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Muted,     // Hints, the map background, plot grids
+    Accent,    // Spinners and prompt markers
+    Highlight, // Selected or otherwise standing out rows
+    Warning,
+    Path,   // Orbit trails and pass paths
+    Tick,   // Time ticks along a path
+    Rise,   // AOS, success
+    Set,    // LOS
+    Marker, // The current position
+    Label,  // Compass points
+    Penumbra,
+    Umbra,
+}
+
+// ANSI 256-color indices by role, in the order of `Role`
+type Palette = [u8; 12];
+
+// The colors the CLI always had: bright ANSI colors on a dark background
+const DARK: Palette = [8, 13, 14, 9, 14, 11, 10, 9, 13, 15, 7, 8];
+// Darker colors that stay readable on a white background
+const LIGHT: Palette = [8, 5, 4, 1, 4, 130, 2, 1, 5, 0, 245, 238];
+const HIGH_CONTRAST: Palette = [7, 11, 11, 9, 14, 11, 10, 9, 13, 15, 11, 12];
+// Okabe-Ito colors: blues, orange, vermillion and purple instead of red against green
+const COLORBLIND: Palette = [8, 175, 74, 166, 74, 227, 36, 166, 214, 15, 7, 8];
+
+// None when nothing is colored (monochrome theme, NO_COLOR or output that isn't a terminal)
+static PALETTE: OnceLock<Option<Palette>> = OnceLock::new();
+
+/**
+Picks the theme from `COSMOS_THEME`, called once before anything is printed
+
+Colors stay off whenever console (and so dialoguer and indicatif) turns them off for stdout:
+`NO_COLOR` is set, `CLICOLOR=0`, `TERM=dumb` or stdout isn't a terminal, unless
+`CLICOLOR_FORCE` is set. The monochrome theme turns them off for the prompts as well.
+
+# Returns
+* An error for an unknown theme name
+*/
+pub fn init() -> Result<(), String> {
+    let name = env::var("COSMOS_THEME").unwrap_or_else(|_| "dark".to_string());
+    let palette = match name.trim().to_lowercase().as_str() {
+        "dark" => Some(DARK),
+        "light" => Some(LIGHT),
+        "high-contrast" => Some(HIGH_CONTRAST),
+        "colorblind" => Some(COLORBLIND),
+        "monochrome" | "mono" => None,
+        other => {
+            return Err(format!(
+            "Unknown theme '{}' (expected dark, light, high-contrast, colorblind or monochrome)",
+            other
+        ))
+        }
+    };
+
+    if palette.is_none() {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+    PALETTE
+        .set(palette.filter(|_| console::colors_enabled()))
+        .ok();
+    Ok(())
+}
+
+fn palette() -> Option<&'static Palette> {
+    PALETTE
+        .get_or_init(|| console::colors_enabled().then_some(DARK))
+        .as_ref()
+}
+
+fn index(role: Role) -> Option<u8> {
+    palette().map(|palette| palette[role as usize])
+}
+
+pub fn color(role: Role) -> Option<Color> {
+    index(role).map(Color::AnsiValue)
+}
+
+pub fn paint(text: &str, role: Role) -> String {
+    match color(role) {
+        Some(color) => text.with(color).to_string(),
+        None => text.to_string(),
+    }
+}
+
+// Bold goes together with colors, plain output has no escape codes at all
+pub fn bold(text: &str) -> String {
+    if palette().is_some() {
+        text.bold().to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+// Appended to an indicatif template key, `:.13` makes `{spinner:.13}`, nothing when colors are off
+pub fn template_style(role: Role) -> String {
+    index(role).map_or_else(String::new, |index| format!(":.{}", index))
+}
+
+// dialoguer's colorful theme in the theme's colors, console drops them when colors are off
+pub fn prompt_theme() -> ColorfulTheme {
+    let Some(palette) = palette() else {
+        return ColorfulTheme::default();
+    };
+    let styled = |role: Role| Style::new().for_stderr().color256(palette[role as usize]);
+    let symbol = |text: &str, role: Role| {
+        style(text.to_string())
+            .for_stderr()
+            .color256(palette[role as usize])
+    };
+
+    ColorfulTheme {
+        defaults_style: styled(Role::Highlight),
+        prompt_prefix: symbol("?", Role::Accent),
+        prompt_suffix: symbol("›", Role::Muted),
+        success_prefix: symbol("✔", Role::Rise),
+        success_suffix: symbol("·", Role::Muted),
+        error_prefix: symbol("✘", Role::Warning),
+        error_style: styled(Role::Warning),
+        hint_style: styled(Role::Muted),
+        values_style: styled(Role::Rise),
+        active_item_style: styled(Role::Highlight),
+        active_item_prefix: symbol("❯", Role::Rise),
+        checked_item_prefix: symbol("✔", Role::Rise),
+        unchecked_item_prefix: symbol("✔", Role::Muted),
+        picked_item_prefix: symbol("❯", Role::Rise),
+        ..ColorfulTheme::default()
+    }
+}
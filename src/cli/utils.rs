@@ -1,9 +1,15 @@
+use std::collections::HashMap;
+use std::env;
 use std::time::Duration;
 
+use dialoguer::console::Term;
 use image::GenericImageView;
 use indicatif::{ProgressBar, ProgressStyle};
 
-use chrono::{Timelike, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+
+// How far ahead the info view looks for the next eclipse entry and exit
+const ECLIPSE_LOOKAHEAD_HOURS: i64 = 24;
 
 pub fn print_satellite_info(satellite: &satellite_search_service::Satellite, lat: f64, lon: f64) {
     let tle = parse_tle(
@@ -22,37 +28,738 @@ pub fn print_satellite_info(satellite: &satellite_search_service::Satellite, lat
         current_utc_time_in_minutes as f64,
     );
 
-    println!("Name: {}", satellite.name);
+    let decay = satellite_decay_service::estimate_decay(&tle, &history_elements(satellite));
+    let derived = orbit::derive_parameters(&tle, &datetime);
+
+    print_section("Identification");
+    print_field("Name", satellite.name.clone());
+    print_field("NORAD ID", satellite.id.to_string());
+    print_field(
+        "International Designator",
+        tle.international_designator
+            .clone()
+            .unwrap_or("N/A".to_string()),
+    );
+    print_field(
+        "Epoch",
+        format!("{} ({:.2} days old)", tle.datetime, derived.epoch_age_days),
+    );
+    print_field("Orbit Regime", derived.regime.label().to_string());
+
+    if let Some(record) = satellite_satcat_service::get_record(satellite.id) {
+        let or_na = |value: Option<String>| value.unwrap_or("N/A".to_string());
+
+        print_section("Catalog Metadata");
+        print_field("Object Type", record.object_type.label().to_string());
+        print_field("Owner", or_na(record.owner.clone()));
+        print_field(
+            "Launch Date",
+            or_na(record.launch_date.map(|d| d.to_string())),
+        );
+        print_field("Launch Site", or_na(record.launch_site.clone()));
+        print_field("Status", record.ops_status.label().to_string());
+        print_field(
+            "RCS Size",
+            or_na(record.rcs_size.map(|s| s.label().to_string())),
+        );
+        if let Some(decay_date) = record.decay_date {
+            print_field("Decay Date", decay_date.to_string());
+        }
+    }
+
+    print_section("Orbital Elements");
+    print_field("Inclination", format!("{:.4} deg", tle.inclination));
+    print_field("RAAN", format!("{:.4} deg", tle.right_ascension));
+    print_field("Eccentricity", format!("{:.7}", tle.eccentricity));
+    print_field(
+        "Argument of Perigee",
+        format!("{:.4} deg", tle.argument_of_perigee),
+    );
+    print_field("Mean Anomaly", format!("{:.4} deg", tle.mean_anomaly));
+    print_field("Mean Motion", format!("{:.8} revs/day", tle.mean_motion));
+    print_field("Orbit Number", tle.revolution_number.to_string());
+
+    print_section("Derived Parameters");
+    print_field(
+        "Semi-major Axis",
+        format!("{:.1} km", derived.semi_major_axis_km),
+    );
+    print_field(
+        "Perigee Altitude",
+        format!("{:.1} km", derived.perigee_altitude_km),
+    );
+    print_field(
+        "Apogee Altitude",
+        format!("{:.1} km", derived.apogee_altitude_km),
+    );
+    print_field("Period", format!("{:.2} min", derived.period_minutes));
+    print_field(
+        "Velocity at Perigee",
+        format!("{:.3} km/s", derived.perigee_velocity_km_s),
+    );
+    print_field(
+        "Velocity at Apogee",
+        format!("{:.3} km/s", derived.apogee_velocity_km_s),
+    );
+
+    print_section("Current Position");
+    print_field(
+        "Distance from Earth",
+        format!("{:.2} km", distance.unwrap_or(0.0)),
+    );
+    print_field("Latitude", format!("{:.2} deg", lat));
+    print_field("Longitude", format!("{:.2} deg", lon));
+
+    if let Ok(periods) = orbit::Propagator::new(tle.clone()).and_then(|propagator| {
+        satellite_eclipse_service::eclipse_timeline(
+            &propagator,
+            &datetime,
+            &(datetime + chrono::Duration::hours(ECLIPSE_LOOKAHEAD_HOURS)),
+        )
+    }) {
+        print_eclipse_state(&periods, &datetime);
+    }
+
+    if let Some(decay) = decay {
+        print_section("Decay");
+        print_field(
+            "Decay Rate",
+            format!("{:.3} km/day", decay.decay_rate_km_per_day),
+        );
+        print_reentry_window(&decay);
+    }
+}
+
+// The current state and the next shadow entry and exit from an eclipse timeline starting now
+fn print_eclipse_state(periods: &[satellite_eclipse_service::EclipsePeriod], now: &DateTime<Utc>) {
+    let Some(current) = periods.first() else {
+        return;
+    };
+    let describe = |period: Option<&satellite_eclipse_service::EclipsePeriod>| match period {
+        Some(period) => format!(
+            "{} (in {} min)",
+            period.start.with_timezone(&Local).format("%H:%M:%S"),
+            (period.start - *now).num_minutes()
+        ),
+        None => format!("Not within {} h", ECLIPSE_LOOKAHEAD_HOURS),
+    };
+
+    print_section("Eclipse");
+    let state = current.state.label().to_string();
+    print_field(
+        "State",
+        match current.state {
+            orbit::EclipseState::Sunlit => state,
+            orbit::EclipseState::Penumbra => theme::paint(&state, Role::Penumbra),
+            orbit::EclipseState::Umbra => theme::paint(&state, Role::Umbra),
+        },
+    );
+
+    // Penumbra to umbra and back is not an entry or exit, only crossing the sunlit boundary is
+    let crossing = |into_shadow: bool| {
+        periods
+            .windows(2)
+            .map(|pair| (&pair[0], &pair[1]))
+            .find(|(before, after)| {
+                let sunlit = |p: &satellite_eclipse_service::EclipsePeriod| {
+                    p.state == orbit::EclipseState::Sunlit
+                };
+                sunlit(before) == into_shadow && sunlit(after) != into_shadow
+            })
+            .map(|(_, after)| after)
+    };
+    print_field("Enters Shadow", describe(crossing(true)));
+    print_field("Back in Sunlight", describe(crossing(false)));
+}
+
+fn print_section(title: &str) {
+    println!("\n{}", theme::bold(title));
+}
+
+fn print_field(label: &str, value: String) {
+    println!("  {:<26} {}", format!("{}:", label), value);
+}
+
+fn reentry_alert_days() -> i64 {
+    env::var("COSMOS_REENTRY_ALERT_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<i64>().ok())
+        .unwrap_or(30)
+}
+
+fn print_reentry_window(decay: &satellite_decay_service::DecayEstimate) {
+    let Some(reentry) = &decay.reentry else {
+        print_field("Estimated Re-entry", "not within 25 years".to_string());
+        return;
+    };
+
+    print_field(
+        "Estimated Re-entry",
+        format!(
+            "{} (between {} and {})",
+            reentry.nominal.format("%Y-%m-%d"),
+            reentry.earliest.format("%Y-%m-%d"),
+            reentry.latest.format("%Y-%m-%d")
+        ),
+    );
+
+    let days_left = (reentry.nominal - Utc::now()).num_days();
+    if days_left <= reentry_alert_days() {
+        println!(
+            "  {}",
+            theme::paint(
+                &format!(
+                    "WARNING: expected to re-enter within {} days",
+                    days_left.max(0)
+                ),
+                Role::Warning
+            )
+        );
+    }
+}
+
+pub fn print_decay_estimate(decay: &satellite_decay_service::DecayEstimate) {
+    const BAR_WIDTH: f64 = 40.0;
+
+    let source = match decay.source {
+        satellite_decay_service::DecaySource::History(count) => {
+            format!("fit over {} historical TLEs", count)
+        }
+        satellite_decay_service::DecaySource::MeanMotionDerivative => {
+            "mean motion derivative".to_string()
+        }
+        satellite_decay_service::DecaySource::Bstar => "BSTAR drag term".to_string(),
+    };
+
+    print_field("Mean Altitude", format!("{:.1} km", decay.altitude_km));
+    print_field(
+        "Decay Rate",
+        format!("{:.3} km/day ({})", decay.decay_rate_km_per_day, source),
+    );
+    print_reentry_window(decay);
+
+    println!("\n{:<12} {:>9} {:>19}", "Date", "Alt (km)", "Band (km)");
+    let top = decay.trend.first().map(|p| p.high_km).unwrap_or(0.0);
+    for point in &decay.trend {
+        // Bars are scaled so the starting altitude fills the whole width
+        let low = (point.low_km / top * BAR_WIDTH).round() as usize;
+        let nominal = (point.altitude_km / top * BAR_WIDTH).round() as usize;
+        let high = (point.high_km / top * BAR_WIDTH).round() as usize;
+        let bar = format!(
+            "{}{}{}",
+            "#".repeat(low),
+            "=".repeat(nominal.saturating_sub(low)),
+            "-".repeat(high.saturating_sub(nominal.max(low)))
+        );
+
+        println!(
+            "{:<12} {:>9.1} {:>8.1} - {:>8.1} {}",
+            point.time.format("%Y-%m-%d"),
+            point.altitude_km,
+            point.low_km,
+            point.high_km,
+            bar
+        );
+    }
+}
+
+pub fn print_conjunctions(conjunctions: &[satellite_conjunction_service::Conjunction]) {
+    println!(
+        "{:<24} {:>7} {:<20} {:>10} {:>9} {:>9} {:>9} {:>9}",
+        "Object", "NORAD", "TCA (UTC)", "Miss (km)", "R (km)", "I (km)", "C (km)", "Vrel km/s"
+    );
+    for conjunction in conjunctions {
+        let [radial, in_track, cross_track] = conjunction.ric_separation_km;
+        println!(
+            "{:<24} {:>7} {:<20} {:>10.3} {:>9.3} {:>9.3} {:>9.3} {:>9.3}",
+            conjunction.name.chars().take(24).collect::<String>(),
+            conjunction.norad_id,
+            conjunction.tca.format("%Y-%m-%d %H:%M:%S"),
+            conjunction.miss_distance_km,
+            radial,
+            in_track,
+            cross_track,
+            conjunction.relative_speed_km_s
+        );
+    }
+}
+
+pub fn print_pass_table(passes: &[satellite_pass_service::StationPass]) {
     println!(
-        "International Designator: {}",
-        tle.international_designator.unwrap_or("N/A".to_string())
+        "{}",
+        theme::bold(&format!(
+            "{:<24} {:<14} {:<19} {:>8} {:>8} {:>7} {:>6} {:>6} {:>6}",
+            "Satellite", "Station", "AOS", "TCA", "LOS", "Max El", "AOS Az", "TCA Az", "LOS Az"
+        ))
     );
-    println!("Epoch: {}", tle.datetime);
-    println!("Inclination: {} degrees", tle.inclination);
-    println!("RAAN: {:.2} degrees", tle.right_ascension);
-    println!("Eccentricity: {}", tle.eccentricity);
-    println!("Perigee: {:.2} degrees", tle.argument_of_perigee);
-    println!("Mean Motion: {:.2} revs per day", tle.mean_motion);
-    println!("Orbit Number: {}", tle.revolution_number);
-    println!("Distance from Earth: {:.2} km", distance.unwrap_or(0.0));
-    println!("Latitude: {:.2} degrees", lat);
-    println!("Longitude: {:.2} degrees", lon);
+    for entry in passes {
+        let pass = &entry.pass;
+        println!(
+            "{:<24} {:<14} {:<19} {:>8} {:>8} {:>7.1} {:>6.0} {:>6.0} {:>6.0}",
+            entry.satellite.name.chars().take(24).collect::<String>(),
+            entry.station.name.chars().take(14).collect::<String>(),
+            pass.aos.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            pass.tca.with_timezone(&Local).format("%H:%M:%S"),
+            pass.los.with_timezone(&Local).format("%H:%M:%S"),
+            pass.max_elevation,
+            pass.aos_azimuth,
+            pass.tca_azimuth,
+            pass.los_azimuth
+        );
+    }
+}
+
+pub fn print_ephemeris_table(rows: &[satellite_ephemeris_service::EphemerisRow], frame: &str) {
+    let with_look = rows.iter().any(|row| row.look.is_some());
+
+    let mut header = format!(
+        "{:<19} {:>11} {:>11} {:>11} {:>9} {:>9} {:>9} {:>8} {:>9} {:>9}",
+        "Time (UTC)",
+        format!("X {} km", frame),
+        "Y km",
+        "Z km",
+        "VX km/s",
+        "VY km/s",
+        "VZ km/s",
+        "Lat",
+        "Lon",
+        "Alt km"
+    );
+    if with_look {
+        header.push_str(&format!(" {:>7} {:>7} {:>10}", "Az", "El", "Range km"));
+    }
+    println!("{}", theme::bold(&header));
+
+    for row in rows {
+        let [x, y, z] = row.state.position;
+        let [vx, vy, vz] = row.state.velocity;
+        let mut line = format!(
+            "{:<19} {:>11.3} {:>11.3} {:>11.3} {:>9.4} {:>9.4} {:>9.4} {:>8.3} {:>9.3} {:>9.2}",
+            row.time.format("%Y-%m-%d %H:%M:%S"),
+            x,
+            y,
+            z,
+            vx,
+            vy,
+            vz,
+            row.geodetic.latitude,
+            row.geodetic.longitude,
+            row.geodetic.altitude_km
+        );
+        if let Some(look) = &row.look {
+            line.push_str(&format!(
+                " {:>7.2} {:>7.2} {:>10.2}",
+                look.azimuth, look.elevation, look.range_km
+            ));
+            // Only the part of the table where the satellite is actually visible stands out
+            if look.elevation > 0.0 {
+                line = theme::paint(&line, Role::Highlight);
+            }
+        }
+        println!("{}", line);
+    }
+}
+
+// Scatter chart of (x, y) points with the y range labelled on the left
+pub fn print_ascii_chart(title: &str, points: &[(f64, f64)], width: usize, height: usize) {
+    const LABEL_WIDTH: usize = 12;
+
+    println!("{}", title);
+    if points.is_empty() {
+        println!("{:>width$}", "(no data)", width = LABEL_WIDTH);
+        return;
+    }
+
+    let min_x = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut grid = vec![vec![' '; width]; height];
+    for (x, y) in points {
+        let column = if max_x > min_x {
+            ((x - min_x) / (max_x - min_x) * (width - 1) as f64).round() as usize
+        } else {
+            0
+        };
+        let row = if max_y > min_y {
+            ((max_y - y) / (max_y - min_y) * (height - 1) as f64).round() as usize
+        } else {
+            height / 2
+        };
+        grid[row][column] = '*';
+    }
+
+    let format_label = |value: f64| {
+        if value == 0.0 || value.abs() >= 1.0 {
+            format!("{:>width$.3}", value, width = LABEL_WIDTH)
+        } else {
+            format!("{:>width$.3e}", value, width = LABEL_WIDTH)
+        }
+    };
+
+    for (i, row) in grid.iter().enumerate() {
+        let label = if i == 0 {
+            format_label(max_y)
+        } else if i == height - 1 {
+            format_label(min_y)
+        } else {
+            " ".repeat(LABEL_WIDTH)
+        };
+        println!("{} |{}", label, row.iter().collect::<String>());
+    }
+    println!("{} +{}", " ".repeat(LABEL_WIDTH), "-".repeat(width));
+}
+
+// Parses a list of satellites, leaving out the ones with malformed TLEs
+pub fn parse_catalog(
+    satellites: &[satellite_search_service::Satellite],
+) -> Vec<satellite_catalog_service::CatalogEntry> {
+    satellites
+        .iter()
+        .filter_map(|satellite| {
+            try_parse_tle(&satellite.name, &satellite.line_one, &satellite.line_two)
+                .ok()
+                .map(|elements| satellite_catalog_service::CatalogEntry {
+                    satellite: satellite.clone(),
+                    elements,
+                })
+        })
+        .collect()
+}
+
+pub fn print_catalog_table(entries: &[&satellite_catalog_service::CatalogEntry]) {
+    let now = Utc::now();
+
+    println!(
+        "{:>7} {:<24} {:<8} {:>8} {:>9} {:>9} {:>9} {:>8}",
+        "NORAD", "Name", "Regime", "Incl", "Perigee", "Apogee", "Period", "Age (d)"
+    );
+    for entry in entries {
+        let derived = orbit::derive_parameters(&entry.elements, &now);
+        println!(
+            "{:>7} {:<24} {:<8} {:>8.3} {:>9.1} {:>9.1} {:>9.2} {:>8.2}",
+            entry.satellite.id,
+            entry.satellite.name.chars().take(24).collect::<String>(),
+            derived.regime.label(),
+            entry.elements.inclination,
+            derived.perigee_altitude_km,
+            derived.apogee_altitude_km,
+            derived.period_minutes,
+            derived.epoch_age_days
+        );
+    }
+}
+
+pub fn print_query_result(result: &satellite_query_service::QueryResult) {
+    let cells: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|value| value.to_string()).collect())
+        .collect();
+    let widths: Vec<usize> = result
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            cells
+                .iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(field.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let header: Vec<String> = result
+        .fields
+        .iter()
+        .zip(&widths)
+        .map(|(field, width)| format!("{:<width$}", field, width = width))
+        .collect();
+    println!("{}", theme::bold(&header.join("  ")));
+
+    for (row, values) in cells.iter().zip(&result.rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(values)
+            .zip(&widths)
+            .map(|((cell, value), width)| match value {
+                satellite_query_service::Value::Number(_) => {
+                    format!("{:>width$}", cell, width = width)
+                }
+                _ => format!("{:<width$}", cell, width = width),
+            })
+            .collect();
+        println!("{}", line.join("  "));
+    }
+}
+
+// Splits `--key value` pairs; a flag without a value is read as "true"
+pub fn parse_flags(args: &[String]) -> Result<HashMap<String, String>, String> {
+    let mut flags = HashMap::new();
+    let mut args = args.iter().peekable();
+
+    while let Some(arg) = args.next() {
+        let Some(key) = arg.strip_prefix("--") else {
+            return Err(format!("Unexpected argument '{}'", arg));
+        };
+        let value = match args.peek() {
+            Some(next) if !next.starts_with("--") => args.next().unwrap().clone(),
+            _ => "true".to_string(),
+        };
+        flags.insert(key.to_string(), value);
+    }
+
+    Ok(flags)
+}
+
+pub fn number_flag(
+    flags: &HashMap<String, String>,
+    key: &str,
+    default: f64,
+) -> Result<f64, String> {
+    flags.get(key).map_or(Ok(default), |text| {
+        text.parse::<f64>()
+            .map_err(|_| format!("Invalid --{} '{}'", key, text))
+    })
+}
+
+// Accepts `now`, RFC 3339 or a UTC date/time such as `2024-05-01 12:00`
+pub fn parse_time(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("now") {
+        return Ok(Utc::now());
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+    .map(|time| Utc.from_utc_datetime(&time))
+    .ok_or_else(|| {
+        format!(
+            "Invalid time '{}' (expected e.g. 2024-05-01 12:00 or now)",
+            text
+        )
+    })
+}
+
+// Parses an observer location written as `LAT,LON[,ALTITUDE_M]`
+pub fn parse_observer(text: &str) -> Result<orbit::Geodetic, String> {
+    let values = text
+        .split(',')
+        .map(|value| value.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| {
+            format!(
+                "Invalid observer '{}' (expected LAT,LON[,ALTITUDE_M])",
+                text
+            )
+        })?;
+
+    match values[..] {
+        [latitude, longitude] | [latitude, longitude, _]
+            if latitude.abs() > 90.0 || longitude.abs() > 180.0 =>
+        {
+            Err(format!("Observer '{}' is out of range", text))
+        }
+        [latitude, longitude] => Ok(orbit::Geodetic {
+            latitude,
+            longitude,
+            altitude_km: 0.0,
+        }),
+        [latitude, longitude, altitude_m] => Ok(orbit::Geodetic {
+            latitude,
+            longitude,
+            altitude_km: altitude_m / 1000.0,
+        }),
+        _ => Err(format!(
+            "Invalid observer '{}' (expected LAT,LON[,ALTITUDE_M])",
+            text
+        )),
+    }
+}
+
+// Stations separated by `;`, each `LAT,LON[,ALT_M]` with an optional `NAME=` in front
+pub fn parse_stations(text: &str) -> Result<Vec<satellite_pass_service::Station>, String> {
+    text.split(';')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let (name, location) = match part.split_once('=') {
+                Some((name, location)) => (name.trim(), location),
+                None => (part.trim(), part),
+            };
+            Ok(satellite_pass_service::Station {
+                name: name.to_string(),
+                location: parse_observer(location)?,
+            })
+        })
+        .collect()
+}
+
+// Parses a frequency in Hz, with an optional k, M or G suffix (e.g. 145.8M, -1.2k)
+pub fn parse_frequency(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    let (number, multiplier) = match text.chars().last() {
+        Some('k' | 'K') => (&text[..text.len() - 1], 1e3),
+        Some('M') => (&text[..text.len() - 1], 1e6),
+        Some('G' | 'g') => (&text[..text.len() - 1], 1e9),
+        _ => (text, 1.0),
+    };
+
+    number
+        .trim()
+        .parse::<f64>()
+        .map(|value| value * multiplier)
+        .map_err(|_| format!("Invalid frequency '{}' (e.g. 145800000 or 145.8M)", text))
+}
+
+// The observer given with `--observer`, falling back to `COSMOS_OBSERVER`
+pub fn observer_from_flags(
+    flags: &HashMap<String, String>,
+) -> Result<Option<orbit::Geodetic>, String> {
+    match flags.get("observer") {
+        Some(text) => parse_observer(text).map(Some),
+        None => default_observer(),
+    }
+}
+
+// Every station given with `--observer` or `COSMOS_OBSERVER`, separated by `;`
+pub fn stations_from_flags(
+    flags: &HashMap<String, String>,
+) -> Result<Vec<satellite_pass_service::Station>, String> {
+    match flags.get("observer") {
+        Some(text) => parse_stations(text),
+        None => parse_stations(&env::var("COSMOS_OBSERVER").unwrap_or_default()),
+    }
+}
+
+pub fn default_observer() -> Result<Option<orbit::Geodetic>, String> {
+    env::var("COSMOS_OBSERVER")
+        .ok()
+        .map(|text| parse_observer(&text))
+        .transpose()
+}
+
+// Resolves comma separated NORAD IDs / COSPAR designators given on the command line
+pub fn resolve_satellites(text: &str) -> Result<Vec<satellite_search_service::Satellite>, String> {
+    let mut satellites = Vec::new();
+    for part in text.split(',') {
+        let identifier = satellite_search_service::parse_identifier(part)
+            .ok_or_else(|| format!("'{}' is neither a NORAD ID nor a COSPAR designator", part))?;
+        let found = satellite_search_service::lookup_satellites(&identifier);
+        if found.is_empty() {
+            return Err(format!("No satellite found for {}", identifier));
+        }
+        satellites.extend(found);
+    }
+
+    Ok(satellites)
+}
+
+// For commands working on a single object, when the identifier names a whole launch
+pub fn ambiguous_identifier(identifier: &str, count: usize) -> String {
+    format!(
+        "{} matches {} objects, pick one piece (e.g. {}A)",
+        identifier, count, identifier
+    )
+}
+
+// Resolves a NORAD ID or COSPAR designator that has to name exactly one object
+pub fn resolve_one_satellite(text: &str) -> Result<satellite_search_service::Satellite, String> {
+    let mut satellites = resolve_satellites(text)?;
+    if satellites.len() > 1 {
+        return Err(ambiguous_identifier(text, satellites.len()));
+    }
+    Ok(satellites.remove(0))
+}
+
+// Parses every stored element set of a satellite, oldest first
+pub fn history_elements(satellite: &satellite_search_service::Satellite) -> Vec<sgp4::Elements> {
+    satellite_history_service::get_history(satellite.id)
+        .iter()
+        .filter_map(|entry| {
+            try_parse_tle(
+                &entry.satellite.name,
+                &entry.satellite.line_one,
+                &entry.satellite.line_two,
+            )
+            .ok()
+        })
+        .collect()
+}
+
+pub fn print_maneuvers(maneuvers: &[satellite_maneuver_service::Maneuver]) {
+    println!(
+        "{:<17} {:<16} {:>9} {:>9} {:>9} {:>10}",
+        "Approx. Time", "Kind", "da (km)", "di (deg)", "dRAAN", "dv (m/s)"
+    );
+    for maneuver in maneuvers {
+        println!(
+            "{:<17} {:<16} {:>9.3} {:>9.4} {:>9.4} {:>10.2}",
+            maneuver.time.format("%Y-%m-%d %H:%M"),
+            maneuver.kind.label(),
+            maneuver.semi_major_axis_change_km,
+            maneuver.inclination_change_deg,
+            maneuver.raan_change_deg,
+            maneuver.delta_v_m_s
+        );
+        println!(
+            "{:<17} between {} and {}",
+            "",
+            maneuver.after.format("%Y-%m-%d %H:%M"),
+            maneuver.before.format("%Y-%m-%d %H:%M")
+        );
+    }
+}
+
+pub fn wait_for_enter() {
+    let term = Term::stdout();
+    term.write_line("\nPress Enter to return...").unwrap();
+    term.read_line().unwrap();
 }
 
 pub fn parse_tle(satellite_name: &str, line1: &str, line2: &str) -> sgp4::Elements {
-    let element = sgp4::Elements::from_tle(
+    try_parse_tle(satellite_name, line1, line2).unwrap()
+}
+
+// Same as parse_tle, but for catalog-sized inputs where one malformed entry shouldn't abort everything
+pub fn try_parse_tle(
+    satellite_name: &str,
+    line1: &str,
+    line2: &str,
+) -> Result<sgp4::Elements, String> {
+    sgp4::Elements::from_tle(
         Some(satellite_name.to_owned()),
         line1.as_bytes(),
         line2.as_bytes(),
     )
-    .unwrap();
-
-    element
+    .map_err(|e| e.to_string())
 }
 
 use sgp4::{Constants, Elements};
 
-use crate::libs::satellite_search_service;
+use crate::cli::theme::{self, Role};
+
+use crate::libs::{
+    orbit, satellite_catalog_service, satellite_conjunction_service, satellite_decay_service,
+    satellite_eclipse_service, satellite_ephemeris_service, satellite_history_service,
+    satellite_maneuver_service, satellite_pass_service, satellite_query_service,
+    satellite_satcat_service, satellite_search_service,
+};
 
 pub fn compute_satellite_distance_from_earth(
     satellite_name: &str,
@@ -91,7 +798,10 @@ pub fn display_spinner(message: String, duration: Option<u64>) -> ProgressBar {
     pb.set_style(
         ProgressStyle::default_spinner()
             .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.magenta} {msg:.magenta}")
+            .template(&format!(
+                "{{spinner{0}}} {{msg{0}}}",
+                theme::template_style(Role::Accent)
+            ))
             .unwrap(),
     );
     pb.enable_steady_tick(Duration::from_millis(duration));
@@ -100,10 +810,25 @@ pub fn display_spinner(message: String, duration: Option<u64>) -> ProgressBar {
     pb
 }
 
-pub fn convert_image_to_ascii(image_src: &str, width: u32, height: u32) -> String {
+// Colors every character of an ASCII drawing by a palette, anything not in it gets `default`
+pub fn colorize_ascii(text: &str, palette: &[(char, Role)], default: Role) -> String {
+    text.chars()
+        .map(|c| {
+            let role = palette
+                .iter()
+                .find(|(marker, _)| *marker == c)
+                .map_or(default, |(_, role)| *role);
+            theme::paint(&c.to_string(), role)
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+// Converts an image stretched to `width` × `height` into ASCII by brightness
+pub fn convert_image_to_ascii(image_src: &str, width: u32, height: u32) -> Result<String, String> {
     let image = image::open(image_src)
-        .unwrap()
-        .resize(width, height, image::imageops::FilterType::Nearest)
+        .map_err(|e| format!("Can't open the map image '{}': {}", image_src, e))?
+        .resize_exact(width, height, image::imageops::FilterType::Nearest)
         .grayscale(); // Convert the image to grayscale
     let pixels = image.pixels();
     let mut ascii_image = String::new();
@@ -133,5 +858,5 @@ pub fn convert_image_to_ascii(image_src: &str, width: u32, height: u32) -> Strin
         })
         .collect();
 
-    ascii_image_with_newlines
+    Ok(ascii_image_with_newlines)
 }
@@ -1,2 +1,28 @@
+pub mod orbit;
+pub mod satellite_beta_service;
+pub mod satellite_calendar_service;
+pub mod satellite_catalog_service;
+pub mod satellite_conjunction_service;
+pub mod satellite_decay_service;
+pub mod satellite_eclipse_service;
+pub mod satellite_ephemeris_service;
+pub mod satellite_export_service;
+pub mod satellite_filter_service;
+pub mod satellite_hamlib_service;
+pub mod satellite_history_service;
+pub mod satellite_index_service;
+pub mod satellite_maneuver_service;
+pub mod satellite_oem_service;
+pub mod satellite_pass_service;
 pub mod satellite_position_service;
+pub mod satellite_query_service;
+pub mod satellite_radio_service;
+pub mod satellite_rotator_service;
+pub mod satellite_satcat_service;
 pub mod satellite_search_service;
+pub mod satellite_track_service;
+pub mod satellite_tracking_service;
+pub mod satellite_watch_service;
+#[cfg(test)]
+mod test_support;
+pub mod world_map;
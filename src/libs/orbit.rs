@@ -0,0 +1,644 @@
+use chrono::{DateTime, TimeZone, Utc};
+use sgp4::{Constants, Elements, MinutesSinceEpoch};
+
+pub const EARTH_RADIUS_KM: f64 = 6378.137;
+pub const EARTH_MU: f64 = 398600.4418; // km^3/s^2
+pub const EARTH_J2: f64 = 1.08262668e-3;
+pub const SECONDS_PER_DAY: f64 = 86400.0;
+pub const EARTH_FLATTENING: f64 = 1.0 / 298.257223563; // WGS84
+pub const EARTH_ROTATION_RAD_S: f64 = 7.292115e-5;
+pub const ASTRONOMICAL_UNIT_KM: f64 = 149_597_870.7;
+pub const SUN_RADIUS_KM: f64 = 696_000.0;
+pub const MOON_RADIUS_KM: f64 = 1737.4;
+
+pub type Vector3 = [f64; 3];
+
+const ARCSEC_TO_RAD: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+// Largest terms of the IAU 1980 nutation series: multipliers of l, l', F, D, Ω and the
+// longitude / obliquity coefficients in 0.0001" (constant, per Julian century)
+const NUTATION_TERMS: [([f64; 5], [f64; 4]); 18] = [
+    ([0.0, 0.0, 0.0, 0.0, 1.0], [-171996.0, -174.2, 92025.0, 8.9]),
+    ([0.0, 0.0, 2.0, -2.0, 2.0], [-13187.0, -1.6, 5736.0, -3.1]),
+    ([0.0, 0.0, 2.0, 0.0, 2.0], [-2274.0, -0.2, 977.0, -0.5]),
+    ([0.0, 0.0, 0.0, 0.0, 2.0], [2062.0, 0.2, -895.0, 0.5]),
+    ([0.0, 1.0, 0.0, 0.0, 0.0], [1426.0, -3.4, 54.0, -0.1]),
+    ([1.0, 0.0, 0.0, 0.0, 0.0], [712.0, 0.1, -7.0, 0.0]),
+    ([0.0, 1.0, 2.0, -2.0, 2.0], [-517.0, 1.2, 224.0, -0.6]),
+    ([0.0, 0.0, 2.0, 0.0, 1.0], [-386.0, -0.4, 200.0, 0.0]),
+    ([1.0, 0.0, 2.0, 0.0, 2.0], [-301.0, 0.0, 129.0, -0.1]),
+    ([0.0, -1.0, 2.0, -2.0, 2.0], [217.0, -0.5, -95.0, 0.3]),
+    ([1.0, 0.0, 0.0, -2.0, 0.0], [-158.0, 0.0, 0.0, 0.0]),
+    ([0.0, 0.0, 2.0, -2.0, 1.0], [129.0, 0.1, -70.0, 0.0]),
+    ([-1.0, 0.0, 2.0, 0.0, 2.0], [123.0, 0.0, -53.0, 0.0]),
+    ([1.0, 0.0, 0.0, 0.0, 1.0], [63.0, 0.1, -33.0, 0.0]),
+    ([0.0, 0.0, 0.0, 2.0, 0.0], [63.0, 0.0, -2.0, 0.0]),
+    ([-1.0, 0.0, 2.0, 2.0, 2.0], [-59.0, 0.0, 26.0, 0.0]),
+    ([-1.0, 0.0, 0.0, 0.0, 1.0], [-58.0, -0.1, 32.0, 0.0]),
+    ([1.0, 0.0, 2.0, 0.0, 1.0], [-51.0, 0.0, 27.0, 0.0]),
+];
+
+// Sun-synchronous orbits precess their node once per year
+const SUN_SYNCHRONOUS_DRIFT_DEG_PER_DAY: f64 = 360.0 / 365.2422;
+const LEO_MAX_ALTITUDE_KM: f64 = 2000.0;
+const GEO_ALTITUDE_KM: f64 = 35786.0;
+
+// Sunlight reaching a satellite, ordered from full light to full shadow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EclipseState {
+    Sunlit,
+    Penumbra,
+    Umbra,
+}
+
+impl EclipseState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EclipseState::Sunlit => "Sunlit",
+            EclipseState::Penumbra => "Penumbra",
+            EclipseState::Umbra => "Umbra",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitRegime {
+    Leo,
+    Sso,
+    Meo,
+    Geo,
+    Heo,
+    Molniya,
+    Other,
+}
+
+impl OrbitRegime {
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrbitRegime::Leo => "LEO",
+            OrbitRegime::Sso => "SSO",
+            OrbitRegime::Meo => "MEO",
+            OrbitRegime::Geo => "GEO",
+            OrbitRegime::Heo => "HEO",
+            OrbitRegime::Molniya => "Molniya",
+            OrbitRegime::Other => "Other",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DerivedParameters {
+    pub semi_major_axis_km: f64,
+    pub perigee_altitude_km: f64,
+    pub apogee_altitude_km: f64,
+    pub period_minutes: f64,
+    pub perigee_velocity_km_s: f64,
+    pub apogee_velocity_km_s: f64,
+    pub epoch_age_days: f64,
+    pub regime: OrbitRegime,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct State {
+    pub position: Vector3, // TEME, km
+    pub velocity: Vector3, // TEME, km/s
+}
+
+// WGS84 latitude / longitude in degrees, altitude above the ellipsoid in km
+#[derive(Debug, Clone, Copy)]
+pub struct Geodetic {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude_km: f64,
+}
+
+// Azimuth / elevation in degrees as seen from an observer, range rate is positive when receding
+#[derive(Debug, Clone, Copy)]
+pub struct LookAngles {
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub range_km: f64,
+    pub range_rate_km_s: f64,
+}
+
+// Wraps the SGP4 constants of a single element set so it can be propagated to wall-clock times
+pub struct Propagator {
+    pub elements: Elements,
+    constants: Constants,
+}
+
+impl Propagator {
+    pub fn new(elements: Elements) -> Result<Self, String> {
+        let constants = Constants::from_elements(&elements).map_err(|e| e.to_string())?;
+        Ok(Propagator {
+            elements,
+            constants,
+        })
+    }
+
+    pub fn epoch(&self) -> DateTime<Utc> {
+        epoch_of(&self.elements)
+    }
+
+    pub fn minutes_since_epoch(&self, time: &DateTime<Utc>) -> f64 {
+        (*time - self.epoch()).num_milliseconds() as f64 / 60000.0
+    }
+
+    pub fn state_at(&self, time: &DateTime<Utc>) -> Result<State, String> {
+        let prediction = self
+            .constants
+            .propagate(MinutesSinceEpoch(self.minutes_since_epoch(time)))
+            .map_err(|e| e.to_string())?;
+
+        Ok(State {
+            position: prediction.position,
+            velocity: prediction.velocity,
+        })
+    }
+}
+
+pub fn epoch_of(elements: &Elements) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&elements.datetime)
+}
+
+// Mean motion is given in revs per day, SGP4 works in radians per second
+pub fn mean_motion_rad_per_sec(elements: &Elements) -> f64 {
+    elements.mean_motion * 2.0 * std::f64::consts::PI / SECONDS_PER_DAY
+}
+
+pub fn semi_major_axis_km(elements: &Elements) -> f64 {
+    let n = mean_motion_rad_per_sec(elements);
+    (EARTH_MU / (n * n)).cbrt()
+}
+
+pub fn perigee_radius_km(elements: &Elements) -> f64 {
+    semi_major_axis_km(elements) * (1.0 - elements.eccentricity)
+}
+
+pub fn apogee_radius_km(elements: &Elements) -> f64 {
+    semi_major_axis_km(elements) * (1.0 + elements.eccentricity)
+}
+
+// Secular precession of the ascending node due to J2
+pub fn raan_drift_deg_per_day(elements: &Elements) -> f64 {
+    let a = semi_major_axis_km(elements);
+    let p = a * (1.0 - elements.eccentricity.powi(2));
+    let n = mean_motion_rad_per_sec(elements) * SECONDS_PER_DAY;
+
+    (-1.5 * EARTH_J2 * (EARTH_RADIUS_KM / p).powi(2) * n * elements.inclination.to_radians().cos())
+        .to_degrees()
+}
+
+pub fn circular_velocity_km_s(radius_km: f64) -> f64 {
+    (EARTH_MU / radius_km).sqrt()
+}
+
+pub fn period_minutes(elements: &Elements) -> f64 {
+    SECONDS_PER_DAY / 60.0 / elements.mean_motion
+}
+
+// Vis-viva: v^2 = mu * (2/r - 1/a)
+pub fn velocity_at_radius_km_s(elements: &Elements, radius_km: f64) -> f64 {
+    (EARTH_MU * (2.0 / radius_km - 1.0 / semi_major_axis_km(elements))).sqrt()
+}
+
+pub fn epoch_age_days(elements: &Elements, now: &DateTime<Utc>) -> f64 {
+    (*now - epoch_of(elements)).num_seconds() as f64 / SECONDS_PER_DAY
+}
+
+pub fn classify_regime(elements: &Elements) -> OrbitRegime {
+    let perigee_altitude = perigee_radius_km(elements) - EARTH_RADIUS_KM;
+    let apogee_altitude = apogee_radius_km(elements) - EARTH_RADIUS_KM;
+    let period_hours = period_minutes(elements) / 60.0;
+
+    if elements.eccentricity > 0.5
+        && (11.0..=13.0).contains(&period_hours)
+        && (60.0..=66.0).contains(&elements.inclination)
+    {
+        OrbitRegime::Molniya
+    } else if elements.eccentricity > 0.25 {
+        OrbitRegime::Heo
+    } else if (0.98..=1.02).contains(&elements.mean_motion) && elements.eccentricity < 0.01 {
+        OrbitRegime::Geo
+    } else if apogee_altitude < LEO_MAX_ALTITUDE_KM {
+        let drift_error = raan_drift_deg_per_day(elements) - SUN_SYNCHRONOUS_DRIFT_DEG_PER_DAY;
+        if drift_error.abs() < 0.1 {
+            OrbitRegime::Sso
+        } else {
+            OrbitRegime::Leo
+        }
+    } else if perigee_altitude >= LEO_MAX_ALTITUDE_KM && apogee_altitude < GEO_ALTITUDE_KM {
+        OrbitRegime::Meo
+    } else {
+        OrbitRegime::Other
+    }
+}
+
+pub fn derive_parameters(elements: &Elements, now: &DateTime<Utc>) -> DerivedParameters {
+    let perigee_radius = perigee_radius_km(elements);
+    let apogee_radius = apogee_radius_km(elements);
+
+    DerivedParameters {
+        semi_major_axis_km: semi_major_axis_km(elements),
+        perigee_altitude_km: perigee_radius - EARTH_RADIUS_KM,
+        apogee_altitude_km: apogee_radius - EARTH_RADIUS_KM,
+        period_minutes: period_minutes(elements),
+        perigee_velocity_km_s: velocity_at_radius_km_s(elements, perigee_radius),
+        apogee_velocity_km_s: velocity_at_radius_km_s(elements, apogee_radius),
+        epoch_age_days: epoch_age_days(elements, now),
+        regime: classify_regime(elements),
+    }
+}
+
+pub fn dot(a: &Vector3, b: &Vector3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: &Vector3, b: &Vector3) -> Vector3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn sub(a: &Vector3, b: &Vector3) -> Vector3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(a: &Vector3, factor: f64) -> Vector3 {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
+
+pub fn norm(a: &Vector3) -> f64 {
+    dot(a, a).sqrt()
+}
+
+pub fn unit(a: &Vector3) -> Vector3 {
+    scale(a, 1.0 / norm(a))
+}
+
+// Angle in degrees from the ascending node to the satellite along the orbit, 0 to 360
+pub fn argument_of_latitude(state: &State) -> f64 {
+    let momentum = cross(&state.position, &state.velocity);
+    let node = [-momentum[1], momentum[0], 0.0];
+    // An equatorial orbit has no node, measure from the x axis instead
+    if norm(&node) < 1e-9 * norm(&momentum) {
+        return state.position[1]
+            .atan2(state.position[0])
+            .to_degrees()
+            .rem_euclid(360.0);
+    }
+
+    let angle = (dot(&node, &state.position) / (norm(&node) * norm(&state.position)))
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees();
+    if state.position[2] < 0.0 {
+        360.0 - angle
+    } else {
+        angle
+    }
+}
+
+/**
+Finds the revolution number at a time, counted like the one in the TLE but going up as the
+satellite passes a chosen point of its orbit rather than the ascending node
+
+The mean motion only tells the count to a fraction of an orbit, so it is snapped to where
+the propagated satellite actually is. The number then changes exactly at that point, the same
+for any element set.
+
+# Arguments
+* `propagator` - The satellite
+* `time` - The time
+* `boundary` - Argument of latitude in degrees where the count goes up, 0 for the TLE's count
+# Returns
+* The revolution number, or the propagation error
+*/
+pub fn revolution_at(
+    propagator: &Propagator,
+    time: &DateTime<Utc>,
+    boundary: f64,
+) -> Result<u64, String> {
+    let elements = &propagator.elements;
+    let days = propagator.minutes_since_epoch(time) / 1440.0;
+    let estimate = elements.revolution_number as f64
+        + (elements.argument_of_perigee + elements.mean_anomaly - boundary) / 360.0
+        + elements.mean_motion * days
+        + elements.mean_motion_dot * days * days;
+    let fraction =
+        (argument_of_latitude(&propagator.state_at(time)?) - boundary).rem_euclid(360.0) / 360.0;
+
+    Ok((estimate - fraction).round().max(0.0) as u64)
+}
+
+// Projects `vector` onto the radial / in-track / cross-track frame of the reference state
+pub fn to_ric(reference: &State, vector: &Vector3) -> Vector3 {
+    let radial = unit(&reference.position);
+    let cross_track = unit(&cross(&reference.position, &reference.velocity));
+    let in_track = cross(&cross_track, &radial);
+
+    [
+        dot(vector, &radial),
+        dot(vector, &in_track),
+        dot(vector, &cross_track),
+    ]
+}
+
+pub fn julian_date(time: &DateTime<Utc>) -> f64 {
+    time.timestamp_millis() as f64 / 86_400_000.0 + 2_440_587.5
+}
+
+// Greenwich mean sidereal time in radians (IAU 1982, as used with TEME)
+pub fn gmst_rad(time: &DateTime<Utc>) -> f64 {
+    let t = (julian_date(time) - 2_451_545.0) / 36525.0;
+    let seconds = 67310.54841 + (876600.0 * 3600.0 + 8640184.812866) * t + 0.093104 * t * t
+        - 6.2e-6 * t * t * t;
+
+    (seconds % SECONDS_PER_DAY / 240.0)
+        .to_radians()
+        .rem_euclid(std::f64::consts::TAU)
+}
+
+// Rotates a TEME position into the Earth-fixed frame, polar motion is ignored
+pub fn teme_to_ecef(position: &Vector3, time: &DateTime<Utc>) -> Vector3 {
+    let (sin, cos) = gmst_rad(time).sin_cos();
+    [
+        cos * position[0] + sin * position[1],
+        -sin * position[0] + cos * position[1],
+        position[2],
+    ]
+}
+
+// Velocities also lose the Earth's rotation when moving to the Earth-fixed frame
+pub fn teme_state_to_ecef(state: &State, time: &DateTime<Utc>) -> State {
+    let position = teme_to_ecef(&state.position, time);
+    let velocity = teme_to_ecef(&state.velocity, time);
+
+    State {
+        velocity: [
+            velocity[0] + EARTH_ROTATION_RAD_S * position[1],
+            velocity[1] - EARTH_ROTATION_RAD_S * position[0],
+            velocity[2],
+        ],
+        position,
+    }
+}
+
+pub fn geodetic_to_ecef(geodetic: &Geodetic) -> Vector3 {
+    let e2 = EARTH_FLATTENING * (2.0 - EARTH_FLATTENING);
+    let latitude = geodetic.latitude.to_radians();
+    let longitude = geodetic.longitude.to_radians();
+    let radius = EARTH_RADIUS_KM / (1.0 - e2 * latitude.sin().powi(2)).sqrt();
+
+    [
+        (radius + geodetic.altitude_km) * latitude.cos() * longitude.cos(),
+        (radius + geodetic.altitude_km) * latitude.cos() * longitude.sin(),
+        (radius * (1.0 - e2) + geodetic.altitude_km) * latitude.sin(),
+    ]
+}
+
+/**
+Computes where a satellite appears in the sky of an observer
+
+# Arguments
+* `observer` - The observer's location on the ground
+* `state` - The satellite's TEME state
+* `time` - The time of the state
+# Returns
+* Azimuth (from north, clockwise), elevation, range and range rate
+*/
+pub fn look_angles(observer: &Geodetic, state: &State, time: &DateTime<Utc>) -> LookAngles {
+    let satellite = teme_state_to_ecef(state, time);
+    let range = sub(&satellite.position, &geodetic_to_ecef(observer));
+    let range_km = norm(&range);
+
+    let (sin_lat, cos_lat) = observer.latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = observer.longitude.to_radians().sin_cos();
+    // Topocentric south / east / zenith components
+    let south = sin_lat * cos_lon * range[0] + sin_lat * sin_lon * range[1] - cos_lat * range[2];
+    let east = -sin_lon * range[0] + cos_lon * range[1];
+    let zenith = cos_lat * cos_lon * range[0] + cos_lat * sin_lon * range[1] + sin_lat * range[2];
+
+    LookAngles {
+        azimuth: east.atan2(-south).to_degrees().rem_euclid(360.0),
+        elevation: (zenith / range_km).asin().to_degrees(),
+        range_km,
+        range_rate_km_s: dot(&range, &satellite.velocity) / range_km,
+    }
+}
+
+// Iterative conversion of an Earth-fixed position to WGS84 coordinates
+pub fn ecef_to_geodetic(position: &Vector3) -> Geodetic {
+    let e2 = EARTH_FLATTENING * (2.0 - EARTH_FLATTENING);
+    let p = (position[0] * position[0] + position[1] * position[1]).sqrt();
+    let longitude = position[1].atan2(position[0]);
+
+    let mut latitude = position[2].atan2(p * (1.0 - e2));
+    let mut radius = EARTH_RADIUS_KM;
+    for _ in 0..5 {
+        radius = EARTH_RADIUS_KM / (1.0 - e2 * latitude.sin().powi(2)).sqrt();
+        latitude = (position[2] + e2 * radius * latitude.sin()).atan2(p);
+    }
+
+    // Close to the poles the altitude is better taken from z
+    let altitude_km = if latitude.cos().abs() > 1e-6 {
+        p / latitude.cos() - radius
+    } else {
+        position[2].abs() - radius * (1.0 - e2)
+    };
+
+    Geodetic {
+        latitude: latitude.to_degrees(),
+        longitude: longitude.to_degrees(),
+        altitude_km,
+    }
+}
+
+pub fn teme_to_geodetic(position: &Vector3, time: &DateTime<Utc>) -> Geodetic {
+    ecef_to_geodetic(&teme_to_ecef(position, time))
+}
+
+// Low precision solar position (Astronomical Almanac, about 0.01°) in the equator of date, km
+pub fn sun_position(time: &DateTime<Utc>) -> Vector3 {
+    let n = julian_date(time) - 2_451_545.0;
+    let mean_longitude = 280.460 + 0.985_647_4 * n;
+    let mean_anomaly = (357.528 + 0.985_600_3 * n).to_radians();
+    let longitude =
+        (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin())
+            .to_radians();
+    let obliquity = (23.439 - 0.000_000_4 * n).to_radians();
+    let distance =
+        (1.000_14 - 0.016_71 * mean_anomaly.cos() - 0.000_14 * (2.0 * mean_anomaly).cos())
+            * ASTRONOMICAL_UNIT_KM;
+
+    [
+        distance * longitude.cos(),
+        distance * obliquity.cos() * longitude.sin(),
+        distance * obliquity.sin() * longitude.sin(),
+    ]
+}
+
+// Whether a TEME position is outside the Earth's shadow, treated as a cylinder
+pub fn is_sunlit(position: &Vector3, time: &DateTime<Utc>) -> bool {
+    let sun = unit(&sun_position(time));
+    let along = dot(position, &sun);
+    along > 0.0 || norm(&sub(position, &scale(&sun, along))) > EARTH_RADIUS_KM
+}
+
+// Low precision lunar position (Astronomical Almanac, about 0.3° and 0.2 Earth radii) in the
+// equator of date, km
+pub fn moon_position(time: &DateTime<Utc>) -> Vector3 {
+    let t = julian_centuries(time);
+    let sin = |base: f64, rate: f64| (base + rate * t).to_radians().sin();
+    let cos = |base: f64, rate: f64| (base + rate * t).to_radians().cos();
+
+    let longitude = (218.32 + 481_267.881 * t + 6.29 * sin(135.0, 477_198.87)
+        - 1.27 * sin(259.3, -413_335.36)
+        + 0.66 * sin(235.7, 890_534.22)
+        + 0.21 * sin(269.9, 954_397.74)
+        - 0.19 * sin(357.5, 35_999.05)
+        - 0.11 * sin(186.5, 966_404.03))
+    .to_radians();
+    let latitude = (5.13 * sin(93.3, 483_202.03) + 0.28 * sin(228.2, 960_400.87)
+        - 0.28 * sin(318.3, 6_003.18)
+        - 0.17 * sin(217.6, -407_332.20))
+    .to_radians();
+    let parallax = (0.9508
+        + 0.0518 * cos(135.0, 477_198.87)
+        + 0.0095 * cos(259.3, -413_335.38)
+        + 0.0078 * cos(235.7, 890_534.23)
+        + 0.0028 * cos(269.9, 954_397.70))
+    .to_radians();
+    let distance = EARTH_RADIUS_KM / parallax.sin();
+
+    // Ecliptic to equatorial coordinates
+    let obliquity = (23.439 - 0.013 * t).to_radians();
+    let ecliptic = [
+        latitude.cos() * longitude.cos(),
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+    ];
+    scale(&rotate_x(&ecliptic, -obliquity), distance)
+}
+
+// How much of the Sun a body hides from `position`, from the apparent radii of both and their
+// angular separation (Montenbruck & Gill 3.4.2)
+fn shadow_of(
+    position: &Vector3,
+    sun: &Vector3,
+    body: &Vector3,
+    body_radius_km: f64,
+) -> EclipseState {
+    let to_sun = sub(sun, position);
+    let to_body = sub(body, position);
+    let sun_radius = (SUN_RADIUS_KM / norm(&to_sun)).asin();
+    let body_radius = (body_radius_km / norm(&to_body)).clamp(-1.0, 1.0).asin();
+    let separation = (dot(&to_sun, &to_body) / (norm(&to_sun) * norm(&to_body)))
+        .clamp(-1.0, 1.0)
+        .acos();
+
+    if separation >= sun_radius + body_radius {
+        EclipseState::Sunlit
+    } else if separation <= body_radius - sun_radius {
+        EclipseState::Umbra
+    } else {
+        // Partly covered, including an annular eclipse where the body is too small to cover the Sun
+        EclipseState::Penumbra
+    }
+}
+
+// Whether a TEME position is in sunlight, penumbra or umbra, with conical shadows of the Earth
+// and the Moon
+pub fn eclipse_state(position: &Vector3, time: &DateTime<Utc>) -> EclipseState {
+    let sun = sun_position(time);
+    let earth = shadow_of(position, &sun, &[0.0; 3], EARTH_RADIUS_KM);
+    let moon = shadow_of(position, &sun, &moon_position(time), MOON_RADIUS_KM);
+    earth.max(moon)
+}
+
+// Frame rotations about the x / y / z axes
+fn rotate_x(v: &Vector3, angle: f64) -> Vector3 {
+    let (sin, cos) = angle.sin_cos();
+    [v[0], cos * v[1] + sin * v[2], -sin * v[1] + cos * v[2]]
+}
+
+fn rotate_y(v: &Vector3, angle: f64) -> Vector3 {
+    let (sin, cos) = angle.sin_cos();
+    [cos * v[0] - sin * v[2], v[1], sin * v[0] + cos * v[2]]
+}
+
+fn rotate_z(v: &Vector3, angle: f64) -> Vector3 {
+    let (sin, cos) = angle.sin_cos();
+    [cos * v[0] + sin * v[1], -sin * v[0] + cos * v[1], v[2]]
+}
+
+// Julian centuries since J2000, UTC stands in for TT which is well within TLE accuracy
+fn julian_centuries(time: &DateTime<Utc>) -> f64 {
+    (julian_date(time) - 2_451_545.0) / 36525.0
+}
+
+// Nutation in longitude and obliquity plus the mean obliquity, all in radians
+fn nutation(time: &DateTime<Utc>) -> (f64, f64, f64) {
+    let t = julian_centuries(time);
+    let degrees = |base: f64, arcsec_rate: f64, arcsec_t2: f64| {
+        (base + (arcsec_rate * t + arcsec_t2 * t * t) / 3600.0).to_radians()
+    };
+
+    // Delaunay arguments of the Moon and Sun
+    let arguments = [
+        degrees(134.96340251, 1717915923.2178, 31.8792),
+        degrees(357.52910918, 129596581.0481, -0.5532),
+        degrees(93.27209062, 1739527262.8478, -12.7512),
+        degrees(297.85019547, 1602961601.2090, -6.3706),
+        degrees(125.04455501, -6962890.2665, 7.4722),
+    ];
+
+    let (mut longitude, mut obliquity) = (0.0, 0.0);
+    for (multipliers, [a, a_t, b, b_t]) in NUTATION_TERMS {
+        let angle: f64 = multipliers
+            .iter()
+            .zip(arguments)
+            .map(|(multiplier, argument)| multiplier * argument)
+            .sum();
+        longitude += (a + a_t * t) * 1e-4 * angle.sin();
+        obliquity += (b + b_t * t) * 1e-4 * angle.cos();
+    }
+
+    let mean_obliquity = 84381.448 - 46.8150 * t - 0.00059 * t * t + 0.001813 * t * t * t;
+    (
+        longitude * ARCSEC_TO_RAD,
+        obliquity * ARCSEC_TO_RAD,
+        mean_obliquity * ARCSEC_TO_RAD,
+    )
+}
+
+// TEME -> true of date -> mean of date -> J2000 (IAU 1976 precession, IAU 1980 nutation)
+fn teme_vector_to_eme2000(v: &Vector3, time: &DateTime<Utc>) -> Vector3 {
+    let t = julian_centuries(time);
+    let (delta_psi, delta_epsilon, mean_obliquity) = nutation(time);
+    let equation_of_equinoxes = delta_psi * mean_obliquity.cos();
+
+    let true_of_date = rotate_z(v, -equation_of_equinoxes);
+    let mean_of_date = rotate_x(
+        &rotate_z(
+            &rotate_x(&true_of_date, mean_obliquity + delta_epsilon),
+            delta_psi,
+        ),
+        -mean_obliquity,
+    );
+
+    let zeta = (2306.2181 * t + 0.30188 * t * t + 0.017998 * t * t * t) * ARCSEC_TO_RAD;
+    let theta = (2004.3109 * t - 0.42665 * t * t - 0.041833 * t * t * t) * ARCSEC_TO_RAD;
+    let z = (2306.2181 * t + 1.09468 * t * t + 0.018203 * t * t * t) * ARCSEC_TO_RAD;
+    rotate_z(&rotate_y(&rotate_z(&mean_of_date, z), -theta), zeta)
+}
+
+// The frame rotates slowly enough that the velocity can be rotated like the position
+pub fn teme_state_to_eme2000(state: &State, time: &DateTime<Utc>) -> State {
+    State {
+        position: teme_vector_to_eme2000(&state.position, time),
+        velocity: teme_vector_to_eme2000(&state.velocity, time),
+    }
+}
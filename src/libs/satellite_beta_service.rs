@@ -0,0 +1,138 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use super::orbit::{self, EARTH_RADIUS_KM};
+
+// The beta angle and the eclipse it causes at one moment
+#[derive(Debug, Clone)]
+pub struct BetaSample {
+    pub time: DateTime<Utc>,
+    pub beta_deg: f64,
+    pub raan_deg: f64,
+    pub eclipse_fraction: f64, // Share of each orbit spent in the Earth's shadow
+    pub eclipse_minutes: f64,
+}
+
+// A stretch of samples with eclipses on every orbit, between full-sun periods
+#[derive(Debug, Clone)]
+pub struct EclipseSeason {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_eclipse_minutes: f64,
+}
+
+// The ascending node at `time`, moved on from the epoch by the J2 precession
+pub fn raan_at(elements: &Elements, time: &DateTime<Utc>) -> f64 {
+    (elements.right_ascension
+        + orbit::raan_drift_deg_per_day(elements) * orbit::epoch_age_days(elements, time))
+    .rem_euclid(360.0)
+}
+
+// The angle between the orbit plane and the direction of the Sun, positive when the Sun is
+// on the side of the orbit normal
+pub fn beta_angle(elements: &Elements, time: &DateTime<Utc>) -> f64 {
+    let inclination = elements.inclination.to_radians();
+    let raan = raan_at(elements, time).to_radians();
+    let normal = [
+        inclination.sin() * raan.sin(),
+        -inclination.sin() * raan.cos(),
+        inclination.cos(),
+    ];
+
+    orbit::dot(&normal, &orbit::unit(&orbit::sun_position(time)))
+        .clamp(-1.0, 1.0)
+        .asin()
+        .to_degrees()
+}
+
+/**
+Computes the share of an orbit spent in the Earth's shadow for a given beta angle
+
+Treats the orbit as circular at the semi-major axis and the shadow as a cylinder, which is
+close for LEO and GEO but only a rough guide for eccentric orbits.
+
+# Arguments
+* `semi_major_axis_km` - The orbit radius
+* `beta_deg` - The beta angle
+# Returns
+* The eclipse fraction from 0 (full sun) to below 0.5
+*/
+pub fn eclipse_fraction(semi_major_axis_km: f64, beta_deg: f64) -> f64 {
+    let critical_beta = (EARTH_RADIUS_KM / semi_major_axis_km).asin();
+    let beta = beta_deg.to_radians().abs();
+    if beta >= critical_beta {
+        return 0.0;
+    }
+
+    let ratio = (semi_major_axis_km.powi(2) - EARTH_RADIUS_KM.powi(2)).sqrt()
+        / (semi_major_axis_km * beta.cos());
+    ratio.clamp(-1.0, 1.0).acos() / std::f64::consts::PI
+}
+
+/**
+Samples the beta angle and the eclipse per orbit over a date range
+
+# Arguments
+* `elements` - The element set, its node is precessed to every sample
+* `start` - The first sample
+* `end` - The end of the range
+* `step` - The time between samples
+# Returns
+* The samples in chronological order, none for a step that isn't positive
+*/
+pub fn beta_timeline(
+    elements: &Elements,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    step: Duration,
+) -> Vec<BetaSample> {
+    let semi_major_axis = orbit::semi_major_axis_km(elements);
+    let period = orbit::period_minutes(elements);
+    let mut samples = Vec::new();
+    if step <= Duration::zero() {
+        return samples;
+    }
+
+    let mut time = *start;
+    while time <= *end {
+        let beta_deg = beta_angle(elements, &time);
+        let eclipse_fraction = eclipse_fraction(semi_major_axis, beta_deg);
+        samples.push(BetaSample {
+            time,
+            beta_deg,
+            raan_deg: raan_at(elements, &time),
+            eclipse_fraction,
+            eclipse_minutes: eclipse_fraction * period,
+        });
+        time += step;
+    }
+
+    samples
+}
+
+// Groups consecutive samples with eclipses into seasons
+pub fn eclipse_seasons(samples: &[BetaSample]) -> Vec<EclipseSeason> {
+    let mut seasons: Vec<EclipseSeason> = Vec::new();
+    let mut in_season = false;
+
+    for sample in samples {
+        if sample.eclipse_fraction <= 0.0 {
+            in_season = false;
+            continue;
+        }
+        match seasons.last_mut() {
+            Some(season) if in_season => {
+                season.end = sample.time;
+                season.max_eclipse_minutes = season.max_eclipse_minutes.max(sample.eclipse_minutes);
+            }
+            _ => seasons.push(EclipseSeason {
+                start: sample.time,
+                end: sample.time,
+                max_eclipse_minutes: sample.eclipse_minutes,
+            }),
+        }
+        in_season = true;
+    }
+
+    seasons
+}
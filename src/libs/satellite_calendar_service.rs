@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+
+use super::satellite_pass_service::StationPass;
+
+const PRODUCT_ID: &str = "-//cosmos_cli//Pass Predictions//EN";
+const UID_DOMAIN: &str = "cosmos_cli";
+const MAX_LINE_OCTETS: usize = 75;
+
+fn format_datetime(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// TEXT values escape backslashes, separators and line breaks (RFC 5545 3.3.11)
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Content lines longer than 75 octets continue on lines starting with a space, never
+// splitting a UTF-8 character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/**
+Builds the UID of a pass event
+
+Predicted times move by seconds with every TLE update, so the UID uses the pass's revolution
+instead. It is counted from the point of the orbit farthest from the station's hemisphere,
+half an orbit away from where the passes culminate, so a shifted TCA keeps its number and a
+calendar updates the event on re-import rather than adding it twice.
+
+# Arguments
+* `entry` - The pass
+# Returns
+* A UID such as `25544-r45123-home@cosmos_cli`
+*/
+pub fn pass_uid(entry: &StationPass) -> String {
+    let station: String = entry
+        .station
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    format!(
+        "{}-r{}-{}@{}",
+        entry.satellite.id, entry.pass.revolution, station, UID_DOMAIN
+    )
+}
+
+fn event_lines(entry: &StationPass, stamp: &DateTime<Utc>) -> Vec<String> {
+    let pass = &entry.pass;
+    let location = &entry.station.location;
+    let description = format!(
+        "AOS {} UTC at azimuth {:.0}°\nTCA {} UTC at azimuth {:.0}°, elevation {:.1}°\n\
+         LOS {} UTC at azimuth {:.0}°\nNORAD ID {}",
+        pass.aos.format("%H:%M:%S"),
+        pass.aos_azimuth,
+        pass.tca.format("%H:%M:%S"),
+        pass.tca_azimuth,
+        pass.max_elevation,
+        pass.los.format("%H:%M:%S"),
+        pass.los_azimuth,
+        entry.satellite.id
+    );
+
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", pass_uid(entry)),
+        format!("DTSTAMP:{}", format_datetime(stamp)),
+        format!("LAST-MODIFIED:{}", format_datetime(stamp)),
+        // Minutes since 1970 grow with every export, so clients replace the older copy of an event
+        format!("SEQUENCE:{}", stamp.timestamp() / 60),
+        format!("DTSTART:{}", format_datetime(&pass.aos)),
+        format!("DTEND:{}", format_datetime(&pass.los)),
+        format!(
+            "SUMMARY:{}",
+            escape_text(&format!(
+                "{} over {} (max {:.0}°)",
+                entry.satellite.name, entry.station.name, pass.max_elevation
+            ))
+        ),
+        format!("DESCRIPTION:{}", escape_text(&description)),
+        format!("LOCATION:{}", escape_text(&entry.station.name)),
+        format!("GEO:{:.6};{:.6}", location.latitude, location.longitude),
+        "CATEGORIES:Satellite pass".to_string(),
+        // Passes shouldn't block the time in free/busy views
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+/**
+Writes predicted passes as an iCalendar (RFC 5545) file
+
+# Arguments
+* `passes` - The passes, one event each
+* `name` - The calendar name shown by calendar apps
+* `stamp` - When the file was created, written as DTSTAMP and LAST-MODIFIED and turned into the
+  SEQUENCE of every event
+# Returns
+* The calendar with CRLF line endings
+*/
+pub fn to_icalendar(passes: &[StationPass], name: &str, stamp: &DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+    for entry in passes {
+        lines.extend(event_lines(entry, stamp));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
@@ -0,0 +1,173 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sgp4::Elements;
+
+use super::satellite_history_service::data_dir;
+use super::satellite_search_service::{get_satellites, Satellite};
+
+const PAGE_SIZE: i32 = 100;
+const DEFAULT_MAX_PAGES: i32 = 20;
+const DEFAULT_CACHE_MAX_AGE_HOURS: i64 = 24;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedCatalog {
+    #[serde(rename = "fetchedAt")]
+    fetched_at: DateTime<Utc>,
+    satellites: Vec<Satellite>,
+}
+
+static CATALOG: OnceLock<Vec<Satellite>> = OnceLock::new();
+
+// A catalog satellite together with its parsed element set
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub satellite: Satellite,
+    pub elements: Elements,
+}
+
+// Parses a TLE / 3LE text file (as distributed by CelesTrak or Space-Track) into satellites
+pub fn parse_tle_file(contents: &str) -> Vec<Satellite> {
+    let lines: Vec<&str> = contents
+        .lines()
+        .map(|line| line.trim_end())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let mut satellites = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        // A name line is optional; line 1 and line 2 always start with their line number
+        let (name, offset) = if lines[i].starts_with("1 ") {
+            (None, 0)
+        } else {
+            (Some(lines[i].trim_start_matches("0 ").trim()), 1)
+        };
+
+        let (Some(line_one), Some(line_two)) = (lines.get(i + offset), lines.get(i + offset + 1))
+        else {
+            break;
+        };
+
+        if !line_one.starts_with("1 ") || !line_two.starts_with("2 ") {
+            // Out of sync, skip ahead one line and try again
+            i += 1;
+            continue;
+        }
+
+        let id = line_one
+            .get(2..7)
+            .and_then(|id| id.trim().parse::<i32>().ok())
+            .unwrap_or(0);
+
+        satellites.push(Satellite {
+            id,
+            name: name.map(str::to_string).unwrap_or_else(|| id.to_string()),
+            line_one: line_one.to_string(),
+            line_two: line_two.to_string(),
+        });
+
+        i += offset + 2;
+    }
+
+    satellites
+}
+
+fn load_catalog_file(path: &str) -> Vec<Satellite> {
+    match fs::read_to_string(path) {
+        Ok(contents) => parse_tle_file(&contents),
+        Err(err) => {
+            eprintln!("Error reading catalog file {}: {}", path, err);
+            vec![]
+        }
+    }
+}
+
+fn fetch_catalog() -> Vec<Satellite> {
+    let max_pages = env::var("COSMOS_CATALOG_MAX_PAGES")
+        .ok()
+        .and_then(|pages| pages.parse::<i32>().ok())
+        .unwrap_or(DEFAULT_MAX_PAGES);
+
+    let mut satellites = Vec::new();
+    for page in 1..=max_pages {
+        let (mut fetched, _) = get_satellites(None, Some(page), Some(PAGE_SIZE));
+        if fetched.is_empty() {
+            break;
+        }
+        let is_last_page = fetched.len() < PAGE_SIZE as usize;
+        satellites.append(&mut fetched);
+        if is_last_page {
+            break;
+        }
+    }
+
+    satellites
+}
+
+fn catalog_cache_path() -> PathBuf {
+    data_dir().join("catalog.json")
+}
+
+// The last fetched catalog, as long as it is younger than `COSMOS_CATALOG_MAX_AGE_HOURS`
+fn load_cached_catalog() -> Option<Vec<Satellite>> {
+    let max_age = env::var("COSMOS_CATALOG_MAX_AGE_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_CACHE_MAX_AGE_HOURS);
+
+    let contents = fs::read_to_string(catalog_cache_path()).ok()?;
+    let cached: CachedCatalog = serde_json::from_str(&contents).ok()?;
+
+    (Utc::now() - cached.fetched_at < Duration::hours(max_age)).then_some(cached.satellites)
+}
+
+fn save_cached_catalog(satellites: &[Satellite]) -> Result<(), String> {
+    let path = catalog_cache_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let cached = CachedCatalog {
+        fetched_at: Utc::now(),
+        satellites: satellites.to_vec(),
+    };
+    let body = serde_json::to_string(&cached).map_err(|e| e.to_string())?;
+    fs::write(path, body).map_err(|e| e.to_string())
+}
+
+fn fetch_or_load_cached_catalog() -> Vec<Satellite> {
+    if let Some(satellites) = load_cached_catalog() {
+        return satellites;
+    }
+
+    let satellites = fetch_catalog();
+    if !satellites.is_empty() {
+        if let Err(err) = save_cached_catalog(&satellites) {
+            eprintln!("Error caching catalog: {}", err);
+        }
+    }
+    satellites
+}
+
+// The catalog if something already loaded it, without triggering a fetch
+pub fn loaded_catalog() -> Option<&'static [Satellite]> {
+    CATALOG.get().map(Vec::as_slice)
+}
+
+/**
+Returns the catalog used for catalog-wide operations (conjunctions, filtering, ...)
+
+The catalog is read from the TLE file at `COSMOS_CATALOG_FILE` when set, otherwise it is
+fetched page by page from the TLE API (up to `COSMOS_CATALOG_MAX_PAGES` pages) and cached
+on disk. It is loaded once and kept for the rest of the session.
+*/
+pub fn get_catalog() -> &'static [Satellite] {
+    CATALOG.get_or_init(|| match env::var("COSMOS_CATALOG_FILE") {
+        Ok(path) => load_catalog_file(&path),
+        Err(_) => fetch_or_load_cached_catalog(),
+    })
+}
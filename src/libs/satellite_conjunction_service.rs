@@ -0,0 +1,181 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use super::orbit::{self, Propagator, State, Vector3};
+
+// Mean elements don't bound the osculating orbit exactly, so pad the apsis overlap test
+const APSIS_FILTER_MARGIN_KM: f64 = 25.0;
+const REFINE_TOLERANCE_SECONDS: f64 = 0.001;
+
+pub struct ScreeningOptions {
+    pub start: DateTime<Utc>,
+    pub duration: Duration,
+    pub threshold_km: f64,
+    pub coarse_step_seconds: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Conjunction {
+    pub norad_id: u64,
+    pub name: String,
+    pub tca: DateTime<Utc>,
+    pub miss_distance_km: f64,
+    pub ric_separation_km: Vector3,
+    pub relative_speed_km_s: f64,
+}
+
+// Cheap pre-filter: two orbits can only meet if their altitude shells overlap
+fn apsides_overlap(primary: &Elements, secondary: &Elements, threshold_km: f64) -> bool {
+    let pad = threshold_km + APSIS_FILTER_MARGIN_KM;
+    orbit::perigee_radius_km(primary) - pad <= orbit::apogee_radius_km(secondary)
+        && orbit::perigee_radius_km(secondary) - pad <= orbit::apogee_radius_km(primary)
+}
+
+// Golden-section search for the minimum of `f` on [a, b]
+fn minimize<F: FnMut(f64) -> f64>(mut f: F, mut a: f64, mut b: f64, tolerance: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut c = b - ratio * (b - a);
+    let mut d = a + ratio * (b - a);
+    let mut fc = f(c);
+    let mut fd = f(d);
+
+    while (b - a).abs() > tolerance {
+        if fc < fd {
+            b = d;
+            d = c;
+            fd = fc;
+            c = b - ratio * (b - a);
+            fc = f(c);
+        } else {
+            a = c;
+            c = d;
+            fc = fd;
+            d = a + ratio * (b - a);
+            fd = f(d);
+        }
+    }
+
+    (a + b) / 2.0
+}
+
+fn offset_time(start: &DateTime<Utc>, seconds: f64) -> DateTime<Utc> {
+    *start + Duration::milliseconds((seconds * 1000.0).round() as i64)
+}
+
+fn separation(primary: &State, secondary: &State) -> f64 {
+    orbit::norm(&orbit::sub(&secondary.position, &primary.position))
+}
+
+fn screen_candidate(
+    primary: &Propagator,
+    primary_states: &[State],
+    candidate: &Propagator,
+    options: &ScreeningOptions,
+) -> Result<Vec<Conjunction>, String> {
+    let step = options.coarse_step_seconds as f64;
+    let mut distances = Vec::with_capacity(primary_states.len());
+    let mut speeds = Vec::with_capacity(primary_states.len());
+
+    for (k, primary_state) in primary_states.iter().enumerate() {
+        let state = candidate.state_at(&offset_time(&options.start, k as f64 * step))?;
+        distances.push(separation(primary_state, &state));
+        speeds.push(orbit::norm(&orbit::sub(
+            &state.velocity,
+            &primary_state.velocity,
+        )));
+    }
+
+    let end = (primary_states.len() - 1) as f64 * step;
+    let mut conjunctions = Vec::new();
+
+    for k in 0..distances.len() {
+        let before = if k > 0 { distances[k - 1] } else { f64::MAX };
+        let after = distances.get(k + 1).copied().unwrap_or(f64::MAX);
+        if distances[k] > before || distances[k] > after {
+            continue;
+        }
+        // The true minimum may lie up to one step of relative motion below the sampled one
+        if distances[k] > options.threshold_km + speeds[k] * step {
+            continue;
+        }
+
+        let low = ((k as f64 - 1.0) * step).max(0.0);
+        let high = ((k as f64 + 1.0) * step).min(end);
+        let distance_at = |seconds: f64| {
+            let time = offset_time(&options.start, seconds);
+            match (primary.state_at(&time), candidate.state_at(&time)) {
+                (Ok(p), Ok(s)) => separation(&p, &s),
+                _ => f64::MAX,
+            }
+        };
+        let tca_seconds = minimize(distance_at, low, high, REFINE_TOLERANCE_SECONDS);
+
+        let tca = offset_time(&options.start, tca_seconds);
+        let primary_state = primary.state_at(&tca)?;
+        let state = candidate.state_at(&tca)?;
+        let relative_position = orbit::sub(&state.position, &primary_state.position);
+        let relative_velocity = orbit::sub(&state.velocity, &primary_state.velocity);
+        let miss_distance_km = orbit::norm(&relative_position);
+
+        if miss_distance_km > options.threshold_km {
+            continue;
+        }
+
+        conjunctions.push(Conjunction {
+            norad_id: candidate.elements.norad_id,
+            name: candidate
+                .elements
+                .object_name
+                .clone()
+                .unwrap_or_else(|| candidate.elements.norad_id.to_string()),
+            tca,
+            miss_distance_km,
+            ric_separation_km: orbit::to_ric(&primary_state, &relative_position),
+            relative_speed_km_s: orbit::norm(&relative_velocity),
+        });
+    }
+
+    Ok(conjunctions)
+}
+
+/**
+Finds close approaches between a primary object and every candidate over a time window
+
+# Arguments
+* `primary` - The elements of the object being screened
+* `candidates` - The elements of every other object in the catalog
+* `options` - The screening window, miss distance threshold and coarse step
+# Returns
+* All approaches closer than the threshold, ordered by miss distance
+*/
+pub fn screen_conjunctions(
+    primary: &Elements,
+    candidates: &[Elements],
+    options: &ScreeningOptions,
+) -> Result<Vec<Conjunction>, String> {
+    let primary = Propagator::new(primary.clone())?;
+    let steps = (options.duration.num_seconds() / options.coarse_step_seconds.max(1)).max(1);
+    let primary_states = (0..=steps)
+        .map(|k| {
+            primary.state_at(&offset_time(
+                &options.start,
+                (k * options.coarse_step_seconds) as f64,
+            ))
+        })
+        .collect::<Result<Vec<State>, String>>()?;
+
+    let mut conjunctions: Vec<Conjunction> = candidates
+        .iter()
+        .filter(|candidate| candidate.norad_id != primary.elements.norad_id)
+        .filter(|candidate| apsides_overlap(&primary.elements, candidate, options.threshold_km))
+        .filter_map(|candidate| Propagator::new(candidate.clone()).ok())
+        // Objects that fail to propagate (e.g. decayed) are simply not screened
+        .filter_map(|candidate| {
+            screen_candidate(&primary, &primary_states, &candidate, options).ok()
+        })
+        .flatten()
+        .collect();
+
+    conjunctions.sort_by(|a, b| a.miss_distance_km.total_cmp(&b.miss_distance_km));
+    Ok(conjunctions)
+}
@@ -0,0 +1,224 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use super::orbit::{self, EARTH_MU, EARTH_RADIUS_KM};
+
+const REENTRY_ALTITUDE_KM: f64 = 120.0;
+// Above this drag is negligible and any lifetime estimate is meaningless
+const MAX_DECAY_ALTITUDE_KM: f64 = 2000.0;
+const HORIZON_DAYS: f64 = 25.0 * 365.25;
+const STEP_DAYS: f64 = 0.1;
+// Atmospheric density easily varies by this factor with solar activity
+const RATE_UNCERTAINTY: f64 = 1.5;
+// Reference density BSTAR is scaled by, in kg/m^2/earth radius
+const BSTAR_REFERENCE_DENSITY: f64 = 0.15696615;
+const TREND_POINTS: usize = 12;
+
+// Exponential atmosphere (Vallado, table 8-4): base altitude (km), density (kg/m^3), scale height (km)
+const ATMOSPHERE: [(f64, f64, f64); 19] = [
+    (100.0, 5.297e-7, 5.877),
+    (110.0, 9.661e-8, 7.263),
+    (120.0, 2.438e-8, 9.473),
+    (130.0, 8.484e-9, 12.636),
+    (140.0, 3.845e-9, 16.149),
+    (150.0, 2.070e-9, 22.523),
+    (180.0, 5.464e-10, 29.740),
+    (200.0, 2.789e-10, 37.105),
+    (250.0, 7.248e-11, 45.546),
+    (300.0, 2.418e-11, 53.628),
+    (350.0, 9.518e-12, 53.298),
+    (400.0, 3.725e-12, 58.515),
+    (450.0, 1.585e-12, 60.828),
+    (500.0, 6.967e-13, 63.822),
+    (600.0, 1.454e-13, 71.835),
+    (700.0, 3.614e-14, 88.667),
+    (800.0, 1.170e-14, 124.64),
+    (900.0, 5.245e-15, 181.05),
+    (1000.0, 3.019e-15, 268.00),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub enum DecaySource {
+    History(usize),
+    MeanMotionDerivative,
+    Bstar,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrendPoint {
+    pub time: DateTime<Utc>,
+    pub altitude_km: f64,
+    pub low_km: f64,
+    pub high_km: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReentryWindow {
+    pub earliest: DateTime<Utc>,
+    pub nominal: DateTime<Utc>,
+    pub latest: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecayEstimate {
+    pub altitude_km: f64,
+    pub decay_rate_km_per_day: f64,
+    pub source: DecaySource,
+    pub trend: Vec<TrendPoint>,
+    pub reentry: Option<ReentryWindow>,
+}
+
+fn atmospheric_density(altitude_km: f64) -> f64 {
+    let (base, density, scale_height) = ATMOSPHERE
+        .iter()
+        .rev()
+        .find(|(base, _, _)| altitude_km >= *base)
+        .copied()
+        .unwrap_or(ATMOSPHERE[0]);
+
+    density * (-(altitude_km - base) / scale_height).exp()
+}
+
+// Semi-major axis decay of a near-circular orbit: da/dt = -rho * B * sqrt(mu * a)
+fn drag_decay_rate(altitude_km: f64, ballistic_coefficient: f64) -> f64 {
+    let a_m = (EARTH_RADIUS_KM + altitude_km) * 1000.0;
+    let rate_m_per_s =
+        atmospheric_density(altitude_km) * ballistic_coefficient * (EARTH_MU * 1e9 * a_m).sqrt();
+
+    rate_m_per_s * orbit::SECONDS_PER_DAY / 1000.0
+}
+
+// The TLE carries half the first derivative of mean motion, in revs per day squared
+fn rate_from_mean_motion_derivative(elements: &Elements) -> Option<f64> {
+    let n_dot = 2.0 * elements.mean_motion_dot;
+    if n_dot <= 0.0 {
+        return None;
+    }
+    Some(2.0 / 3.0 * orbit::semi_major_axis_km(elements) * n_dot / elements.mean_motion)
+}
+
+// Least-squares slope of the semi-major axis over a series of element sets, in km/day
+fn rate_from_history(history: &[Elements]) -> Option<f64> {
+    if history.len() < 3 {
+        return None;
+    }
+
+    let first_epoch = orbit::epoch_of(&history[0]);
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .map(|elements| {
+            let days = (orbit::epoch_of(elements) - first_epoch).num_seconds() as f64
+                / orbit::SECONDS_PER_DAY;
+            (days, orbit::semi_major_axis_km(elements))
+        })
+        .collect();
+
+    let count = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / count;
+    let mean_a = points.iter().map(|(_, a)| a).sum::<f64>() / count;
+    let covariance: f64 = points
+        .iter()
+        .map(|(t, a)| (t - mean_t) * (a - mean_a))
+        .sum();
+    let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+
+    // Need at least a day of spread for the slope to mean anything
+    if variance < 1.0 {
+        return None;
+    }
+    let slope = covariance / variance;
+
+    (slope < 0.0).then_some(-slope)
+}
+
+// Integrates the altitude forward until re-entry or the horizon; returns altitude per step
+fn simulate(start_altitude_km: f64, ballistic_coefficient: f64) -> (Vec<f64>, Option<usize>) {
+    let mut altitudes = vec![start_altitude_km];
+    let mut altitude = start_altitude_km;
+    let steps = (HORIZON_DAYS / STEP_DAYS) as usize;
+
+    for step in 1..=steps {
+        altitude -= drag_decay_rate(altitude, ballistic_coefficient) * STEP_DAYS;
+        if altitude <= REENTRY_ALTITUDE_KM {
+            altitudes.push(REENTRY_ALTITUDE_KM);
+            return (altitudes, Some(step));
+        }
+        altitudes.push(altitude);
+    }
+
+    (altitudes, None)
+}
+
+fn altitude_at(altitudes: &[f64], step: usize) -> f64 {
+    altitudes.get(step).copied().unwrap_or(REENTRY_ALTITUDE_KM)
+}
+
+/**
+Estimates the orbital decay and re-entry window of a low Earth orbit object
+
+# Arguments
+* `elements` - The latest element set of the object
+* `history` - Older element sets of the same object, ordered by epoch (may be empty)
+# Returns
+* The decay estimate, or None when the object is too high or carries no drag information
+*/
+pub fn estimate_decay(elements: &Elements, history: &[Elements]) -> Option<DecayEstimate> {
+    // Circular approximation: the mean altitude stands in for the whole orbit
+    let altitude_km = orbit::semi_major_axis_km(elements) - EARTH_RADIUS_KM;
+    if orbit::perigee_radius_km(elements) - EARTH_RADIUS_KM > MAX_DECAY_ALTITUDE_KM {
+        return None;
+    }
+
+    let observed = rate_from_history(history)
+        .map(|rate| (rate, DecaySource::History(history.len())))
+        .or_else(|| {
+            rate_from_mean_motion_derivative(elements)
+                .map(|rate| (rate, DecaySource::MeanMotionDerivative))
+        });
+
+    // Either calibrate the ballistic coefficient to the observed decay, or derive it from BSTAR
+    let (ballistic_coefficient, source) = match observed {
+        Some((rate, source)) => (rate / drag_decay_rate(altitude_km, 1.0), source),
+        None if elements.drag_term > 0.0 => (
+            2.0 * elements.drag_term / BSTAR_REFERENCE_DENSITY,
+            DecaySource::Bstar,
+        ),
+        None => return None,
+    };
+
+    let (nominal, nominal_step) = simulate(altitude_km, ballistic_coefficient);
+    let (fast, fast_step) = simulate(altitude_km, ballistic_coefficient * RATE_UNCERTAINTY);
+    let (slow, slow_step) = simulate(altitude_km, ballistic_coefficient / RATE_UNCERTAINTY);
+
+    let epoch = orbit::epoch_of(elements);
+    let time_at = |step: usize| {
+        epoch + Duration::seconds((step as f64 * STEP_DAYS * orbit::SECONDS_PER_DAY) as i64)
+    };
+
+    let reentry = nominal_step.map(|step| ReentryWindow {
+        earliest: time_at(fast_step.unwrap_or(step)),
+        nominal: time_at(step),
+        latest: time_at(slow_step.unwrap_or(slow.len() - 1)),
+    });
+
+    let span = slow_step.unwrap_or(slow.len() - 1);
+    let trend = (0..TREND_POINTS)
+        .map(|i| {
+            let step = span * i / (TREND_POINTS - 1);
+            TrendPoint {
+                time: time_at(step),
+                altitude_km: altitude_at(&nominal, step),
+                low_km: altitude_at(&fast, step),
+                high_km: altitude_at(&slow, step),
+            }
+        })
+        .collect();
+
+    Some(DecayEstimate {
+        altitude_km,
+        decay_rate_km_per_day: drag_decay_rate(altitude_km, ballistic_coefficient),
+        source,
+        trend,
+        reentry,
+    })
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::orbit::{self, EclipseState, Propagator};
+
+// Short enough for LEO, where the penumbra is crossed in under ten seconds between samples
+const SCAN_STEP_SECONDS: i64 = 30;
+const REFINE_TOLERANCE_MS: i64 = 100;
+
+// A stretch of time in which the sunlight on the satellite doesn't change
+#[derive(Debug, Clone)]
+pub struct EclipsePeriod {
+    pub state: EclipseState,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl EclipsePeriod {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+pub fn eclipse_state_at(
+    propagator: &Propagator,
+    time: &DateTime<Utc>,
+) -> Result<EclipseState, String> {
+    let state = propagator.state_at(time)?;
+    Ok(orbit::eclipse_state(&state.position, time))
+}
+
+// The first moment after `before` at which the state differs from the one at `before`
+fn refine_change(
+    propagator: &Propagator,
+    mut before: DateTime<Utc>,
+    mut after: DateTime<Utc>,
+) -> Result<(DateTime<Utc>, EclipseState), String> {
+    let initial = eclipse_state_at(propagator, &before)?;
+    let mut changed = eclipse_state_at(propagator, &after)?;
+
+    while (after - before).num_milliseconds() > REFINE_TOLERANCE_MS {
+        let middle = before + (after - before) / 2;
+        let state = eclipse_state_at(propagator, &middle)?;
+        if state == initial {
+            before = middle;
+        } else {
+            after = middle;
+            changed = state;
+        }
+    }
+
+    Ok((after, changed))
+}
+
+/**
+Splits a time range into sunlit, penumbra and umbra periods
+
+# Arguments
+* `propagator` - The satellite
+* `start` - The start of the range, the first period starts here
+* `end` - The end of the range, the last period is cut off here
+# Returns
+* The periods in chronological order, or the propagation error
+*/
+pub fn eclipse_timeline(
+    propagator: &Propagator,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+) -> Result<Vec<EclipsePeriod>, String> {
+    let step = Duration::seconds(SCAN_STEP_SECONDS);
+    let mut periods = Vec::new();
+    let mut current = EclipsePeriod {
+        state: eclipse_state_at(propagator, start)?,
+        start: *start,
+        end: *end,
+    };
+
+    let mut time = *start;
+    while time < *end {
+        let next = (time + step).min(*end);
+        let sampled = eclipse_state_at(propagator, &next)?;
+
+        // Several changes may fall between two samples, e.g. both penumbra edges
+        let mut from = time;
+        while sampled != current.state {
+            let (change, state) = refine_change(propagator, from, next)?;
+            periods.push(EclipsePeriod {
+                end: change,
+                ..current.clone()
+            });
+            current = EclipsePeriod {
+                state,
+                start: change,
+                end: *end,
+            };
+            from = change;
+        }
+
+        time = next;
+    }
+    periods.push(current);
+
+    Ok(periods)
+}
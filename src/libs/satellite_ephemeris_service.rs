@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use sgp4::Elements;
+
+use super::orbit::{self, Geodetic, LookAngles, Propagator, State};
+
+const MAX_SAMPLES: i64 = 100_000; // A mistyped span or step shouldn't fill the memory
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frame {
+    Teme,
+    Ecef,
+}
+
+#[derive(Debug, Clone)]
+pub struct EphemerisRow {
+    pub time: DateTime<Utc>,
+    pub state: State, // In the requested frame
+    pub geodetic: Geodetic,
+    pub look: Option<LookAngles>,
+}
+
+impl Frame {
+    pub fn parse(text: &str) -> Result<Frame, String> {
+        match text.trim().to_lowercase().as_str() {
+            "teme" => Ok(Frame::Teme),
+            "ecef" | "itrf" => Ok(Frame::Ecef),
+            other => Err(format!("Unknown frame '{}' (expected teme or ecef)", other)),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Frame::Teme => "TEME",
+            Frame::Ecef => "ECEF",
+        }
+    }
+}
+
+// Every `step` from `start`, with the end of the span always included
+pub fn sample_times(
+    start: &DateTime<Utc>,
+    duration: Duration,
+    step: Duration,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    if step <= Duration::zero() {
+        return Err("The step has to be positive".to_string());
+    }
+    let samples = duration.num_milliseconds() / step.num_milliseconds().max(1);
+    if samples >= MAX_SAMPLES {
+        return Err(format!(
+            "Too many samples ({}), use a step above {:.3} seconds",
+            samples + 1,
+            duration.num_milliseconds() as f64 / MAX_SAMPLES as f64 / 1000.0
+        ));
+    }
+
+    let end = start
+        .checked_add_signed(duration)
+        .ok_or("The time span is too long")?;
+    let mut times = Vec::new();
+    let mut time = *start;
+    while time < end {
+        times.push(time);
+        time += step;
+    }
+    times.push(end);
+
+    Ok(times)
+}
+
+/**
+Propagates a satellite over a time span
+
+# Arguments
+* `elements` - The element set to propagate
+* `times` - The times to propagate to
+* `frame` - The frame positions and velocities are given in
+* `observer` - When set, azimuth, elevation and range from this location are added
+# Returns
+* One row per time, or the propagation error
+*/
+pub fn generate_ephemeris(
+    elements: &Elements,
+    times: &[DateTime<Utc>],
+    frame: Frame,
+    observer: Option<&Geodetic>,
+) -> Result<Vec<EphemerisRow>, String> {
+    let propagator = Propagator::new(elements.clone())?;
+
+    times
+        .iter()
+        .map(|time| {
+            let teme = propagator.state_at(time)?;
+            let ecef = orbit::teme_state_to_ecef(&teme, time);
+            Ok(EphemerisRow {
+                time: *time,
+                state: match frame {
+                    Frame::Teme => teme,
+                    Frame::Ecef => ecef,
+                },
+                geodetic: orbit::ecef_to_geodetic(&ecef.position),
+                look: observer.map(|observer| orbit::look_angles(observer, &teme, time)),
+            })
+        })
+        .collect()
+}
+
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+pub fn ephemeris_to_csv(rows: &[EphemerisRow]) -> String {
+    let mut csv =
+        String::from("time,x_km,y_km,z_km,vx_km_s,vy_km_s,vz_km_s,latitude,longitude,altitude_km");
+    if rows.iter().any(|row| row.look.is_some()) {
+        csv.push_str(",azimuth,elevation,range_km,range_rate_km_s");
+    }
+    csv.push('\n');
+
+    for row in rows {
+        let [x, y, z] = row.state.position;
+        let [vx, vy, vz] = row.state.velocity;
+        csv.push_str(&format!(
+            "{},{:.6},{:.6},{:.6},{:.9},{:.9},{:.9},{:.6},{:.6},{:.6}",
+            timestamp(&row.time),
+            x,
+            y,
+            z,
+            vx,
+            vy,
+            vz,
+            row.geodetic.latitude,
+            row.geodetic.longitude,
+            row.geodetic.altitude_km
+        ));
+        if let Some(look) = &row.look {
+            csv.push_str(&format!(
+                ",{:.4},{:.4},{:.6},{:.9}",
+                look.azimuth, look.elevation, look.range_km, look.range_rate_km_s
+            ));
+        }
+        csv.push('\n');
+    }
+
+    csv
+}
+
+pub fn ephemeris_to_json(rows: &[EphemerisRow], frame: Frame) -> String {
+    let rows: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            let mut value = json!({
+                "time": timestamp(&row.time),
+                "position_km": row.state.position,
+                "velocity_km_s": row.state.velocity,
+                "latitude": row.geodetic.latitude,
+                "longitude": row.geodetic.longitude,
+                "altitude_km": row.geodetic.altitude_km,
+            });
+            if let Some(look) = &row.look {
+                value["azimuth"] = json!(look.azimuth);
+                value["elevation"] = json!(look.elevation);
+                value["range_km"] = json!(look.range_km);
+                value["range_rate_km_s"] = json!(look.range_rate_km_s);
+            }
+            value
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&json!({ "frame": frame.label(), "states": rows }))
+        .unwrap_or_default()
+}
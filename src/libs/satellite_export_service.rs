@@ -0,0 +1,332 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::{json, Value};
+use sgp4::Elements;
+
+use super::orbit::Propagator;
+use super::satellite_track_service::{self, Ring, TrackPoint};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    GeoJson,
+    Kml,
+    Gpx,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub start: DateTime<Utc>,
+    pub duration: Duration,
+    pub step: Duration,
+    pub include_position: bool,
+    pub include_footprint: bool,
+}
+
+// Everything exported for one satellite
+#[derive(Debug, Clone)]
+pub struct SatelliteTrack {
+    pub name: String,
+    pub norad_id: u64,
+    pub points: Vec<TrackPoint>,
+    pub position: Option<TrackPoint>,
+    pub footprint: Vec<Ring>,
+}
+
+impl ExportFormat {
+    pub fn parse(text: &str) -> Result<ExportFormat, String> {
+        match text.trim().to_lowercase().as_str() {
+            "geojson" | "json" => Ok(ExportFormat::GeoJson),
+            "kml" => Ok(ExportFormat::Kml),
+            "gpx" => Ok(ExportFormat::Gpx),
+            other => Err(format!(
+                "Unknown export format '{}' (expected geojson, kml or gpx)",
+                other
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::GeoJson => "geojson",
+            ExportFormat::Kml => "kml",
+            ExportFormat::Gpx => "gpx",
+        }
+    }
+}
+
+/**
+Propagates the ground track, and optionally the current position and footprint, of a satellite
+
+# Arguments
+* `elements` - The element set to propagate
+* `options` - The time range, step and optional extras
+# Returns
+* The track, or the propagation error
+*/
+pub fn build_track(elements: &Elements, options: &ExportOptions) -> Result<SatelliteTrack, String> {
+    let points = satellite_track_service::ground_track(
+        elements,
+        &options.start,
+        options.duration,
+        options.step,
+    )?;
+
+    let position = if options.include_position || options.include_footprint {
+        let propagator = Propagator::new(elements.clone())?;
+        Some(satellite_track_service::position_at(
+            &propagator,
+            &Utc::now(),
+        )?)
+    } else {
+        None
+    };
+    let footprint = match (&position, options.include_footprint) {
+        (Some(position), true) => satellite_track_service::footprint(&position.position),
+        _ => vec![],
+    };
+
+    Ok(SatelliteTrack {
+        name: elements.object_name.clone().unwrap_or_default(),
+        norad_id: elements.norad_id,
+        points,
+        position: position.filter(|_| options.include_position),
+        footprint,
+    })
+}
+
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// Six decimals are about 10 cm, plenty for a ground track
+fn round(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn to_geojson(tracks: &[SatelliteTrack]) -> String {
+    let mut features: Vec<Value> = Vec::new();
+
+    for track in tracks {
+        let segments = satellite_track_service::split_at_antimeridian(&track.points);
+        for (index, segment) in segments.iter().enumerate() {
+            let coordinates: Vec<Value> = segment
+                .iter()
+                .map(|p| json!([round(p.position.longitude), round(p.position.latitude)]))
+                .collect();
+            features.push(json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": coordinates },
+                "properties": {
+                    "kind": "track",
+                    "name": track.name,
+                    "norad_id": track.norad_id,
+                    "segment": index,
+                    "start": timestamp(&segment[0].time),
+                    "end": timestamp(&segment[segment.len() - 1].time),
+                },
+            }));
+        }
+
+        if let Some(position) = &track.position {
+            features.push(json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": [
+                        round(position.position.longitude),
+                        round(position.position.latitude),
+                    ],
+                },
+                "properties": {
+                    "kind": "position",
+                    "name": track.name,
+                    "norad_id": track.norad_id,
+                    "time": timestamp(&position.time),
+                    "altitude_km": round(position.position.altitude_km),
+                },
+            }));
+        }
+
+        if !track.footprint.is_empty() {
+            let polygons: Vec<Value> = track
+                .footprint
+                .iter()
+                .map(|ring| {
+                    let ring: Vec<Value> = ring
+                        .iter()
+                        .map(|(x, y)| json!([round(*x), round(*y)]))
+                        .collect();
+                    json!([ring])
+                })
+                .collect();
+            features.push(json!({
+                "type": "Feature",
+                "geometry": { "type": "MultiPolygon", "coordinates": polygons },
+                "properties": {
+                    "kind": "footprint",
+                    "name": track.name,
+                    "norad_id": track.norad_id,
+                },
+            }));
+        }
+    }
+
+    let collection = json!({ "type": "FeatureCollection", "features": features });
+    serde_json::to_string_pretty(&collection).unwrap_or_default()
+}
+
+fn to_kml(tracks: &[SatelliteTrack]) -> String {
+    let mut kml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n",
+    );
+
+    for track in tracks {
+        let name = escape_xml(&track.name);
+        kml.push_str(&format!(
+            "<Folder>\n<name>{} ({})</name>\n",
+            name, track.norad_id
+        ));
+
+        // Absolute altitudes put the orbit itself in the sky rather than on the ground
+        kml.push_str(&format!(
+            "<Placemark>\n<name>{} track</name>\n<MultiGeometry>\n",
+            name
+        ));
+        for segment in satellite_track_service::split_at_antimeridian(&track.points) {
+            let coordinates: Vec<String> = segment
+                .iter()
+                .map(|p| {
+                    format!(
+                        "{:.6},{:.6},{:.0}",
+                        p.position.longitude,
+                        p.position.latitude,
+                        p.position.altitude_km * 1000.0
+                    )
+                })
+                .collect();
+            kml.push_str(&format!(
+                "<LineString>\n<altitudeMode>absolute</altitudeMode>\n\
+                 <coordinates>{}</coordinates>\n</LineString>\n",
+                coordinates.join(" ")
+            ));
+        }
+        kml.push_str("</MultiGeometry>\n</Placemark>\n");
+
+        if let Some(position) = &track.position {
+            kml.push_str(&format!(
+                "<Placemark>\n<name>{}</name>\n<TimeStamp><when>{}</when></TimeStamp>\n\
+                 <Point>\n<altitudeMode>absolute</altitudeMode>\n\
+                 <coordinates>{:.6},{:.6},{:.0}</coordinates>\n</Point>\n</Placemark>\n",
+                name,
+                timestamp(&position.time),
+                position.position.longitude,
+                position.position.latitude,
+                position.position.altitude_km * 1000.0
+            ));
+        }
+
+        if !track.footprint.is_empty() {
+            kml.push_str(&format!(
+                "<Placemark>\n<name>{} footprint</name>\n<MultiGeometry>\n",
+                name
+            ));
+            for ring in &track.footprint {
+                let coordinates: Vec<String> = ring
+                    .iter()
+                    .map(|(x, y)| format!("{:.6},{:.6}", x, y))
+                    .collect();
+                kml.push_str(&format!(
+                    "<Polygon>\n<outerBoundaryIs><LinearRing>\
+                     <coordinates>{}</coordinates></LinearRing></outerBoundaryIs>\n</Polygon>\n",
+                    coordinates.join(" ")
+                ));
+            }
+            kml.push_str("</MultiGeometry>\n</Placemark>\n");
+        }
+
+        kml.push_str("</Folder>\n");
+    }
+
+    kml.push_str("</Document>\n</kml>\n");
+    kml
+}
+
+fn to_gpx(tracks: &[SatelliteTrack]) -> String {
+    let mut gpx = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <gpx version=\"1.1\" creator=\"cosmos_cli\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n",
+    );
+
+    // GPX wants its waypoints before any tracks
+    for track in tracks {
+        if let Some(position) = &track.position {
+            gpx.push_str(&format!(
+                "<wpt lat=\"{:.6}\" lon=\"{:.6}\"><ele>{:.0}</ele><time>{}</time><name>{}</name></wpt>\n",
+                position.position.latitude,
+                position.position.longitude,
+                position.position.altitude_km * 1000.0,
+                timestamp(&position.time),
+                escape_xml(&track.name)
+            ));
+        }
+    }
+
+    for track in tracks {
+        gpx.push_str(&format!(
+            "<trk>\n<name>{} ({})</name>\n",
+            escape_xml(&track.name),
+            track.norad_id
+        ));
+        for segment in satellite_track_service::split_at_antimeridian(&track.points) {
+            gpx.push_str("<trkseg>\n");
+            for point in segment {
+                gpx.push_str(&format!(
+                    "<trkpt lat=\"{:.6}\" lon=\"{:.6}\"><ele>{:.0}</ele><time>{}</time></trkpt>\n",
+                    point.position.latitude,
+                    point.position.longitude,
+                    point.position.altitude_km * 1000.0,
+                    timestamp(&point.time)
+                ));
+            }
+            gpx.push_str("</trkseg>\n");
+        }
+        gpx.push_str("</trk>\n");
+
+        // GPX has no polygons, the footprint outline is written as a track of its own
+        if !track.footprint.is_empty() {
+            gpx.push_str(&format!(
+                "<trk>\n<name>{} footprint</name>\n",
+                escape_xml(&track.name)
+            ));
+            for ring in &track.footprint {
+                gpx.push_str("<trkseg>\n");
+                for (longitude, latitude) in ring {
+                    gpx.push_str(&format!(
+                        "<trkpt lat=\"{:.6}\" lon=\"{:.6}\"></trkpt>\n",
+                        latitude, longitude
+                    ));
+                }
+                gpx.push_str("</trkseg>\n");
+            }
+            gpx.push_str("</trk>\n");
+        }
+    }
+
+    gpx.push_str("</gpx>\n");
+    gpx
+}
+
+pub fn export_tracks(tracks: &[SatelliteTrack], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::GeoJson => to_geojson(tracks),
+        ExportFormat::Kml => to_kml(tracks),
+        ExportFormat::Gpx => to_gpx(tracks),
+    }
+}
@@ -0,0 +1,182 @@
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+use super::orbit::{self, OrbitRegime};
+use super::satellite_catalog_service::CatalogEntry;
+use super::satellite_satcat_service::{self, ObjectType, OpsStatus, RcsSize};
+
+#[derive(Debug, Clone, Default)]
+pub struct CatalogFilter {
+    pub inclination: Option<RangeInclusive<f64>>,
+    // Both apsides have to fall inside the altitude range
+    pub altitude_km: Option<RangeInclusive<f64>>,
+    pub period_minutes: Option<RangeInclusive<f64>>,
+    pub eccentricity: Option<RangeInclusive<f64>>,
+    pub regimes: Vec<OrbitRegime>,
+    pub max_epoch_age_days: Option<f64>,
+    pub launch_year: Option<RangeInclusive<i32>>,
+    pub name: Option<Regex>,
+    // SATCAT metadata, objects without a SATCAT record never match these
+    pub object_types: Vec<ObjectType>,
+    pub owners: Vec<String>,
+    pub ops_statuses: Vec<OpsStatus>,
+    pub rcs_sizes: Vec<RcsSize>,
+}
+
+/**
+Parses a range written as `MIN..MAX`, `MIN..`, `..MAX` or a single value
+
+# Arguments
+* `text` - The range as typed by the user
+# Returns
+* The inclusive range, open ends are filled with the type's extremes
+*/
+pub fn parse_range<T>(text: &str, lowest: T, highest: T) -> Result<RangeInclusive<T>, String>
+where
+    T: std::str::FromStr + Copy,
+{
+    let parse = |value: &str, default: T| -> Result<T, String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(default);
+        }
+        value
+            .parse::<T>()
+            .map_err(|_| format!("Invalid number '{}' in range '{}'", value, text))
+    };
+
+    match text.split_once("..") {
+        Some((min, max)) => Ok(parse(min, lowest)?..=parse(max, highest)?),
+        None => {
+            let value = parse(text, lowest)?;
+            Ok(value..=value)
+        }
+    }
+}
+
+pub fn parse_regime(text: &str) -> Result<OrbitRegime, String> {
+    match text.trim().to_lowercase().as_str() {
+        "leo" => Ok(OrbitRegime::Leo),
+        "sso" => Ok(OrbitRegime::Sso),
+        "meo" => Ok(OrbitRegime::Meo),
+        "geo" => Ok(OrbitRegime::Geo),
+        "heo" => Ok(OrbitRegime::Heo),
+        "molniya" => Ok(OrbitRegime::Molniya),
+        "other" => Ok(OrbitRegime::Other),
+        other => Err(format!(
+            "Unknown orbit regime '{}' (expected leo, sso, meo, geo, heo, molniya or other)",
+            other
+        )),
+    }
+}
+
+pub fn parse_object_type(text: &str) -> Result<ObjectType, String> {
+    ObjectType::parse(text).ok_or_else(|| {
+        format!(
+            "Unknown object type '{}' (expected payload, rocket-body, debris or unknown)",
+            text.trim()
+        )
+    })
+}
+
+pub fn parse_ops_status(text: &str) -> Result<OpsStatus, String> {
+    OpsStatus::parse(text).ok_or_else(|| {
+        format!(
+            "Unknown status '{}' (expected operational, nonoperational, partial, backup, spare, \
+             extended, decayed or unknown)",
+            text.trim()
+        )
+    })
+}
+
+pub fn parse_rcs_size(text: &str) -> Result<RcsSize, String> {
+    RcsSize::parse(text).ok_or_else(|| {
+        format!(
+            "Unknown RCS size '{}' (expected small, medium or large)",
+            text.trim()
+        )
+    })
+}
+
+// Accepts both the TLE form (98067A) and the COSPAR form (1998-067A)
+pub fn designator_year(designator: &str) -> Option<i32> {
+    let designator = designator.trim();
+    if designator.contains('-') {
+        return designator.get(0..4)?.parse().ok();
+    }
+
+    let year: i32 = designator.get(0..2)?.parse().ok()?;
+    Some(if year < 57 { 2000 + year } else { 1900 + year })
+}
+
+impl CatalogFilter {
+    pub fn matches(&self, entry: &CatalogEntry, now: &DateTime<Utc>) -> bool {
+        let elements = &entry.elements;
+        let derived = orbit::derive_parameters(elements, now);
+
+        let in_range = |range: &Option<RangeInclusive<f64>>, value: f64| {
+            range.as_ref().is_none_or(|range| range.contains(&value))
+        };
+
+        in_range(&self.inclination, elements.inclination)
+            && in_range(&self.eccentricity, elements.eccentricity)
+            && in_range(&self.period_minutes, derived.period_minutes)
+            && in_range(&self.altitude_km, derived.perigee_altitude_km)
+            && in_range(&self.altitude_km, derived.apogee_altitude_km)
+            && (self.regimes.is_empty() || self.regimes.contains(&derived.regime))
+            && self
+                .max_epoch_age_days
+                .is_none_or(|max_age| derived.epoch_age_days <= max_age)
+            && self.launch_year.as_ref().is_none_or(|years| {
+                elements
+                    .international_designator
+                    .as_deref()
+                    .and_then(designator_year)
+                    .is_some_and(|year| years.contains(&year))
+            })
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|name| name.is_match(&entry.satellite.name))
+            && self.matches_satcat(entry.satellite.id)
+    }
+
+    fn matches_satcat(&self, norad_id: i32) -> bool {
+        if self.object_types.is_empty()
+            && self.owners.is_empty()
+            && self.ops_statuses.is_empty()
+            && self.rcs_sizes.is_empty()
+        {
+            return true;
+        }
+        let Some(record) = satellite_satcat_service::get_record(norad_id) else {
+            return false;
+        };
+
+        (self.object_types.is_empty() || self.object_types.contains(&record.object_type))
+            && (self.owners.is_empty()
+                || record.owner.as_ref().is_some_and(|owner| {
+                    self.owners
+                        .iter()
+                        .any(|wanted| wanted.eq_ignore_ascii_case(owner))
+                }))
+            && (self.ops_statuses.is_empty() || self.ops_statuses.contains(&record.ops_status))
+            && (self.rcs_sizes.is_empty()
+                || record
+                    .rcs_size
+                    .is_some_and(|size| self.rcs_sizes.contains(&size)))
+    }
+}
+
+pub fn filter_catalog<'a>(
+    catalog: &'a [CatalogEntry],
+    filter: &CatalogFilter,
+    now: &DateTime<Utc>,
+) -> Vec<&'a CatalogEntry> {
+    catalog
+        .iter()
+        .filter(|entry| filter.matches(entry, now))
+        .collect()
+}
@@ -0,0 +1,165 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Hamlib error code for a command the daemon doesn't implement
+pub const RIG_ENIMPL: i32 = -4;
+
+// A connection to a rotctld or rigctld daemon speaking the default line protocol
+pub struct HamlibConnection {
+    address: String,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl HamlibConnection {
+    pub fn connect(address: &str) -> Result<HamlibConnection, String> {
+        let socket = address
+            .to_socket_addrs()
+            .map_err(|e| format!("Invalid address {}: {}", address, e))?
+            .next()
+            .ok_or(format!("Invalid address {}", address))?;
+        let stream = TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT)
+            .map_err(|e| format!("Error connecting to {}: {}", address, e))?;
+        stream
+            .set_read_timeout(Some(REPLY_TIMEOUT))
+            .map_err(|e| format!("Error configuring {}: {}", address, e))?;
+        let writer = stream
+            .try_clone()
+            .map_err(|e| format!("Error configuring {}: {}", address, e))?;
+
+        Ok(HamlibConnection {
+            address: address.to_string(),
+            reader: BufReader::new(stream),
+            writer,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /**
+    Sends one command and reads its reply
+
+    # Arguments
+    * `command` - The command line without the newline, e.g. `P 180.0 45.0`
+    * `values` - How many value lines a get command answers with, 0 for set commands
+    # Returns
+    * The value lines, or the error when the daemon answers `RPRT` with a non-zero code
+    */
+    pub fn command(&mut self, command: &str, values: usize) -> Result<Vec<String>, String> {
+        writeln!(self.writer, "{}", command)
+            .map_err(|e| format!("Error sending '{}' to {}: {}", command, self.address, e))?;
+
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| format!("Error reading reply from {}: {}", self.address, e))?;
+            if read == 0 {
+                return Err(format!("{} closed the connection", self.address));
+            }
+
+            let line = line.trim();
+            if let Some(code) = line.strip_prefix("RPRT") {
+                return match code.trim().parse::<i32>() {
+                    Ok(0) => Ok(lines),
+                    Ok(code) => Err(format!(
+                        "{} rejected '{}' (RPRT {})",
+                        self.address, command, code
+                    )),
+                    Err(_) => Err(format!("Unexpected reply from {}: {}", self.address, line)),
+                };
+            }
+
+            lines.push(line.to_string());
+            if values > 0 && lines.len() == values {
+                return Ok(lines);
+            }
+        }
+    }
+
+    pub fn close(mut self) {
+        let _ = writeln!(self.writer, "q");
+    }
+}
+
+pub fn report(code: i32) -> String {
+    format!("RPRT {}\n", code)
+}
+
+// Binds a stand-in daemon to localhost, port 0 picks a free one (see `TcpListener::local_addr`)
+pub fn listen_locally(port: u16) -> Result<TcpListener, String> {
+    TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Error listening on port {}: {}", port, e))
+}
+
+/**
+Runs a stand-in daemon for trying the tracking modes without hardware
+
+Clients are served one at a time and every command is logged, so a tracking run
+can be checked against what a real rotctld or rigctld would have been sent.
+
+# Arguments
+* `listener` - Where to accept clients, see `listen_locally`
+* `handler` - Answers a command line with the full reply text, or None to close the connection
+* `log` - Called with each command received
+*/
+pub fn serve_fake_daemon_on<H, L>(
+    listener: TcpListener,
+    mut handler: H,
+    log: L,
+) -> Result<(), String>
+where
+    H: FnMut(&str) -> Option<String>,
+    L: Fn(&str),
+{
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Error accepting connection: {}", err);
+                continue;
+            }
+        };
+        let peer = stream
+            .peer_addr()
+            .map(|address| address.to_string())
+            .unwrap_or_default();
+        log(&format!("{} connected", peer));
+
+        let mut writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(err) => {
+                eprintln!("Error accepting connection: {}", err);
+                continue;
+            }
+        };
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            log(line);
+            match handler(line) {
+                Some(reply) => {
+                    if writer.write_all(reply.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        log(&format!("{} disconnected", peer));
+    }
+
+    Ok(())
+}
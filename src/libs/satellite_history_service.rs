@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sgp4::Elements;
+
+use super::orbit::{self, EARTH_RADIUS_KM};
+use super::satellite_search_service::Satellite;
+
+// Epochs known to be in the store per NORAD ID, so fetching the same element sets again (search
+// pages, catalog downloads, watch refreshes) doesn't touch the disk
+static STORED_EPOCHS: OnceLock<Mutex<HashMap<i32, HashSet<DateTime<Utc>>>>> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub epoch: DateTime<Utc>,
+    #[serde(rename = "fetchedAt")]
+    pub fetched_at: DateTime<Utc>,
+    pub satellite: Satellite,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelinePoint {
+    pub epoch: DateTime<Utc>,
+    pub inclination: f64,
+    pub eccentricity: f64,
+    pub mean_motion: f64,
+    pub perigee_altitude_km: f64,
+    pub apogee_altitude_km: f64,
+    pub bstar: f64,
+}
+
+// Local data lives in `COSMOS_DATA_DIR`, defaulting to ~/.cosmos_cli
+pub fn data_dir() -> PathBuf {
+    env::var("COSMOS_DATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(home).join(".cosmos_cli")
+        })
+}
+
+fn history_path(norad_id: i32) -> PathBuf {
+    data_dir()
+        .join("history")
+        .join(format!("{}.json", norad_id))
+}
+
+// Reads the epoch (YYDDD.DDDDDDDD, columns 19-32) straight from line 1
+pub fn tle_epoch(line_one: &str) -> Option<DateTime<Utc>> {
+    let field = line_one.get(18..32)?.trim();
+    let year: i32 = field.get(0..2)?.parse().ok()?;
+    let day_of_year: f64 = field.get(2..)?.parse().ok()?;
+    // Two digit years from 57 onwards belong to the 1900s, as in the TLE spec
+    let year = if year < 57 { 2000 + year } else { 1900 + year };
+
+    let start_of_year = Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).single()?;
+    Some(start_of_year + Duration::microseconds(((day_of_year - 1.0) * 86400e6).round() as i64))
+}
+
+pub fn get_history(norad_id: i32) -> Vec<HistoryEntry> {
+    fs::read_to_string(history_path(norad_id))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_history(norad_id: i32, entries: &[HistoryEntry]) -> Result<(), String> {
+    let path = history_path(norad_id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let body = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    fs::write(path, body).map_err(|e| e.to_string())
+}
+
+/**
+Adds freshly fetched TLEs to the local history store
+
+The stored epochs of an object are read once per session, after that only element sets with a
+new epoch cost a read and write of that object's file.
+
+# Arguments
+* `satellites` - The satellites as returned by the API; element sets already stored for the
+  same epoch are ignored
+*/
+pub fn record_history(satellites: &[Satellite]) {
+    let fetched_at = Utc::now();
+    let mut stored = STORED_EPOCHS
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    for satellite in satellites {
+        let Some(epoch) = tle_epoch(&satellite.line_one) else {
+            continue;
+        };
+        if stored
+            .get(&satellite.id)
+            .is_some_and(|epochs| epochs.contains(&epoch))
+        {
+            continue;
+        }
+
+        let mut entries = get_history(satellite.id);
+        let epochs = stored.entry(satellite.id).or_default();
+        epochs.extend(entries.iter().map(|entry| entry.epoch));
+        if !epochs.insert(epoch) {
+            continue;
+        }
+
+        entries.push(HistoryEntry {
+            epoch,
+            fetched_at,
+            satellite: satellite.clone(),
+        });
+        entries.sort_by_key(|entry| entry.epoch);
+
+        if let Err(err) = save_history(satellite.id, &entries) {
+            eprintln!("Error saving TLE history for {}: {}", satellite.id, err);
+        }
+    }
+}
+
+pub fn build_timeline(history: &[Elements]) -> Vec<TimelinePoint> {
+    history
+        .iter()
+        .map(|elements| TimelinePoint {
+            epoch: orbit::epoch_of(elements),
+            inclination: elements.inclination,
+            eccentricity: elements.eccentricity,
+            mean_motion: elements.mean_motion,
+            perigee_altitude_km: orbit::perigee_radius_km(elements) - EARTH_RADIUS_KM,
+            apogee_altitude_km: orbit::apogee_radius_km(elements) - EARTH_RADIUS_KM,
+            bstar: elements.drag_term,
+        })
+        .collect()
+}
+
+pub fn timeline_to_csv(timeline: &[TimelinePoint]) -> String {
+    let mut csv = String::from(
+        "epoch,inclination_deg,eccentricity,mean_motion_rev_per_day,perigee_alt_km,apogee_alt_km,bstar\n",
+    );
+
+    for point in timeline {
+        csv.push_str(&format!(
+            "{},{:.4},{:.7},{:.8},{:.3},{:.3},{:e}\n",
+            point.epoch.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            point.inclination,
+            point.eccentricity,
+            point.mean_motion,
+            point.perigee_altitude_km,
+            point.apogee_altitude_km,
+            point.bstar
+        ));
+    }
+
+    csv
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fs;
+
+use super::satellite_history_service::data_dir;
+use super::satellite_search_service::Satellite;
+
+// Common names that don't appear in the catalog name, by NORAD ID
+const BUILTIN_ALIASES: [(&str, i32); 12] = [
+    ("HUBBLE", 20580),
+    ("HST", 20580),
+    ("HUBBLE SPACE TELESCOPE", 20580),
+    ("ISS", 25544),
+    ("INTERNATIONAL SPACE STATION", 25544),
+    ("ZARYA", 25544),
+    ("TIANGONG", 48274),
+    ("CSS", 48274),
+    ("CHINESE SPACE STATION", 48274),
+    ("TIANHE", 48274),
+    ("SWIFT", 28485),
+    ("FERMI", 33053),
+];
+
+const EXACT_ID_SCORE: f64 = 1000.0;
+const EXACT_ALIAS_SCORE: f64 = 900.0;
+const EXACT_NAME_SCORE: f64 = 800.0;
+const DESIGNATOR_PREFIX_SCORE: f64 = 700.0;
+const ALIAS_PREFIX_SCORE: f64 = 650.0;
+const NAME_PREFIX_SCORE: f64 = 600.0;
+const NAME_CONTAINS_SCORE: f64 = 500.0;
+const COMPACT_NAME_CONTAINS_SCORE: f64 = 450.0;
+const FUZZY_SCORE: f64 = 300.0;
+
+struct IndexEntry {
+    satellite: Satellite,
+    name: String,
+    name_compact: String,
+    name_tokens: Vec<String>,
+    designator: Option<String>, // Compact COSPAR form, e.g. 1998067A
+    aliases: Vec<String>,
+}
+
+pub struct SearchIndex {
+    entries: Vec<IndexEntry>,
+}
+
+pub struct SearchHit<'a> {
+    pub satellite: &'a Satellite,
+    pub designator: Option<String>, // COSPAR form, e.g. 1998-067A
+}
+
+// Upper case, with punctuation turned into single spaces
+fn normalize(text: &str) -> String {
+    text.to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn compact(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+// Columns 10-17 of line 1 hold the designator as YYNNNPPP, returned in compact form (1998067A)
+pub fn designator_from_tle(line_one: &str) -> Option<String> {
+    let field = line_one.get(9..17)?.trim();
+    let year: i32 = field.get(0..2)?.parse().ok()?;
+    let year = if year < 57 { 2000 + year } else { 1900 + year };
+
+    Some(format!("{}{}", year, field.get(2..)?))
+}
+
+fn format_designator(designator: &str) -> String {
+    match (
+        designator.get(0..4),
+        designator.get(4..7),
+        designator.get(7..),
+    ) {
+        (Some(year), Some(number), Some(piece)) => format!("{}-{}{}", year, number, piece),
+        _ => designator.to_string(),
+    }
+}
+
+// Optimal string alignment distance, a swapped pair of letters counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j - 1] + cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+// How well a single query token matches a single candidate token, from 0 to 1
+fn token_similarity(query: &str, candidate: &str) -> f64 {
+    if query == candidate {
+        return 1.0;
+    }
+    if candidate.starts_with(query) {
+        return 0.9;
+    }
+
+    // Allow roughly one typo per four characters
+    let allowed = (query.chars().count() / 4).max(1);
+    let prefix: String = candidate.chars().take(query.chars().count() + 1).collect();
+    let distance = edit_distance(query, candidate).min(edit_distance(query, &prefix));
+    if distance <= allowed {
+        return 0.8 - 0.15 * distance as f64;
+    }
+
+    if query.chars().count() >= 3 && is_subsequence(query, candidate) {
+        return 0.5;
+    }
+
+    0.0
+}
+
+// Every query token has to match some candidate token for the text to count as a fuzzy hit
+fn fuzzy_similarity(query_tokens: &[&str], candidate_tokens: &[String]) -> f64 {
+    let mut total = 0.0;
+    for query in query_tokens {
+        let best = candidate_tokens
+            .iter()
+            .map(|candidate| token_similarity(query, candidate))
+            .fold(0.0, f64::max);
+        if best == 0.0 {
+            return 0.0;
+        }
+        total += best;
+    }
+
+    total / query_tokens.len() as f64
+}
+
+// User aliases live in `aliases.json` in the data directory as {"alias": norad_id}
+fn load_aliases() -> HashMap<String, i32> {
+    let mut aliases: HashMap<String, i32> = BUILTIN_ALIASES
+        .iter()
+        .map(|(alias, id)| (normalize(alias), *id))
+        .collect();
+
+    if let Ok(contents) = fs::read_to_string(data_dir().join("aliases.json")) {
+        match serde_json::from_str::<HashMap<String, i32>>(&contents) {
+            Ok(user_aliases) => {
+                aliases.extend(
+                    user_aliases
+                        .into_iter()
+                        .map(|(alias, id)| (normalize(&alias), id)),
+                );
+            }
+            Err(err) => eprintln!("Error reading aliases.json: {}", err),
+        }
+    }
+
+    aliases
+}
+
+impl IndexEntry {
+    fn score(&self, query: &str, query_tokens: &[&str]) -> f64 {
+        let query_compact = compact(query);
+        let mut score: f64 = 0.0;
+
+        if query_compact == self.satellite.id.to_string() {
+            score = score.max(EXACT_ID_SCORE);
+        }
+
+        if let Some(designator) = &self.designator {
+            // Both 1998-067A and the short TLE form 98067A are accepted
+            let short = designator.get(2..).unwrap_or_default();
+            if query_compact == *designator || query_compact == short {
+                score = score.max(EXACT_ID_SCORE);
+            } else if query_compact.len() >= 4
+                && query_compact.chars().any(|c| c.is_ascii_digit())
+                && (designator.starts_with(&query_compact) || short.starts_with(&query_compact))
+            {
+                // The main piece (A) should come before its debris (ZZ)
+                let remaining = designator.len() - query_compact.len().min(designator.len());
+                score = score.max(DESIGNATOR_PREFIX_SCORE - remaining as f64);
+            }
+        }
+
+        for alias in &self.aliases {
+            if alias == query {
+                score = score.max(EXACT_ALIAS_SCORE);
+            } else if alias.starts_with(query) {
+                score = score.max(ALIAS_PREFIX_SCORE);
+            } else {
+                let alias_tokens: Vec<String> = alias.split(' ').map(str::to_string).collect();
+                score = score.max(FUZZY_SCORE * fuzzy_similarity(query_tokens, &alias_tokens));
+            }
+        }
+
+        if self.name == query {
+            score = score.max(EXACT_NAME_SCORE);
+        } else if self.name.starts_with(query) {
+            score = score.max(NAME_PREFIX_SCORE);
+        } else if let Some(position) = self.name.find(query) {
+            score = score.max(NAME_CONTAINS_SCORE - position as f64);
+        } else if self.name_compact.contains(&query_compact) {
+            // "NOAA19" for "NOAA 19"
+            score = score.max(COMPACT_NAME_CONTAINS_SCORE);
+        } else {
+            score = score.max(FUZZY_SCORE * fuzzy_similarity(query_tokens, &self.name_tokens));
+        }
+
+        score
+    }
+}
+
+impl SearchIndex {
+    pub fn build(satellites: &[Satellite]) -> SearchIndex {
+        let aliases = load_aliases();
+        let mut aliases_by_id: HashMap<i32, Vec<String>> = HashMap::new();
+        for (alias, id) in aliases {
+            aliases_by_id.entry(id).or_default().push(alias);
+        }
+
+        let entries = satellites
+            .iter()
+            .map(|satellite| {
+                let name = normalize(&satellite.name);
+                IndexEntry {
+                    satellite: satellite.clone(),
+                    name_tokens: name.split(' ').map(str::to_string).collect(),
+                    name_compact: compact(&name),
+                    name,
+                    designator: designator_from_tle(&satellite.line_one),
+                    aliases: aliases_by_id
+                        .get(&satellite.id)
+                        .cloned()
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        SearchIndex { entries }
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /**
+    Ranks the indexed satellites against a free-text query
+
+    # Arguments
+    * `query` - A (possibly misspelled) name, alias, NORAD ID or COSPAR designator
+    * `limit` - The maximum number of hits to return
+    # Returns
+    * The best hits, most relevant first
+    */
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit<'_>> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return vec![];
+        }
+        let query_tokens: Vec<&str> = query.split(' ').collect();
+
+        let mut hits: Vec<(&IndexEntry, f64)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry, entry.score(&query, &query_tokens)))
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+
+        // Shorter names win ties, "ISS" should beat "ISS DEB" for the same score
+        hits.sort_by(|(a, score_a), (b, score_b)| {
+            score_b
+                .total_cmp(score_a)
+                .then(a.name.len().cmp(&b.name.len()))
+                .then(a.satellite.id.cmp(&b.satellite.id))
+        });
+
+        hits.into_iter()
+            .take(limit)
+            .map(|(entry, _)| SearchHit {
+                satellite: &entry.satellite,
+                designator: entry.designator.as_deref().map(format_designator),
+            })
+            .collect()
+    }
+}
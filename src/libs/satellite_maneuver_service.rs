@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use super::orbit;
+
+// Floors for the jump thresholds, below these TLE fitting noise dominates
+const MIN_SEMI_MAJOR_AXIS_JUMP_KM: f64 = 0.2;
+const MIN_INCLINATION_JUMP_DEG: f64 = 0.005;
+const MIN_RAAN_JUMP_DEG: f64 = 0.01;
+// How many median absolute deviations a residual must exceed to count as a jump
+const MAD_FACTOR: f64 = 6.0;
+// The noise estimate needs a few samples before it can be trusted
+const MIN_PAIRS_FOR_MAD: usize = 5;
+// In-plane changes above this are orbit raising or lowering rather than station-keeping
+const ORBIT_CHANGE_MIN_KM: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManeuverKind {
+    OrbitRaising,
+    OrbitLowering,
+    PlaneChange,
+    StationKeeping,
+}
+
+impl ManeuverKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ManeuverKind::OrbitRaising => "Orbit raising",
+            ManeuverKind::OrbitLowering => "Orbit lowering",
+            ManeuverKind::PlaneChange => "Plane change",
+            ManeuverKind::StationKeeping => "Station-keeping",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Maneuver {
+    pub after: DateTime<Utc>,
+    pub before: DateTime<Utc>,
+    pub time: DateTime<Utc>,
+    pub kind: ManeuverKind,
+    pub semi_major_axis_change_km: f64,
+    pub inclination_change_deg: f64,
+    pub raan_change_deg: f64,
+    pub delta_v_m_s: f64,
+}
+
+// Differences between consecutive element sets once natural drift is taken out
+struct Residual {
+    semi_major_axis_km: f64,
+    inclination_deg: f64,
+    raan_deg: f64,
+}
+
+fn wrap_degrees(angle: f64) -> f64 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn residual(previous: &Elements, next: &Elements) -> Residual {
+    let days = (orbit::epoch_of(next) - orbit::epoch_of(previous)).num_seconds() as f64
+        / orbit::SECONDS_PER_DAY;
+
+    // Drag decay from the TLE's (halved) mean motion derivative: da/dt = -2/3 * a * n_dot / n
+    let a = orbit::semi_major_axis_km(previous);
+    let expected_da =
+        -2.0 / 3.0 * a * (2.0 * previous.mean_motion_dot) / previous.mean_motion * days;
+    let expected_draan = orbit::raan_drift_deg_per_day(previous) * days;
+
+    Residual {
+        semi_major_axis_km: orbit::semi_major_axis_km(next) - a - expected_da,
+        inclination_deg: next.inclination - previous.inclination,
+        raan_deg: wrap_degrees(next.right_ascension - previous.right_ascension - expected_draan),
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    // Both indices coincide for odd lengths
+    (values[(values.len() - 1) / 2] + values[values.len() / 2]) / 2.0
+}
+
+fn jump_threshold(values: &[f64], floor: f64) -> f64 {
+    if values.len() < MIN_PAIRS_FOR_MAD {
+        return floor;
+    }
+    let center = median(values.to_vec());
+    let mad = median(values.iter().map(|v| (v - center).abs()).collect());
+
+    (MAD_FACTOR * mad).max(floor)
+}
+
+// Angle between the orbit normals of two planes, in radians
+fn plane_angle(i1: f64, i2: f64, delta_raan: f64) -> f64 {
+    let (i1, i2, delta_raan) = (i1.to_radians(), i2.to_radians(), delta_raan.to_radians());
+    let cos_angle = i1.cos() * i2.cos() + i1.sin() * i2.sin() * delta_raan.cos();
+
+    cos_angle.clamp(-1.0, 1.0).acos()
+}
+
+/**
+Detects likely maneuvers in a sequence of element sets of the same object
+
+# Arguments
+* `history` - Element sets ordered by epoch, e.g. parsed via `utils::parse_tle`
+# Returns
+* One entry per jump in semi-major axis, inclination or RAAN that natural drift doesn't explain
+*/
+pub fn detect_maneuvers(history: &[Elements]) -> Vec<Maneuver> {
+    if history.len() < 2 {
+        return vec![];
+    }
+
+    let residuals: Vec<Residual> = history
+        .windows(2)
+        .map(|pair| residual(&pair[0], &pair[1]))
+        .collect();
+
+    let a_threshold = jump_threshold(
+        &residuals
+            .iter()
+            .map(|r| r.semi_major_axis_km)
+            .collect::<Vec<_>>(),
+        MIN_SEMI_MAJOR_AXIS_JUMP_KM,
+    );
+    let i_threshold = jump_threshold(
+        &residuals
+            .iter()
+            .map(|r| r.inclination_deg)
+            .collect::<Vec<_>>(),
+        MIN_INCLINATION_JUMP_DEG,
+    );
+    let raan_threshold = jump_threshold(
+        &residuals.iter().map(|r| r.raan_deg).collect::<Vec<_>>(),
+        MIN_RAAN_JUMP_DEG,
+    );
+
+    history
+        .windows(2)
+        .zip(residuals.iter())
+        .filter_map(|(pair, residual)| {
+            let in_plane = residual.semi_major_axis_km.abs() > a_threshold;
+            let out_of_plane = residual.inclination_deg.abs() > i_threshold
+                || residual.raan_deg.abs() > raan_threshold;
+            if !in_plane && !out_of_plane {
+                return None;
+            }
+
+            let (previous, next) = (&pair[0], &pair[1]);
+            let a = orbit::semi_major_axis_km(previous);
+            let velocity = orbit::circular_velocity_km_s(a);
+
+            // Tangential burn on a near-circular orbit: dv = v * da / (2a)
+            let in_plane_dv = if in_plane {
+                velocity * residual.semi_major_axis_km.abs() / (2.0 * a)
+            } else {
+                0.0
+            };
+            let plane_dv = if out_of_plane {
+                let angle = plane_angle(previous.inclination, next.inclination, residual.raan_deg);
+                2.0 * velocity * (angle / 2.0).sin()
+            } else {
+                0.0
+            };
+
+            let kind = if out_of_plane && plane_dv >= in_plane_dv {
+                ManeuverKind::PlaneChange
+            } else if residual.semi_major_axis_km > ORBIT_CHANGE_MIN_KM {
+                ManeuverKind::OrbitRaising
+            } else if residual.semi_major_axis_km < -ORBIT_CHANGE_MIN_KM {
+                ManeuverKind::OrbitLowering
+            } else {
+                ManeuverKind::StationKeeping
+            };
+
+            let after = orbit::epoch_of(previous);
+            let before = orbit::epoch_of(next);
+
+            Some(Maneuver {
+                after,
+                before,
+                // The burn happened somewhere between the two epochs
+                time: after + (before - after) / 2,
+                kind,
+                semi_major_axis_change_km: residual.semi_major_axis_km,
+                inclination_change_deg: residual.inclination_deg,
+                raan_change_deg: residual.raan_deg,
+                delta_v_m_s: (in_plane_dv.powi(2) + plane_dv.powi(2)).sqrt() * 1000.0,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,671 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use sgp4::Elements;
+
+use super::orbit::{self, Propagator, Vector3};
+
+/*
+CCSDS Orbit Ephemeris Message (CCSDS 502.0-B-2), written and read in both encodings:
+
+    KVN: `KEYWORD = value` header and metadata lines followed by `EPOCH X Y Z X_DOT Y_DOT Z_DOT`
+    XML: <oem><header/><body><segment><metadata/><data><stateVector/>...</data></segment></body>
+
+Units are km and km/s, epochs are UTC.
+*/
+
+const OEM_VERSION: &str = "2.0";
+const ORIGINATOR: &str = "COSMOS_CLI";
+const EPOCH_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f";
+const MAX_STATES: i64 = 100_000; // A mistyped step shouldn't fill the memory
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OemFrame {
+    Teme,
+    Eme2000,
+    Itrf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OemEncoding {
+    Kvn,
+    Xml,
+}
+
+#[derive(Debug, Clone)]
+pub struct OemState {
+    pub epoch: DateTime<Utc>,
+    pub position: Vector3,
+    pub velocity: Vector3,
+}
+
+// One metadata block with its states, the metadata is kept as ordered keyword / value pairs
+#[derive(Debug, Clone)]
+pub struct OemSegment {
+    pub metadata: Vec<(String, String)>,
+    pub comments: Vec<String>,
+    pub states: Vec<OemState>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Oem {
+    pub header: Vec<(String, String)>,
+    pub segments: Vec<OemSegment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OemOptions {
+    pub start: DateTime<Utc>,
+    pub stop: DateTime<Utc>,
+    pub step_seconds: f64,
+    pub frame: OemFrame,
+    // Interpolation method and degree recommended to users of the file, e.g. LAGRANGE / 7
+    pub interpolation: Option<(String, u32)>,
+}
+
+impl OemFrame {
+    pub fn parse(text: &str) -> Result<OemFrame, String> {
+        match text.trim().to_uppercase().as_str() {
+            "TEME" => Ok(OemFrame::Teme),
+            "EME2000" | "J2000" => Ok(OemFrame::Eme2000),
+            "ITRF" | "ECEF" => Ok(OemFrame::Itrf),
+            other => Err(format!(
+                "Unknown frame '{}' (expected teme, eme2000 or itrf)",
+                other
+            )),
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            OemFrame::Teme => "TEME",
+            OemFrame::Eme2000 => "EME2000",
+            OemFrame::Itrf => "ITRF",
+        }
+    }
+}
+
+impl OemEncoding {
+    pub fn parse(text: &str) -> Result<OemEncoding, String> {
+        match text.trim().to_lowercase().as_str() {
+            "kvn" | "oem" => Ok(OemEncoding::Kvn),
+            "xml" => Ok(OemEncoding::Xml),
+            other => Err(format!(
+                "Unknown OEM format '{}' (expected kvn or xml)",
+                other
+            )),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OemEncoding::Kvn => "oem",
+            OemEncoding::Xml => "xml",
+        }
+    }
+}
+
+impl OemSegment {
+    pub fn get(&self, keyword: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(key, _)| key == keyword)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn format_epoch(time: &DateTime<Utc>) -> String {
+    time.format(EPOCH_FORMAT).to_string()
+}
+
+// CCSDS epochs may also use day-of-year (2024-122T12:00:00) and a trailing Z
+fn parse_epoch(text: &str) -> Result<DateTime<Utc>, String> {
+    let text = text.trim().trim_end_matches('Z');
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%jT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|time| Utc.from_utc_datetime(&time))
+        .ok_or_else(|| format!("Invalid epoch '{}'", text))
+}
+
+// The full COSPAR form (1998-067A) from the TLE form (98067A)
+fn object_id(elements: &Elements) -> String {
+    let Some(designator) = elements.international_designator.as_deref() else {
+        return elements.norad_id.to_string();
+    };
+    match (
+        designator
+            .get(0..2)
+            .and_then(|year| year.parse::<i32>().ok()),
+        designator.get(2..),
+    ) {
+        (Some(year), Some(rest)) if rest.len() >= 3 => {
+            let year = if year < 57 { 2000 + year } else { 1900 + year };
+            format!("{}-{}", year, rest)
+        }
+        _ => designator.to_string(),
+    }
+}
+
+/**
+Propagates a satellite and packages the states as an OEM
+
+# Arguments
+* `elements` - The element set to propagate
+* `options` - Time span, step, reference frame and interpolation hint
+# Returns
+* The message, or the propagation error
+*/
+pub fn build_oem(elements: &Elements, options: &OemOptions) -> Result<Oem, String> {
+    if options.stop <= options.start {
+        return Err("The stop time has to be after the start time".to_string());
+    }
+    if !(options.step_seconds > 0.0 && options.step_seconds.is_finite()) {
+        return Err("The step has to be positive".to_string());
+    }
+    // Steps are whole milliseconds, a shorter one would never move the epoch forward
+    let step = chrono::Duration::milliseconds((options.step_seconds * 1000.0) as i64);
+    if step <= chrono::Duration::zero() {
+        return Err("The step has to be at least a millisecond".to_string());
+    }
+    let count = (options.stop - options.start).num_milliseconds() / step.num_milliseconds() + 1;
+    if count > MAX_STATES {
+        return Err(format!(
+            "Too many states ({}), use a step above {:.3} seconds",
+            count,
+            (options.stop - options.start).num_milliseconds() as f64 / MAX_STATES as f64 / 1000.0
+        ));
+    }
+
+    let propagator = Propagator::new(elements.clone())?;
+
+    let mut states = Vec::new();
+    let mut epoch = options.start;
+    loop {
+        let teme = propagator.state_at(&epoch)?;
+        let state = match options.frame {
+            OemFrame::Teme => teme,
+            OemFrame::Eme2000 => orbit::teme_state_to_eme2000(&teme, &epoch),
+            OemFrame::Itrf => orbit::teme_state_to_ecef(&teme, &epoch),
+        };
+        states.push(OemState {
+            epoch,
+            position: state.position,
+            velocity: state.velocity,
+        });
+
+        if epoch >= options.stop {
+            break;
+        }
+        epoch = epoch
+            .checked_add_signed(step)
+            .map_or(options.stop, |next| next.min(options.stop));
+    }
+
+    let mut metadata = vec![
+        (
+            "OBJECT_NAME".to_string(),
+            elements.object_name.clone().unwrap_or_default(),
+        ),
+        ("OBJECT_ID".to_string(), object_id(elements)),
+        ("CENTER_NAME".to_string(), "EARTH".to_string()),
+        ("REF_FRAME".to_string(), options.frame.keyword().to_string()),
+        ("TIME_SYSTEM".to_string(), "UTC".to_string()),
+        ("START_TIME".to_string(), format_epoch(&options.start)),
+        ("STOP_TIME".to_string(), format_epoch(&options.stop)),
+    ];
+    if let Some((method, degree)) = &options.interpolation {
+        metadata.push(("INTERPOLATION".to_string(), method.to_uppercase()));
+        metadata.push(("INTERPOLATION_DEGREE".to_string(), degree.to_string()));
+    }
+
+    let mut comments = vec![format!(
+        "Propagated with SGP4 from the TLE with epoch {}",
+        format_epoch(&orbit::epoch_of(elements))
+    )];
+    if options.frame == OemFrame::Itrf {
+        comments.push("Earth-fixed frame without polar motion corrections".to_string());
+    }
+
+    Ok(Oem {
+        header: vec![
+            ("CCSDS_OEM_VERS".to_string(), OEM_VERSION.to_string()),
+            ("CREATION_DATE".to_string(), format_epoch(&Utc::now())),
+            ("ORIGINATOR".to_string(), ORIGINATOR.to_string()),
+        ],
+        segments: vec![OemSegment {
+            metadata,
+            comments,
+            states,
+        }],
+    })
+}
+
+fn format_state(state: &OemState) -> [String; 7] {
+    [
+        format_epoch(&state.epoch),
+        format!("{:.6}", state.position[0]),
+        format!("{:.6}", state.position[1]),
+        format!("{:.6}", state.position[2]),
+        format!("{:.9}", state.velocity[0]),
+        format!("{:.9}", state.velocity[1]),
+        format!("{:.9}", state.velocity[2]),
+    ]
+}
+
+fn to_kvn(oem: &Oem) -> String {
+    let mut kvn = String::new();
+    for (key, value) in &oem.header {
+        kvn.push_str(&format!("{:<20} = {}\n", key, value));
+    }
+
+    for segment in &oem.segments {
+        kvn.push_str("\nMETA_START\n");
+        for (key, value) in &segment.metadata {
+            kvn.push_str(&format!("{:<20} = {}\n", key, value));
+        }
+        kvn.push_str("META_STOP\n\n");
+
+        for comment in &segment.comments {
+            kvn.push_str(&format!("COMMENT {}\n", comment));
+        }
+        for state in &segment.states {
+            kvn.push_str(&format_state(state).join(" "));
+            kvn.push('\n');
+        }
+    }
+
+    kvn
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn to_xml(oem: &Oem) -> String {
+    let version = oem
+        .header
+        .iter()
+        .find(|(key, _)| key == "CCSDS_OEM_VERS")
+        .map_or(OEM_VERSION, |(_, value)| value.as_str());
+
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <oem id=\"CCSDS_OEM_VERS\" version=\"{}\">\n  <header>\n",
+        version
+    );
+    for (key, value) in oem.header.iter().filter(|(key, _)| key != "CCSDS_OEM_VERS") {
+        xml.push_str(&format!("    <{0}>{1}</{0}>\n", key, escape_xml(value)));
+    }
+    xml.push_str("  </header>\n  <body>\n");
+
+    for segment in &oem.segments {
+        xml.push_str("    <segment>\n      <metadata>\n");
+        for (key, value) in &segment.metadata {
+            xml.push_str(&format!("        <{0}>{1}</{0}>\n", key, escape_xml(value)));
+        }
+        xml.push_str("      </metadata>\n      <data>\n");
+        for comment in &segment.comments {
+            xml.push_str(&format!(
+                "        <COMMENT>{}</COMMENT>\n",
+                escape_xml(comment)
+            ));
+        }
+        for state in &segment.states {
+            let [epoch, x, y, z, vx, vy, vz] = format_state(state);
+            xml.push_str(&format!(
+                "        <stateVector>\n          <EPOCH>{}</EPOCH>\n          \
+                 <X>{}</X>\n          <Y>{}</Y>\n          <Z>{}</Z>\n          \
+                 <X_DOT>{}</X_DOT>\n          <Y_DOT>{}</Y_DOT>\n          <Z_DOT>{}</Z_DOT>\n        \
+                 </stateVector>\n",
+                epoch, x, y, z, vx, vy, vz
+            ));
+        }
+        xml.push_str("      </data>\n    </segment>\n");
+    }
+
+    xml.push_str("  </body>\n</oem>\n");
+    xml
+}
+
+pub fn write_oem(oem: &Oem, encoding: OemEncoding) -> String {
+    match encoding {
+        OemEncoding::Kvn => to_kvn(oem),
+        OemEncoding::Xml => to_xml(oem),
+    }
+}
+
+fn parse_vector(values: &[&str]) -> Result<Vector3, String> {
+    let mut vector = [0.0; 3];
+    for (component, value) in vector.iter_mut().zip(values) {
+        *component = value
+            .parse()
+            .map_err(|_| format!("Invalid number '{}'", value))?;
+    }
+    Ok(vector)
+}
+
+fn parse_kvn(contents: &str) -> Result<Oem, String> {
+    let mut oem = Oem {
+        header: vec![],
+        segments: vec![],
+    };
+    let mut in_metadata = false;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line == "META_START" {
+            in_metadata = true;
+            oem.segments.push(OemSegment {
+                metadata: vec![],
+                comments: vec![],
+                states: vec![],
+            });
+            continue;
+        }
+        if line == "META_STOP" {
+            in_metadata = false;
+            continue;
+        }
+
+        let segment = oem.segments.last_mut();
+        if let Some(comment) = line.strip_prefix("COMMENT") {
+            if let Some(segment) = segment {
+                segment.comments.push(comment.trim().to_string());
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let pair = (key.trim().to_string(), value.trim().to_string());
+            match segment {
+                Some(segment) if in_metadata => segment.metadata.push(pair),
+                None => oem.header.push(pair),
+                Some(_) => {
+                    return Err(format!(
+                        "Line {}: keyword {} outside of a metadata block",
+                        line_number, pair.0
+                    ))
+                }
+            }
+            continue;
+        }
+
+        // Covariance blocks and accelerations aren't used, only the first 7 columns are read
+        let values: Vec<&str> = line.split_whitespace().collect();
+        match segment {
+            Some(segment) if !in_metadata && values.len() >= 7 => {
+                let state = parse_epoch(values[0]).and_then(|epoch| {
+                    Ok(OemState {
+                        epoch,
+                        position: parse_vector(&values[1..4])?,
+                        velocity: parse_vector(&values[4..7])?,
+                    })
+                });
+                segment
+                    .states
+                    .push(state.map_err(|e| format!("Line {}: {}", line_number, e))?);
+            }
+            _ => return Err(format!("Line {}: unexpected '{}'", line_number, line)),
+        }
+    }
+
+    Ok(oem)
+}
+
+// A minimal reader for the flat structure written by `to_xml`, leaf elements become pairs
+fn parse_xml(contents: &str) -> Result<Oem, String> {
+    let tag = Regex::new(r"<(/?)([A-Za-z_]+)[^>]*?(/?)>").unwrap();
+    let version = Regex::new(r#"version="([^"]*)""#).unwrap();
+    let mut oem = Oem {
+        header: vec![],
+        segments: vec![],
+    };
+    let mut open: Option<(String, usize)> = None;
+    let mut section = "";
+    let mut state: HashMap<String, String> = HashMap::new();
+
+    for captures in tag.captures_iter(contents) {
+        let whole = captures.get(0).unwrap();
+        let closing = &captures[1] == "/";
+        let name = captures[2].to_string();
+        if &captures[3] == "/" {
+            // Empty elements carry no value
+            continue;
+        }
+
+        if !closing {
+            match name.as_str() {
+                "oem" => {
+                    if let Some(version) = version.captures(whole.as_str()) {
+                        oem.header
+                            .push(("CCSDS_OEM_VERS".to_string(), version[1].to_string()));
+                    }
+                }
+                "header" => section = "header",
+                "segment" => oem.segments.push(OemSegment {
+                    metadata: vec![],
+                    comments: vec![],
+                    states: vec![],
+                }),
+                "metadata" => section = "metadata",
+                "data" => section = "data",
+                "stateVector" => state.clear(),
+                _ => {}
+            }
+            open = Some((name, whole.end()));
+            continue;
+        }
+
+        // Only a closing tag right after its own opening tag is a value
+        let value = match open.take() {
+            Some((open_name, start)) if open_name == name => contents[start..whole.start()]
+                .trim()
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&"),
+            _ => {
+                if name == "stateVector" {
+                    let segment = oem
+                        .segments
+                        .last_mut()
+                        .ok_or("stateVector outside of a segment")?;
+                    let field = |key: &str| {
+                        state
+                            .get(key)
+                            .map(String::as_str)
+                            .ok_or(format!("stateVector without {}", key))
+                    };
+                    segment.states.push(OemState {
+                        epoch: parse_epoch(field("EPOCH")?)?,
+                        position: parse_vector(&[field("X")?, field("Y")?, field("Z")?])?,
+                        velocity: parse_vector(&[
+                            field("X_DOT")?,
+                            field("Y_DOT")?,
+                            field("Z_DOT")?,
+                        ])?,
+                    });
+                }
+                continue;
+            }
+        };
+
+        match section {
+            "header" => oem.header.push((name, value)),
+            "metadata" | "data" => {
+                let segment = oem
+                    .segments
+                    .last_mut()
+                    .ok_or(format!("{} outside of a segment", name))?;
+                if name == "COMMENT" {
+                    segment.comments.push(value);
+                } else if section == "metadata" {
+                    segment.metadata.push((name, value));
+                } else {
+                    state.insert(name, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(oem)
+}
+
+// Either encoding, told apart by the leading `<` of XML
+pub fn parse_oem(contents: &str) -> Result<Oem, String> {
+    if contents.trim_start().starts_with('<') {
+        parse_xml(contents)
+    } else {
+        parse_kvn(contents)
+    }
+}
+
+/**
+Checks a parsed OEM against the mandatory keywords and ordering rules of the standard
+
+# Arguments
+* `oem` - The parsed message
+# Returns
+* Every problem found, empty for a valid message
+*/
+pub fn validate_oem(oem: &Oem) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for keyword in ["CCSDS_OEM_VERS", "CREATION_DATE", "ORIGINATOR"] {
+        if !oem.header.iter().any(|(key, _)| key == keyword) {
+            problems.push(format!("Header is missing {}", keyword));
+        }
+    }
+    if oem.segments.is_empty() {
+        problems.push("No segments".to_string());
+    }
+
+    for (index, segment) in oem.segments.iter().enumerate() {
+        let number = index + 1;
+        for keyword in [
+            "OBJECT_NAME",
+            "OBJECT_ID",
+            "CENTER_NAME",
+            "REF_FRAME",
+            "TIME_SYSTEM",
+            "START_TIME",
+            "STOP_TIME",
+        ] {
+            if segment.get(keyword).is_none() {
+                problems.push(format!("Segment {} is missing {}", number, keyword));
+            }
+        }
+        if segment.states.is_empty() {
+            problems.push(format!("Segment {} has no states", number));
+        }
+
+        if let Some(degree) = segment.get("INTERPOLATION_DEGREE") {
+            match degree.parse::<usize>() {
+                Ok(degree) if degree >= segment.states.len() => problems.push(format!(
+                    "Segment {}: interpolation degree {} needs more than {} states",
+                    number,
+                    degree,
+                    segment.states.len()
+                )),
+                Ok(_) => {}
+                Err(_) => problems.push(format!(
+                    "Segment {}: invalid INTERPOLATION_DEGREE '{}'",
+                    number, degree
+                )),
+            }
+        }
+
+        if segment
+            .states
+            .windows(2)
+            .any(|pair| pair[1].epoch <= pair[0].epoch)
+        {
+            problems.push(format!("Segment {}: epochs are not increasing", number));
+        }
+
+        let bound = |keyword: &str| segment.get(keyword).map(parse_epoch);
+        match (bound("START_TIME"), bound("STOP_TIME")) {
+            (Some(Ok(start)), Some(Ok(stop)))
+                if segment
+                    .states
+                    .iter()
+                    .any(|state| state.epoch < start || state.epoch > stop) =>
+            {
+                problems.push(format!(
+                    "Segment {}: states outside of START_TIME / STOP_TIME",
+                    number
+                ));
+            }
+            (Some(Err(err)), _) | (_, Some(Err(err))) => {
+                problems.push(format!("Segment {}: {}", number, err))
+            }
+            _ => {}
+        }
+    }
+
+    problems
+}
+
+/**
+Writes the OEM and reads it back, to make sure the output survives a round trip
+
+# Returns
+* The encoded message, or what was lost on the way
+*/
+pub fn write_verified_oem(oem: &Oem, encoding: OemEncoding) -> Result<String, String> {
+    let encoded = write_oem(oem, encoding);
+    let parsed = parse_oem(&encoded)?;
+
+    let problems = validate_oem(&parsed);
+    if !problems.is_empty() {
+        return Err(problems.join("\n"));
+    }
+
+    let tolerance_km = 1e-5; // Positions are written with six decimals
+    for (written, read) in oem.segments.iter().zip(&parsed.segments) {
+        let matches = written.states.len() == read.states.len()
+            && written.states.iter().zip(&read.states).all(|(a, b)| {
+                (a.epoch - b.epoch).num_milliseconds().abs() <= 1
+                    && orbit::norm(&orbit::sub(&a.position, &b.position)) < tolerance_km
+            });
+        if !matches {
+            return Err("The written OEM doesn't read back to the same states".to_string());
+        }
+    }
+
+    Ok(encoded)
+}
+
+// First and last state and the frame of every segment, for a quick look at a file
+pub fn summarize_oem(oem: &Oem) -> Vec<String> {
+    oem.segments
+        .iter()
+        .map(|segment| {
+            let span = match (segment.states.first(), segment.states.last()) {
+                (Some(first), Some(last)) => format!(
+                    "{} to {}",
+                    format_epoch(&first.epoch),
+                    format_epoch(&last.epoch)
+                ),
+                _ => "no states".to_string(),
+            };
+            format!(
+                "{} ({}), {} frame, {} states, {}",
+                segment.get("OBJECT_NAME").unwrap_or("?"),
+                segment.get("OBJECT_ID").unwrap_or("?"),
+                segment.get("REF_FRAME").unwrap_or("?"),
+                segment.states.len(),
+                span
+            )
+        })
+        .collect()
+}
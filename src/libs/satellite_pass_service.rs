@@ -0,0 +1,289 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::orbit::{self, Geodetic, LookAngles, Propagator};
+use super::satellite_search_service::Satellite;
+
+const COARSE_STEP_SECONDS: i64 = 30;
+const REFINE_TOLERANCE_MS: i64 = 500;
+const GOLDEN_RATIO: f64 = 0.618_033_988_749_895;
+const NEXT_PASS_SEARCH_DAYS: i64 = 7;
+
+// A named observer location, e.g. when predicting for several ground stations
+#[derive(Debug, Clone)]
+pub struct Station {
+    pub name: String,
+    pub location: Geodetic,
+}
+
+// One pass of a satellite over an observer, from rise (AOS) to set (LOS)
+#[derive(Debug, Clone)]
+pub struct Pass {
+    pub aos: DateTime<Utc>,
+    pub tca: DateTime<Utc>, // Time of closest approach, i.e. maximum elevation
+    pub los: DateTime<Utc>,
+    pub aos_azimuth: f64,
+    pub tca_azimuth: f64,
+    pub los_azimuth: f64,
+    pub max_elevation: f64,
+    // The satellite was already up at the start of the search, so `aos` is that start
+    pub in_progress: bool,
+    // The revolution at TCA, counted from the far side of the orbit (see `pass_revolution`), which
+    // names the pass across predictions and TLE updates
+    pub revolution: u64,
+}
+
+// A pass together with the satellite and station it belongs to
+#[derive(Debug, Clone)]
+pub struct StationPass {
+    pub satellite: Satellite,
+    pub station: Station,
+    pub pass: Pass,
+}
+
+pub fn look_at(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    time: &DateTime<Utc>,
+) -> Result<LookAngles, String> {
+    let state = propagator.state_at(time)?;
+    Ok(orbit::look_angles(observer, &state, time))
+}
+
+// Narrows a horizon crossing between `before` and `after` down to the refine tolerance
+fn refine_crossing(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    min_elevation: f64,
+    mut before: DateTime<Utc>,
+    mut after: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let rising = look_at(propagator, observer, &before)?.elevation < min_elevation;
+
+    while (after - before).num_milliseconds() > REFINE_TOLERANCE_MS {
+        let middle = before + (after - before) / 2;
+        let above = look_at(propagator, observer, &middle)?.elevation >= min_elevation;
+        if above == rising {
+            after = middle;
+        } else {
+            before = middle;
+        }
+    }
+
+    Ok(after)
+}
+
+// Golden-section search for the highest elevation between AOS and LOS
+fn find_culmination(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    aos: DateTime<Utc>,
+    los: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    let elevation = |time: &DateTime<Utc>| -> Result<f64, String> {
+        Ok(look_at(propagator, observer, time)?.elevation)
+    };
+    let at = |fraction: f64| {
+        aos + Duration::milliseconds(((los - aos).num_milliseconds() as f64 * fraction) as i64)
+    };
+
+    let (mut low, mut high) = (0.0, 1.0);
+    while ((los - aos).num_milliseconds() as f64 * (high - low)) as i64 > REFINE_TOLERANCE_MS {
+        let left = high - GOLDEN_RATIO * (high - low);
+        let right = low + GOLDEN_RATIO * (high - low);
+        if elevation(&at(left))? < elevation(&at(right))? {
+            low = left;
+        } else {
+            high = right;
+        }
+    }
+
+    Ok(at((low + high) / 2.0))
+}
+
+/*
+A station only sees the satellite near its own latitude, so the passes culminate around the
+same two points of the orbit (ascending and descending). Counting revolutions from the extreme
+of the orbit in the other hemisphere keeps the boundary half an orbit from the passes, where a
+TLE update moving TCA by minutes can't carry it across.
+*/
+fn pass_revolution(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    tca: &DateTime<Utc>,
+) -> Result<u64, String> {
+    let boundary = if observer.latitude >= 0.0 {
+        270.0
+    } else {
+        90.0
+    };
+    orbit::revolution_at(propagator, tca, boundary)
+}
+
+/**
+Predicts the passes of a satellite over an observer
+
+# Arguments
+* `propagator` - The satellite to predict
+* `observer` - The observer's location
+* `start` - The start of the search window
+* `end` - The end of the search window, a pass still in progress at the end is left out
+* `min_elevation` - The elevation in degrees a pass has to rise above
+# Returns
+* The passes in chronological order, or the propagation error
+*/
+pub fn predict_passes(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    min_elevation: f64,
+) -> Result<Vec<Pass>, String> {
+    let step = Duration::seconds(COARSE_STEP_SECONDS);
+    let mut passes = Vec::new();
+
+    let mut previous_time = *start;
+    let mut previous_up = look_at(propagator, observer, start)?.elevation >= min_elevation;
+    let mut aos = previous_up.then_some(*start);
+
+    let mut time = *start;
+    while time < *end {
+        time = (time + step).min(*end);
+        let up = look_at(propagator, observer, &time)?.elevation >= min_elevation;
+
+        if up && !previous_up {
+            aos = Some(refine_crossing(
+                propagator,
+                observer,
+                min_elevation,
+                previous_time,
+                time,
+            )?);
+        } else if !up && previous_up {
+            if let Some(rise) = aos.take() {
+                let los =
+                    refine_crossing(propagator, observer, min_elevation, previous_time, time)?;
+                let tca = find_culmination(propagator, observer, rise, los)?;
+                let culmination = look_at(propagator, observer, &tca)?;
+
+                passes.push(Pass {
+                    aos: rise,
+                    tca,
+                    los,
+                    aos_azimuth: look_at(propagator, observer, &rise)?.azimuth,
+                    tca_azimuth: culmination.azimuth,
+                    los_azimuth: look_at(propagator, observer, &los)?.azimuth,
+                    max_elevation: culmination.elevation,
+                    in_progress: rise == *start,
+                    revolution: pass_revolution(propagator, observer, &tca)?,
+                });
+            }
+        }
+
+        previous_up = up;
+        previous_time = time;
+    }
+
+    Ok(passes)
+}
+
+/**
+Predicts the passes of several satellites over several stations
+
+# Arguments
+* `targets` - The satellites to predict
+* `stations` - The observer locations
+* `start` - The start of the search window
+* `end` - The end of the search window
+* `min_elevation` - The elevation in degrees a pass has to rise above
+# Returns
+* The passes ordered by AOS, and an error message for each satellite that failed
+*/
+pub fn predict_station_passes(
+    targets: &[(Satellite, Propagator)],
+    stations: &[Station],
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    min_elevation: f64,
+) -> (Vec<StationPass>, Vec<String>) {
+    let mut passes = Vec::new();
+    let mut errors = Vec::new();
+
+    for (satellite, propagator) in targets {
+        for station in stations {
+            match predict_passes(propagator, &station.location, start, end, min_elevation) {
+                Ok(predicted) => passes.extend(predicted.into_iter().map(|pass| StationPass {
+                    satellite: satellite.clone(),
+                    station: station.clone(),
+                    pass,
+                })),
+                Err(err) => errors.push(format!(
+                    "Error predicting passes of {} over {}: {}",
+                    satellite.name, station.name, err
+                )),
+            }
+        }
+    }
+
+    passes.sort_by_key(|entry| entry.pass.aos);
+    (passes, errors)
+}
+
+// Azimuth / elevation every `step` through a pass, used to plan rotator moves and plots
+pub fn pass_path(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    pass: &Pass,
+    step: Duration,
+) -> Result<Vec<(DateTime<Utc>, LookAngles)>, String> {
+    let mut path = Vec::new();
+    let mut time = pass.aos;
+    while time < pass.los {
+        path.push((time, look_at(propagator, observer, &time)?));
+        time += step;
+    }
+    path.push((pass.los, look_at(propagator, observer, &pass.los)?));
+
+    Ok(path)
+}
+
+// The first pass after `from` (or the one in progress), looking up to a week ahead
+pub fn next_pass(
+    propagator: &Propagator,
+    observer: &Geodetic,
+    from: &DateTime<Utc>,
+    min_elevation: f64,
+) -> Result<Pass, String> {
+    predict_passes(
+        propagator,
+        observer,
+        from,
+        &(*from + Duration::days(NEXT_PASS_SEARCH_DAYS)),
+        min_elevation,
+    )?
+    .into_iter()
+    .next()
+    .ok_or(format!(
+        "No pass in the next {} days",
+        NEXT_PASS_SEARCH_DAYS
+    ))
+}
+
+// Sleeps until `time` in short naps, returning false when `running` was cleared meanwhile
+pub fn wait_until(time: &DateTime<Utc>, running: &AtomicBool) -> bool {
+    while running.load(Ordering::SeqCst) {
+        let remaining = *time - Utc::now();
+        if remaining <= Duration::zero() {
+            return true;
+        }
+        thread::sleep(
+            remaining
+                .min(Duration::seconds(1))
+                .to_std()
+                .unwrap_or_default(),
+        );
+    }
+    false
+}
@@ -0,0 +1,726 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+use super::orbit::{self, DerivedParameters};
+use super::satellite_catalog_service::CatalogEntry;
+use super::satellite_filter_service::designator_year;
+use super::satellite_satcat_service;
+
+/*
+Small query language over catalog entries, e.g.
+
+    select name, period where inclination > 97 and perigee_alt < 600 order by period desc limit 10
+
+Every clause is optional and `where` may be left out in front of a bare expression.
+
+    expression := and ("or" and)*
+    and        := not ("and" not)*
+    not        := "not" not | comparison
+    comparison := operand (("<" | "<=" | ">" | ">=" | "=" | "!=" | "~") operand)?
+    operand    := number | 'text' | "text" | field | "(" expression ")" | "-" operand
+*/
+
+pub const FIELDS: [&str; 28] = [
+    "norad_id",
+    "name",
+    "intl_designator",
+    "launch_year",
+    "regime",
+    "inclination",
+    "raan",
+    "eccentricity",
+    "arg_perigee",
+    "mean_anomaly",
+    "mean_motion",
+    "bstar",
+    "revolution",
+    "semi_major_axis",
+    "perigee_alt",
+    "apogee_alt",
+    "period",
+    "perigee_velocity",
+    "apogee_velocity",
+    "epoch",
+    "epoch_age_days",
+    "object_type",
+    "owner",
+    "launch_date",
+    "launch_site",
+    "ops_status",
+    "rcs_size",
+    "decay_date",
+];
+
+const DEFAULT_FIELDS: [&str; 7] = [
+    "norad_id",
+    "name",
+    "regime",
+    "inclination",
+    "perigee_alt",
+    "apogee_alt",
+    "period",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Null,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // Integers (IDs, years, revolution numbers) shouldn't grow a fraction
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Value::Number(n) if n.abs() < 1e-3 && *n != 0.0 => write!(f, "{:.4e}", n),
+            Value::Number(n) => write!(f, "{:.4}", n),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Null => write!(f, "-"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Matches,
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    Literal(Value),
+    Field(String),
+    Negate(Box<Expression>),
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Compare(Box<Expression>, Operator, Box<Expression>),
+    Matches(Box<Expression>, Regex), // Compiled once when parsing, not for every row
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub fields: Vec<String>,
+    filter: Option<Expression>,
+    order_by: Option<(String, bool)>, // (field, descending)
+    limit: Option<usize>,
+}
+
+pub struct QueryResult {
+    pub fields: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub entries: Vec<usize>, // Index into the catalog of every row
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Word(String),
+    Operator(Operator),
+    Minus,
+    Comma,
+    OpenParen,
+    CloseParen,
+}
+
+// Tokens carry the (1-based) column they start at for error messages
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let (token, length) = match c {
+            '(' => (Token::OpenParen, 1),
+            ')' => (Token::CloseParen, 1),
+            ',' => (Token::Comma, 1),
+            '-' => (Token::Minus, 1),
+            '~' => (Token::Operator(Operator::Matches), 1),
+            '<' | '>' | '=' | '!' => {
+                let next = chars.get(i + 1).copied();
+                match (c, next) {
+                    ('<', Some('=')) => (Token::Operator(Operator::LessEqual), 2),
+                    ('>', Some('=')) => (Token::Operator(Operator::GreaterEqual), 2),
+                    ('=', Some('=')) => (Token::Operator(Operator::Equal), 2),
+                    ('!', Some('=')) => (Token::Operator(Operator::NotEqual), 2),
+                    ('<', Some('>')) => (Token::Operator(Operator::NotEqual), 2),
+                    ('<', _) => (Token::Operator(Operator::Less), 1),
+                    ('>', _) => (Token::Operator(Operator::Greater), 1),
+                    ('=', _) => (Token::Operator(Operator::Equal), 1),
+                    _ => return Err(format!("Unexpected '!' at column {}", column)),
+                }
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| format!("Unterminated text starting at column {}", column))?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                (Token::Text(text), end + 2)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = i;
+                while end < chars.len()
+                    && (chars[end].is_ascii_digit()
+                        || chars[end] == '.'
+                        || chars[end] == 'e'
+                        || chars[end] == 'E'
+                        || ((chars[end] == '-' || chars[end] == '+')
+                            && matches!(chars[end - 1], 'e' | 'E')))
+                {
+                    end += 1;
+                }
+                let literal: String = chars[i..end].iter().collect();
+                let number = literal
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}' at column {}", literal, column))?;
+                (Token::Number(number), end - i)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = i;
+                while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                (Token::Word(chars[i..end].iter().collect()), end - i)
+            }
+            other => return Err(format!("Unexpected '{}' at column {}", other, column)),
+        };
+
+        tokens.push((token, column));
+        i += length;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    end_column: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map(|(_, column)| *column)
+            .unwrap_or(self.end_column)
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, String> {
+        match self.tokens.get(self.position) {
+            Some((token, column)) => Err(format!(
+                "Expected {} at column {}, found {}",
+                expected,
+                column,
+                describe(token)
+            )),
+            None => Err(format!("Expected {} at end of query", expected)),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            self.error(&format!("'{}'", keyword))
+        }
+    }
+
+    fn field(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let field = word.to_lowercase();
+                if !FIELDS.contains(&field.as_str()) {
+                    return Err(format!(
+                        "Unknown field '{}' at column {} (available: {})",
+                        word,
+                        self.column(),
+                        FIELDS.join(", ")
+                    ));
+                }
+                self.position += 1;
+                Ok(field)
+            }
+            _ => self.error("a field name"),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, String> {
+        let mut fields = Vec::new();
+        if self.eat_keyword("select") {
+            loop {
+                fields.push(self.field()?);
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.position += 1;
+            }
+        }
+
+        let has_where = self.eat_keyword("where");
+        let filter = if has_where || !(self.is_keyword("order") || self.is_keyword("limit")) {
+            if self.peek().is_none() && !has_where {
+                None
+            } else {
+                Some(self.or()?)
+            }
+        } else {
+            None
+        };
+
+        let mut order_by = None;
+        if self.eat_keyword("order") {
+            self.expect_keyword("by")?;
+            let field = self.field()?;
+            let descending = if self.eat_keyword("desc") {
+                true
+            } else {
+                self.eat_keyword("asc");
+                false
+            };
+            order_by = Some((field, descending));
+        }
+
+        let mut limit = None;
+        if self.eat_keyword("limit") {
+            match self.peek() {
+                Some(Token::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => {
+                    limit = Some(*n as usize);
+                    self.position += 1;
+                }
+                _ => return self.error("a row count"),
+            }
+        }
+
+        if self.peek().is_some() {
+            return self.error("end of query");
+        }
+
+        if fields.is_empty() {
+            fields = DEFAULT_FIELDS.iter().map(|f| f.to_string()).collect();
+        }
+
+        Ok(Query {
+            fields,
+            filter,
+            order_by,
+            limit,
+        })
+    }
+
+    fn or(&mut self) -> Result<Expression, String> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            left = Expression::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expression, String> {
+        let mut left = self.not()?;
+        while self.eat_keyword("and") {
+            left = Expression::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expression, String> {
+        if self.eat_keyword("not") {
+            return Ok(Expression::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        let left = self.operand()?;
+        if let Some(Token::Operator(operator)) = self.peek().cloned() {
+            self.position += 1;
+            if operator == Operator::Matches {
+                return self.pattern(left);
+            }
+            let right = self.operand()?;
+            return Ok(Expression::Compare(
+                Box::new(left),
+                operator,
+                Box::new(right),
+            ));
+        }
+        Ok(left)
+    }
+
+    // The right side of `~` has to be quoted text, compiled as a case-insensitive regex
+    fn pattern(&mut self, left: Expression) -> Result<Expression, String> {
+        let Some(Token::Text(pattern)) = self.peek().cloned() else {
+            return self.error("a quoted pattern after '~'");
+        };
+        let regex = Regex::new(&format!("(?i){}", pattern)).map_err(|e| {
+            format!(
+                "Invalid regex '{}' at column {}: {}",
+                pattern,
+                self.column(),
+                e
+            )
+        })?;
+        self.position += 1;
+        Ok(Expression::Matches(Box::new(left), regex))
+    }
+
+    fn operand(&mut self) -> Result<Expression, String> {
+        match self.peek().cloned() {
+            Some(Token::Number(n)) => {
+                self.position += 1;
+                Ok(Expression::Literal(Value::Number(n)))
+            }
+            Some(Token::Text(text)) => {
+                self.position += 1;
+                Ok(Expression::Literal(Value::Text(text)))
+            }
+            Some(Token::Minus) => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.operand()?)))
+            }
+            Some(Token::OpenParen) => {
+                self.position += 1;
+                let expression = self.or()?;
+                if self.peek() != Some(&Token::CloseParen) {
+                    return self.error("')'");
+                }
+                self.position += 1;
+                Ok(expression)
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("true") => {
+                self.position += 1;
+                Ok(Expression::Literal(Value::Bool(true)))
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("false") => {
+                self.position += 1;
+                Ok(Expression::Literal(Value::Bool(false)))
+            }
+            Some(Token::Word(_)) => Ok(Expression::Field(self.field()?)),
+            _ => self.error("a value or field name"),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("number {}", n),
+        Token::Text(text) => format!("text '{}'", text),
+        Token::Word(word) => format!("'{}'", word),
+        Token::Operator(_) => "an operator".to_string(),
+        Token::Minus => "'-'".to_string(),
+        Token::Comma => "','".to_string(),
+        Token::OpenParen => "'('".to_string(),
+        Token::CloseParen => "')'".to_string(),
+    }
+}
+
+/**
+Parses a catalog query
+
+# Arguments
+* `text` - The query, e.g. `inclination > 97 and perigee_alt < 600 order by period desc`
+# Returns
+* The parsed query, or a message pointing at the column where parsing failed
+*/
+pub fn parse_query(text: &str) -> Result<Query, String> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        position: 0,
+        end_column: text.chars().count() + 1,
+    };
+    parser.query()
+}
+
+pub fn field_value(entry: &CatalogEntry, derived: &DerivedParameters, field: &str) -> Value {
+    let elements = &entry.elements;
+    match field {
+        "norad_id" => Value::Number(entry.satellite.id as f64),
+        "name" => Value::Text(entry.satellite.name.clone()),
+        "intl_designator" => elements
+            .international_designator
+            .clone()
+            .map_or(Value::Null, Value::Text),
+        "launch_year" => elements
+            .international_designator
+            .as_deref()
+            .and_then(designator_year)
+            .map_or(Value::Null, |year| Value::Number(year as f64)),
+        "regime" => Value::Text(derived.regime.label().to_string()),
+        "inclination" => Value::Number(elements.inclination),
+        "raan" => Value::Number(elements.right_ascension),
+        "eccentricity" => Value::Number(elements.eccentricity),
+        "arg_perigee" => Value::Number(elements.argument_of_perigee),
+        "mean_anomaly" => Value::Number(elements.mean_anomaly),
+        "mean_motion" => Value::Number(elements.mean_motion),
+        "bstar" => Value::Number(elements.drag_term),
+        "revolution" => Value::Number(elements.revolution_number as f64),
+        "semi_major_axis" => Value::Number(derived.semi_major_axis_km),
+        "perigee_alt" => Value::Number(derived.perigee_altitude_km),
+        "apogee_alt" => Value::Number(derived.apogee_altitude_km),
+        "period" => Value::Number(derived.period_minutes),
+        "perigee_velocity" => Value::Number(derived.perigee_velocity_km_s),
+        "apogee_velocity" => Value::Number(derived.apogee_velocity_km_s),
+        "epoch" => Value::Text(
+            orbit::epoch_of(elements)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        ),
+        "epoch_age_days" => Value::Number(derived.epoch_age_days),
+        "object_type" | "owner" | "launch_date" | "launch_site" | "ops_status" | "rcs_size"
+        | "decay_date" => satcat_value(entry.satellite.id, field),
+        _ => Value::Null,
+    }
+}
+
+// Dates are ISO formatted text, so they compare correctly against '2020-01-01'
+fn satcat_value(norad_id: i32, field: &str) -> Value {
+    let Some(record) = satellite_satcat_service::get_record(norad_id) else {
+        return Value::Null;
+    };
+    let text = match field {
+        "object_type" => Some(record.object_type.label().to_string()),
+        "owner" => record.owner.clone(),
+        "launch_date" => record.launch_date.map(|date| date.to_string()),
+        "launch_site" => record.launch_site.clone(),
+        "ops_status" => Some(record.ops_status.label().to_string()),
+        "rcs_size" => record.rcs_size.map(|size| size.label().to_string()),
+        "decay_date" => record.decay_date.map(|date| date.to_string()),
+        _ => None,
+    };
+
+    text.map_or(Value::Null, Value::Text)
+}
+
+fn compare_values(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+        // Text comparisons are case-insensitive, regimes and names are upper case in the data
+        (Value::Text(a), Value::Text(b)) => Some(a.to_lowercase().cmp(&b.to_lowercase())),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+// Total order for `order by`: values of one type by compare_values, otherwise booleans, numbers
+// and text in that order, with Null (missing SATCAT data, no designator) after everything
+fn sort_order(left: &Value, right: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Bool(_) => 0,
+        Value::Number(_) => 1,
+        Value::Text(_) => 2,
+        Value::Null => 3,
+    };
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+        _ => compare_values(left, right).unwrap_or_else(|| rank(left).cmp(&rank(right))),
+    }
+}
+
+fn evaluate(
+    expression: &Expression,
+    entry: &CatalogEntry,
+    derived: &DerivedParameters,
+) -> Result<Value, String> {
+    let truthy = |value: Value| match value {
+        Value::Bool(b) => Ok(b),
+        Value::Null => Ok(false),
+        other => Err(format!("Expected a condition, found value {}", other)),
+    };
+
+    match expression {
+        Expression::Literal(value) => Ok(value.clone()),
+        Expression::Field(field) => Ok(field_value(entry, derived, field)),
+        Expression::Negate(inner) => match evaluate(inner, entry, derived)? {
+            Value::Number(n) => Ok(Value::Number(-n)),
+            other => Err(format!("Cannot negate {}", other)),
+        },
+        Expression::Not(inner) => Ok(Value::Bool(!truthy(evaluate(inner, entry, derived)?)?)),
+        Expression::And(left, right) => Ok(Value::Bool(
+            truthy(evaluate(left, entry, derived)?)? && truthy(evaluate(right, entry, derived)?)?,
+        )),
+        Expression::Or(left, right) => Ok(Value::Bool(
+            truthy(evaluate(left, entry, derived)?)? || truthy(evaluate(right, entry, derived)?)?,
+        )),
+        Expression::Compare(left, operator, right) => {
+            let left = evaluate(left, entry, derived)?;
+            let right = evaluate(right, entry, derived)?;
+
+            if left == Value::Null || right == Value::Null {
+                return Ok(Value::Bool(false));
+            }
+
+            let ordering = compare_values(&left, &right)
+                .ok_or_else(|| format!("Cannot compare {} with {}", left, right))?;
+            Ok(Value::Bool(match operator {
+                Operator::Less => ordering == Ordering::Less,
+                Operator::LessEqual => ordering != Ordering::Greater,
+                Operator::Greater => ordering == Ordering::Greater,
+                Operator::GreaterEqual => ordering != Ordering::Less,
+                Operator::Equal => ordering == Ordering::Equal,
+                Operator::NotEqual => ordering != Ordering::Equal,
+                Operator::Matches => unreachable!(), // Parsed into Expression::Matches
+            }))
+        }
+        Expression::Matches(inner, regex) => match evaluate(inner, entry, derived)? {
+            Value::Text(text) => Ok(Value::Bool(regex.is_match(&text))),
+            Value::Null => Ok(Value::Bool(false)),
+            other => Err(format!("'~' needs text on the left, found {}", other)),
+        },
+    }
+}
+
+pub fn run_query(
+    catalog: &[CatalogEntry],
+    query: &Query,
+    now: &DateTime<Utc>,
+) -> Result<QueryResult, String> {
+    let mut matches = Vec::new();
+    for (index, entry) in catalog.iter().enumerate() {
+        let derived = orbit::derive_parameters(&entry.elements, now);
+        let keep = match &query.filter {
+            Some(filter) => match evaluate(filter, entry, &derived)? {
+                Value::Bool(keep) => keep,
+                other => return Err(format!("The filter must be a condition, found {}", other)),
+            },
+            None => true,
+        };
+        if keep {
+            matches.push((index, derived));
+        }
+    }
+
+    if let Some((field, descending)) = &query.order_by {
+        matches.sort_by(|(a, derived_a), (b, derived_b)| {
+            let ordering = sort_order(
+                &field_value(&catalog[*a], derived_a, field),
+                &field_value(&catalog[*b], derived_b, field),
+            );
+            if *descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    if let Some(limit) = query.limit {
+        matches.truncate(limit);
+    }
+
+    Ok(QueryResult {
+        fields: query.fields.clone(),
+        rows: matches
+            .iter()
+            .map(|(index, derived)| {
+                query
+                    .fields
+                    .iter()
+                    .map(|field| field_value(&catalog[*index], derived, field))
+                    .collect()
+            })
+            .collect(),
+        entries: matches.iter().map(|(index, _)| *index).collect(),
+    })
+}
+
+pub fn query_result_to_csv(result: &QueryResult) -> String {
+    let escape = |value: &Value| {
+        let text = value.to_string();
+        if text.contains(',') || text.contains('"') {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text
+        }
+    };
+
+    let mut csv = result.fields.join(",");
+    csv.push('\n');
+    for row in &result.rows {
+        csv.push_str(&row.iter().map(escape).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::libs::test_support;
+
+    fn launch_years(catalog: &[CatalogEntry], text: &str) -> Vec<Option<f64>> {
+        let result = run_query(catalog, &parse_query(text).unwrap(), &Utc::now()).unwrap();
+        result
+            .rows
+            .iter()
+            .map(|row| match row[0] {
+                Value::Number(year) => Some(year),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn orders_by_a_field_with_nulls() {
+        // Enough rows for the sort to merge runs, with objects lacking a designator in between
+        let designators = ["98067A", "", "20001B", "57001A", "", "10003C", "98067B", ""];
+        let catalog: Vec<CatalogEntry> = designators
+            .iter()
+            .cycle()
+            .take(64)
+            .enumerate()
+            .map(|(index, designator)| test_support::catalog_entry(index as i32 + 1, designator))
+            .collect();
+
+        let ascending = launch_years(&catalog, "select launch_year order by launch_year");
+        let known = ascending.iter().take_while(|year| year.is_some()).count();
+        assert_eq!(known, 40);
+        assert!(ascending[known..].iter().all(Option::is_none));
+        assert!(ascending[..known].windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let descending = launch_years(&catalog, "select launch_year order by launch_year desc");
+        let mut reversed = ascending.clone();
+        reversed.reverse();
+        assert_eq!(descending, reversed);
+    }
+}
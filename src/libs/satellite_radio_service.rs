@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+use chrono::{Duration, Utc};
+
+use super::orbit::{Geodetic, Propagator};
+use super::satellite_hamlib_service::{self, HamlibConnection};
+use super::satellite_pass_service::{self, wait_until, Pass};
+
+pub const DEFAULT_RIGCTLD_ADDRESS: &str = "localhost:4532";
+const SPEED_OF_LIGHT_KM_S: f64 = 299_792.458;
+
+// The nominal frequencies at the satellite; for a linear transponder the pair that maps onto each other
+#[derive(Debug, Clone, Copy)]
+pub struct Transponder {
+    pub downlink_hz: f64,
+    pub uplink_hz: Option<f64>,
+    // The passband is mirrored, so moving up on the downlink means moving down on the uplink
+    pub inverting: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    pub downlink_hz: f64,
+    pub uplink_hz: Option<f64>,
+}
+
+/**
+Computes the frequencies to tune for a given range rate
+
+# Arguments
+* `transponder` - The nominal frequencies at the satellite
+* `range_rate_km_s` - The range rate, positive when the satellite moves away
+* `offset_hz` - Manual offset within the passband, applied to the downlink and, mirrored
+  for inverting transponders, to the uplink
+# Returns
+* The downlink to receive on and the uplink to transmit on
+*/
+pub fn doppler_tuning(transponder: &Transponder, range_rate_km_s: f64, offset_hz: f64) -> Tuning {
+    let factor = 1.0 - range_rate_km_s / SPEED_OF_LIGHT_KM_S;
+    let uplink_offset = if transponder.inverting {
+        -offset_hz
+    } else {
+        offset_hz
+    };
+
+    Tuning {
+        downlink_hz: (transponder.downlink_hz + offset_hz) * factor,
+        // Transmit so the signal arrives at the satellite on the nominal uplink
+        uplink_hz: transponder
+            .uplink_hz
+            .map(|uplink| (uplink + uplink_offset) / factor),
+    }
+}
+
+pub struct Rig {
+    connection: HamlibConnection,
+    current_vfo: Option<String>,
+}
+
+impl Rig {
+    pub fn connect(address: &str) -> Result<Rig, String> {
+        Ok(Rig {
+            connection: HamlibConnection::connect(address)?,
+            current_vfo: None,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        self.connection.address()
+    }
+
+    // Selects a VFO (e.g. VFOA, Main, Sub), skipped when it is already the current one
+    pub fn set_vfo(&mut self, vfo: &str) -> Result<(), String> {
+        if self.current_vfo.as_deref() == Some(vfo) {
+            return Ok(());
+        }
+        self.connection.command(&format!("V {}", vfo), 0)?;
+        self.current_vfo = Some(vfo.to_string());
+        Ok(())
+    }
+
+    pub fn set_frequency(&mut self, hz: f64) -> Result<(), String> {
+        self.connection
+            .command(&format!("F {:.0}", hz), 0)
+            .map(|_| ())
+    }
+
+    pub fn get_frequency(&mut self) -> Result<f64, String> {
+        let values = self.connection.command("f", 1)?;
+        values[0].parse::<f64>().map_err(|_| {
+            format!(
+                "Unexpected frequency from {}: {}",
+                self.address(),
+                values[0]
+            )
+        })
+    }
+
+    pub fn close(self) {
+        self.connection.close();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RadioOptions {
+    pub transponder: Transponder,
+    pub downlink_vfo: Option<String>, // None tunes whatever VFO is current
+    pub uplink_vfo: Option<String>,
+    pub horizon: f64,
+    pub interval: Duration,
+    pub tolerance_hz: f64, // Smallest change worth retuning for
+    pub passes: usize,
+}
+
+pub enum RadioEvent {
+    NextPass(Pass),
+    Tuned(f64, Tuning), // Range rate and the new frequencies
+    PassEnded(Pass),
+}
+
+fn tune(rig: &mut Rig, vfo: &Option<String>, hz: f64) -> Result<(), String> {
+    if let Some(vfo) = vfo {
+        rig.set_vfo(vfo)?;
+    }
+    rig.set_frequency(hz)
+}
+
+// Whether a link drifted past the tolerance from what it was last tuned to, noting the new frequency
+fn needs_retune(sent_hz: &mut Option<f64>, hz: f64, tolerance_hz: f64) -> bool {
+    if sent_hz.is_some_and(|sent| (hz - sent).abs() < tolerance_hz) {
+        return false;
+    }
+    *sent_hz = Some(hz);
+    true
+}
+
+/**
+Tunes a rig for the Doppler shift through the next passes of a satellite
+
+# Arguments
+* `rig` - The connected rig
+* `propagator` - The satellite to track
+* `observer` - The ground station location
+* `options` - Frequencies, VFOs and timing
+* `offset_hz` - The manual offset in Hz, may be changed by the caller while tuning
+* `running` - Cleared by the caller to stop tuning
+* `on_event` - Called as tuning progresses, for display
+*/
+pub fn tune_passes<F>(
+    rig: &mut Rig,
+    propagator: &Propagator,
+    observer: &Geodetic,
+    options: &RadioOptions,
+    offset_hz: &AtomicI64,
+    running: &AtomicBool,
+    mut on_event: F,
+) -> Result<(), String>
+where
+    F: FnMut(RadioEvent),
+{
+    let mut search_from = Utc::now();
+
+    for _ in 0..options.passes {
+        let pass =
+            satellite_pass_service::next_pass(propagator, observer, &search_from, options.horizon)?;
+        on_event(RadioEvent::NextPass(pass.clone()));
+
+        if !wait_until(&pass.aos, running) {
+            break;
+        }
+
+        // Kept per link, the uplink and downlink shifts grow at different rates
+        let mut sent_downlink: Option<f64> = None;
+        let mut sent_uplink: Option<f64> = None;
+        while running.load(Ordering::SeqCst) && Utc::now() < pass.los {
+            let look = satellite_pass_service::look_at(propagator, observer, &Utc::now())?;
+            let tuning = doppler_tuning(
+                &options.transponder,
+                look.range_rate_km_s,
+                offset_hz.load(Ordering::SeqCst) as f64,
+            );
+
+            let downlink_changed =
+                needs_retune(&mut sent_downlink, tuning.downlink_hz, options.tolerance_hz);
+            if downlink_changed {
+                tune(rig, &options.downlink_vfo, tuning.downlink_hz)?;
+            }
+            let uplink_changed = match tuning.uplink_hz {
+                Some(uplink) if needs_retune(&mut sent_uplink, uplink, options.tolerance_hz) => {
+                    tune(rig, &options.uplink_vfo, uplink)?;
+                    true
+                }
+                _ => false,
+            };
+            if downlink_changed || uplink_changed {
+                on_event(RadioEvent::Tuned(look.range_rate_km_s, tuning));
+            }
+
+            wait_until(&(Utc::now() + options.interval), running);
+        }
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+
+        on_event(RadioEvent::PassEnded(pass.clone()));
+        search_from = pass.los + Duration::seconds(1);
+    }
+
+    Ok(())
+}
+
+/**
+Runs a stand-in rigctld on localhost that keeps a frequency per VFO and logs every command
+
+# Arguments
+* `listener` - Where to accept clients, rigctld uses 4532
+* `log` - Called with each command received
+*/
+pub fn serve_fake_rigctld<L: Fn(&str)>(listener: TcpListener, log: L) -> Result<(), String> {
+    let mut vfo = "VFOA".to_string();
+    let mut frequencies: HashMap<String, f64> = HashMap::new();
+    let mut mode = ("FM".to_string(), 15000);
+
+    satellite_hamlib_service::serve_fake_daemon_on(
+        listener,
+        |line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let reply = match parts.as_slice() {
+                ["F" | "\\set_freq", hz] => match hz.parse::<f64>() {
+                    Ok(hz) => {
+                        frequencies.insert(vfo.clone(), hz);
+                        satellite_hamlib_service::report(0)
+                    }
+                    Err(_) => satellite_hamlib_service::report(-1),
+                },
+                ["f" | "\\get_freq"] => {
+                    format!("{:.0}\n", frequencies.get(&vfo).copied().unwrap_or(0.0))
+                }
+                ["V" | "\\set_vfo", name] => {
+                    vfo = name.to_string();
+                    satellite_hamlib_service::report(0)
+                }
+                ["v" | "\\get_vfo"] => format!("{}\n", vfo),
+                ["M" | "\\set_mode", name, passband] => match passband.parse::<i32>() {
+                    Ok(passband) => {
+                        mode = (name.to_string(), passband);
+                        satellite_hamlib_service::report(0)
+                    }
+                    Err(_) => satellite_hamlib_service::report(-1),
+                },
+                ["m" | "\\get_mode"] => format!("{}\n{}\n", mode.0, mode.1),
+                ["T" | "\\set_ptt", _] => satellite_hamlib_service::report(0),
+                ["q" | "Q"] => return None,
+                _ => satellite_hamlib_service::report(satellite_hamlib_service::RIG_ENIMPL),
+            };
+            Some(reply)
+        },
+        log,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::libs::test_support;
+
+    const DOWNLINK_HZ: f64 = 145_960_000.0;
+    const UPLINK_HZ: f64 = 435_250_000.0;
+
+    fn transponder(inverting: bool) -> Transponder {
+        Transponder {
+            downlink_hz: DOWNLINK_HZ,
+            uplink_hz: Some(UPLINK_HZ),
+            inverting,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn retunes_each_link_from_what_it_was_last_sent() {
+        // The uplink drifts past the tolerance every tick, the downlink only after three
+        let (mut downlink, mut uplink) = (None, None);
+        let retuned: Vec<(bool, bool)> = (0..4)
+            .map(|tick| {
+                let drift = tick as f64;
+                (
+                    needs_retune(&mut downlink, DOWNLINK_HZ + 40.0 * drift, 100.0),
+                    needs_retune(&mut uplink, UPLINK_HZ + 120.0 * drift, 100.0),
+                )
+            })
+            .collect();
+        assert_eq!(
+            retuned,
+            [(true, true), (false, true), (false, true), (true, true)]
+        );
+    }
+
+    #[test]
+    fn tunes_nominal_frequencies_without_motion() {
+        let tuning = doppler_tuning(&transponder(false), 0.0, 0.0);
+        assert_eq!(
+            tuning,
+            Tuning {
+                downlink_hz: DOWNLINK_HZ,
+                uplink_hz: Some(UPLINK_HZ),
+            }
+        );
+    }
+
+    #[test]
+    fn corrects_for_an_approaching_satellite() {
+        let range_rate = -7.0;
+        let factor = 1.0 - range_rate / SPEED_OF_LIGHT_KM_S;
+        let tuning = doppler_tuning(&transponder(false), range_rate, 0.0);
+
+        // Received higher than sent, so the uplink goes out lower to arrive on the nominal frequency
+        assert!(tuning.downlink_hz > DOWNLINK_HZ);
+        assert_close(tuning.downlink_hz, DOWNLINK_HZ * factor);
+        assert_close(tuning.uplink_hz.unwrap(), UPLINK_HZ / factor);
+    }
+
+    #[test]
+    fn moves_both_links_the_same_way_with_an_offset() {
+        let range_rate = 5.0;
+        let factor = 1.0 - range_rate / SPEED_OF_LIGHT_KM_S;
+        let tuning = doppler_tuning(&transponder(false), range_rate, 1200.0);
+
+        assert_close(tuning.downlink_hz, (DOWNLINK_HZ + 1200.0) * factor);
+        assert_close(tuning.uplink_hz.unwrap(), (UPLINK_HZ + 1200.0) / factor);
+    }
+
+    #[test]
+    fn mirrors_the_offset_on_an_inverting_transponder() {
+        let range_rate = 5.0;
+        let factor = 1.0 - range_rate / SPEED_OF_LIGHT_KM_S;
+        let tuning = doppler_tuning(&transponder(true), range_rate, 1200.0);
+
+        assert_close(tuning.downlink_hz, (DOWNLINK_HZ + 1200.0) * factor);
+        assert_close(tuning.uplink_hz.unwrap(), (UPLINK_HZ - 1200.0) / factor);
+    }
+
+    #[test]
+    fn tunes_a_pass_with_the_fake_rigctld() {
+        let (propagator, observer) = test_support::overhead_pass();
+        let (address, log) = test_support::spawn_fake_daemon(serve_fake_rigctld);
+        let options = RadioOptions {
+            transponder: transponder(true),
+            downlink_vfo: Some("Sub".to_string()),
+            uplink_vfo: Some("Main".to_string()),
+            horizon: 0.0,
+            interval: Duration::milliseconds(100),
+            tolerance_hz: 0.0,
+            passes: 1,
+        };
+        let offset_hz = AtomicI64::new(500);
+        let running = AtomicBool::new(true);
+        let mut rig = Rig::connect(&address).unwrap();
+
+        let mut tunings = Vec::new();
+        tune_passes(
+            &mut rig,
+            &propagator,
+            &observer,
+            &options,
+            &offset_hz,
+            &running,
+            |event| {
+                if let RadioEvent::Tuned(range_rate, tuning) = event {
+                    assert_eq!(
+                        tuning,
+                        doppler_tuning(&options.transponder, range_rate, 500.0)
+                    );
+                    tunings.push(tuning);
+                    if tunings.len() == 2 {
+                        running.store(false, Ordering::SeqCst);
+                    }
+                }
+            },
+        )
+        .unwrap();
+        // The uplink was tuned last, on the main VFO
+        let uplink = format!("{:.0}", tunings[1].uplink_hz.unwrap());
+        assert_eq!(rig.get_frequency().unwrap(), uplink.parse::<f64>().unwrap());
+        rig.close();
+
+        // Each link is tuned on its own VFO
+        let mut expected = Vec::new();
+        for tuning in &tunings {
+            expected.push("V Sub".to_string());
+            expected.push(format!("F {:.0}", tuning.downlink_hz));
+            expected.push("V Main".to_string());
+            expected.push(format!("F {:.0}", tuning.uplink_hz.unwrap()));
+        }
+        expected.extend(["f", "q"].map(String::from));
+        assert_eq!(test_support::commands_after_disconnect(&log), expected);
+    }
+}
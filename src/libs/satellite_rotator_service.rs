@@ -0,0 +1,487 @@
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{Duration, Utc};
+
+use super::orbit::{Geodetic, LookAngles, Propagator};
+use super::satellite_hamlib_service::{self, HamlibConnection};
+use super::satellite_pass_service::{self, wait_until, Pass};
+
+pub const DEFAULT_ROTCTLD_ADDRESS: &str = "localhost:4533";
+const PATH_STEP_SECONDS: i64 = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RotatorLimits {
+    pub min_azimuth: f64,
+    pub max_azimuth: f64, // Above 360 for rotators with overlap, e.g. 450
+    pub min_elevation: f64,
+    pub max_elevation: f64, // 180 for rotators that can flip over the top
+}
+
+impl Default for RotatorLimits {
+    fn default() -> Self {
+        RotatorLimits {
+            min_azimuth: 0.0,
+            max_azimuth: 360.0,
+            min_elevation: 0.0,
+            max_elevation: 90.0,
+        }
+    }
+}
+
+impl RotatorLimits {
+    pub fn can_flip(&self) -> bool {
+        self.max_elevation >= 180.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointingMode {
+    Normal,
+    // Azimuth turned by 180 degrees and elevation mirrored over the zenith, so a pass
+    // crossing north is followed without unwinding the azimuth axis mid-pass
+    Flipped,
+}
+
+impl PointingMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PointingMode::Normal => "normal",
+            PointingMode::Flipped => "flipped",
+        }
+    }
+}
+
+// Turns look angles into rotator positions for one pass, keeping azimuth moves continuous
+pub struct Pointer {
+    mode: PointingMode,
+    limits: RotatorLimits,
+    last_azimuth: f64,
+}
+
+fn transform(look: &LookAngles, mode: PointingMode, limits: &RotatorLimits) -> (f64, f64) {
+    let elevation = look.elevation.max(limits.min_elevation);
+    let (azimuth, elevation) = match mode {
+        PointingMode::Normal => (look.azimuth, elevation),
+        PointingMode::Flipped => ((look.azimuth + 180.0) % 360.0, 180.0 - elevation),
+    };
+    (
+        azimuth,
+        elevation.clamp(limits.min_elevation, limits.max_elevation),
+    )
+}
+
+// The azimuths of a path without the jumps at north, e.g. 350, 355, 365, 370
+fn unwrap_azimuths(azimuths: &[f64]) -> Vec<f64> {
+    let mut unwrapped: Vec<f64> = Vec::with_capacity(azimuths.len());
+    for &azimuth in azimuths {
+        let value = match unwrapped.last() {
+            Some(previous) => {
+                let delta = (azimuth - previous).rem_euclid(360.0);
+                previous + if delta > 180.0 { delta - 360.0 } else { delta }
+            }
+            None => azimuth,
+        };
+        unwrapped.push(value);
+    }
+    unwrapped
+}
+
+// The turn by a multiple of 360 degrees that fits an unwrapped path into the azimuth range
+fn fit_azimuths(unwrapped: &[f64], limits: &RotatorLimits) -> Option<f64> {
+    let low = unwrapped.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = unwrapped.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    (-2..=2)
+        .map(|turns| turns as f64 * 360.0)
+        .find(|shift| low + shift >= limits.min_azimuth && high + shift <= limits.max_azimuth)
+}
+
+impl Pointer {
+    /**
+    Plans how to follow a pass within the rotator limits
+
+    A path that fits the azimuth range (including any overlap) is followed as is. One that
+    crosses north without fitting is flipped when allowed and the rotator supports it,
+    otherwise the rotator has to unwind once during the pass.
+
+    # Arguments
+    * `path` - Look angles through the pass, see `satellite_pass_service::pass_path`
+    * `limits` - The rotator's mechanical limits
+    * `allow_flip` - Whether flipping over the top may be used
+    */
+    pub fn for_pass(path: &[LookAngles], limits: RotatorLimits, allow_flip: bool) -> Pointer {
+        let plan = |mode: PointingMode| -> Option<f64> {
+            let azimuths: Vec<f64> = path
+                .iter()
+                .map(|look| transform(look, mode, &limits).0)
+                .collect();
+            let unwrapped = unwrap_azimuths(&azimuths);
+            fit_azimuths(&unwrapped, &limits).map(|shift| unwrapped[0] + shift)
+        };
+
+        let start = path.first().map_or(0.0, |look| look.azimuth);
+        let (mode, last_azimuth) = match plan(PointingMode::Normal) {
+            Some(azimuth) => (PointingMode::Normal, azimuth),
+            None => match plan(PointingMode::Flipped) {
+                Some(azimuth) if allow_flip && limits.can_flip() => {
+                    (PointingMode::Flipped, azimuth)
+                }
+                _ => (PointingMode::Normal, start),
+            },
+        };
+
+        Pointer {
+            mode,
+            limits,
+            last_azimuth,
+        }
+    }
+
+    pub fn mode(&self) -> PointingMode {
+        self.mode
+    }
+
+    // The rotator position for the given look angles, closest to the previous azimuth
+    pub fn point(&mut self, look: &LookAngles) -> (f64, f64) {
+        let (azimuth, elevation) = transform(look, self.mode, &self.limits);
+
+        let azimuth = (-1..=2)
+            .map(|turns| azimuth + turns as f64 * 360.0)
+            .filter(|candidate| {
+                *candidate >= self.limits.min_azimuth && *candidate <= self.limits.max_azimuth
+            })
+            .min_by(|a, b| {
+                (a - self.last_azimuth)
+                    .abs()
+                    .total_cmp(&(b - self.last_azimuth).abs())
+            })
+            .unwrap_or(azimuth.clamp(self.limits.min_azimuth, self.limits.max_azimuth));
+
+        self.last_azimuth = azimuth;
+        (azimuth, elevation)
+    }
+}
+
+pub struct Rotator {
+    connection: HamlibConnection,
+}
+
+impl Rotator {
+    pub fn connect(address: &str) -> Result<Rotator, String> {
+        Ok(Rotator {
+            connection: HamlibConnection::connect(address)?,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        self.connection.address()
+    }
+
+    pub fn set_position(&mut self, azimuth: f64, elevation: f64) -> Result<(), String> {
+        self.connection
+            .command(&format!("P {:.1} {:.1}", azimuth, elevation), 0)
+            .map(|_| ())
+    }
+
+    pub fn get_position(&mut self) -> Result<(f64, f64), String> {
+        let values = self.connection.command("p", 2)?;
+        let parse = |text: &str| {
+            text.parse::<f64>()
+                .map_err(|_| format!("Unexpected position from {}: {}", self.address(), text))
+        };
+        Ok((parse(&values[0])?, parse(&values[1])?))
+    }
+
+    // Moves to the given park position, or the rotator's own one (`K`) when none is set
+    pub fn park(&mut self, position: Option<(f64, f64)>) -> Result<(), String> {
+        match position {
+            Some((azimuth, elevation)) => self.set_position(azimuth, elevation),
+            None => self.connection.command("K", 0).map(|_| ()),
+        }
+    }
+
+    pub fn close(self) {
+        self.connection.close();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackingOptions {
+    pub limits: RotatorLimits,
+    pub allow_flip: bool,
+    pub park: Option<(f64, f64)>,
+    pub horizon: f64,           // Elevation a pass has to rise above to be tracked
+    pub pre_position: Duration, // How long before AOS to move to the rise azimuth
+    pub interval: Duration,     // Time between position updates
+    pub tolerance: f64,         // Smallest move in degrees worth sending
+    pub passes: usize,
+}
+
+pub enum TrackingEvent {
+    NextPass(Pass, PointingMode),
+    PrePositioned(f64, f64),
+    Pointed(LookAngles, f64, f64),
+    PassEnded(Pass),
+    Parked(Option<(f64, f64)>),
+}
+
+/**
+Tracks the next passes of a satellite with a rotator, parking it between passes and at the end
+
+# Arguments
+* `rotator` - The connected rotator
+* `propagator` - The satellite to track
+* `observer` - The ground station location
+* `options` - Limits, park position and timing
+* `running` - Cleared by the caller to stop tracking, the rotator is still parked
+* `on_event` - Called as tracking progresses, for display
+*/
+pub fn track_passes<F>(
+    rotator: &mut Rotator,
+    propagator: &Propagator,
+    observer: &Geodetic,
+    options: &TrackingOptions,
+    running: &AtomicBool,
+    mut on_event: F,
+) -> Result<(), String>
+where
+    F: FnMut(TrackingEvent),
+{
+    let mut search_from = Utc::now();
+
+    for _ in 0..options.passes {
+        let pass =
+            satellite_pass_service::next_pass(propagator, observer, &search_from, options.horizon)?;
+
+        let path: Vec<LookAngles> = satellite_pass_service::pass_path(
+            propagator,
+            observer,
+            &pass,
+            Duration::seconds(PATH_STEP_SECONDS),
+        )?
+        .into_iter()
+        .map(|(_, look)| look)
+        .collect();
+        let mut pointer = Pointer::for_pass(&path, options.limits, options.allow_flip);
+        on_event(TrackingEvent::NextPass(pass.clone(), pointer.mode()));
+
+        if !wait_until(&(pass.aos - options.pre_position), running) {
+            break;
+        }
+        let mut last = pointer.point(&path[0]);
+        rotator.set_position(last.0, last.1)?;
+        on_event(TrackingEvent::PrePositioned(last.0, last.1));
+
+        if !wait_until(&pass.aos, running) {
+            break;
+        }
+        while running.load(Ordering::SeqCst) && Utc::now() < pass.los {
+            let look = satellite_pass_service::look_at(propagator, observer, &Utc::now())?;
+            let (azimuth, elevation) = pointer.point(&look);
+            if (azimuth - last.0).abs() >= options.tolerance
+                || (elevation - last.1).abs() >= options.tolerance
+            {
+                rotator.set_position(azimuth, elevation)?;
+                last = (azimuth, elevation);
+                on_event(TrackingEvent::Pointed(look, azimuth, elevation));
+            }
+            wait_until(&(Utc::now() + options.interval), running);
+        }
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+
+        on_event(TrackingEvent::PassEnded(pass.clone()));
+        rotator.park(options.park)?;
+        on_event(TrackingEvent::Parked(options.park));
+        search_from = pass.los + Duration::seconds(1);
+    }
+
+    if !running.load(Ordering::SeqCst) {
+        rotator.park(options.park)?;
+        on_event(TrackingEvent::Parked(options.park));
+    }
+
+    Ok(())
+}
+
+/**
+Runs a fake rotctld on localhost that accepts `P`, `p`, `K`, `S` and `q` and logs every command
+
+# Arguments
+* `listener` - Where to accept clients, rotctld uses 4533
+* `log` - Called with each command received
+*/
+pub fn serve_fake_rotctld<L: Fn(&str)>(listener: TcpListener, log: L) -> Result<(), String> {
+    let mut position = (0.0_f64, 0.0_f64);
+
+    satellite_hamlib_service::serve_fake_daemon_on(
+        listener,
+        |line| {
+            let mut parts = line.split_whitespace();
+            let reply = match parts.next() {
+                Some("P") | Some("\\set_pos") => {
+                    let values: Vec<Option<f64>> = parts.map(|part| part.parse().ok()).collect();
+                    match values.as_slice() {
+                        [Some(azimuth), Some(elevation)] => {
+                            position = (*azimuth, *elevation);
+                            satellite_hamlib_service::report(0)
+                        }
+                        _ => satellite_hamlib_service::report(-1),
+                    }
+                }
+                Some("p") | Some("\\get_pos") => {
+                    format!("{:.6}\n{:.6}\n", position.0, position.1)
+                }
+                Some("K") | Some("\\park") => {
+                    position = (0.0, 0.0);
+                    satellite_hamlib_service::report(0)
+                }
+                Some("S") | Some("\\stop") => satellite_hamlib_service::report(0),
+                Some("q") | Some("Q") => return None,
+                _ => satellite_hamlib_service::report(satellite_hamlib_service::RIG_ENIMPL),
+            };
+            Some(reply)
+        },
+        log,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::libs::test_support;
+
+    fn look(azimuth: f64, elevation: f64) -> LookAngles {
+        LookAngles {
+            azimuth,
+            elevation,
+            range_km: 1000.0,
+            range_rate_km_s: 0.0,
+        }
+    }
+
+    // A pass rising in the north-west and setting in the north-east
+    fn crossing_north() -> Vec<LookAngles> {
+        vec![
+            look(300.0, 0.0),
+            look(350.0, 40.0),
+            look(10.0, 40.0),
+            look(60.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn follows_a_pass_that_fits_as_is() {
+        let path = vec![look(120.0, 0.0), look(180.0, 60.0), look(240.0, 0.0)];
+        let mut pointer = Pointer::for_pass(&path, RotatorLimits::default(), true);
+
+        assert_eq!(pointer.mode(), PointingMode::Normal);
+        let positions: Vec<(f64, f64)> = path.iter().map(|look| pointer.point(look)).collect();
+        assert_eq!(positions, vec![(120.0, 0.0), (180.0, 60.0), (240.0, 0.0)]);
+    }
+
+    #[test]
+    fn uses_the_overlap_for_a_pass_crossing_north() {
+        let limits = RotatorLimits {
+            max_azimuth: 450.0,
+            ..RotatorLimits::default()
+        };
+        let path = crossing_north();
+        let mut pointer = Pointer::for_pass(&path, limits, false);
+
+        assert_eq!(pointer.mode(), PointingMode::Normal);
+        let azimuths: Vec<f64> = path.iter().map(|look| pointer.point(look).0).collect();
+        assert_eq!(azimuths, vec![300.0, 350.0, 370.0, 420.0]);
+    }
+
+    #[test]
+    fn flips_a_pass_crossing_north_without_overlap() {
+        let limits = RotatorLimits {
+            max_elevation: 180.0,
+            ..RotatorLimits::default()
+        };
+        let path = crossing_north();
+        let mut pointer = Pointer::for_pass(&path, limits, true);
+
+        assert_eq!(pointer.mode(), PointingMode::Flipped);
+        let positions: Vec<(f64, f64)> = path.iter().map(|look| pointer.point(look)).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (120.0, 180.0),
+                (170.0, 140.0),
+                (190.0, 140.0),
+                (240.0, 180.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn unwinds_when_flipping_is_not_possible() {
+        let limits = RotatorLimits {
+            max_elevation: 180.0,
+            ..RotatorLimits::default()
+        };
+        for (limits, allow_flip) in [(RotatorLimits::default(), true), (limits, false)] {
+            let path = crossing_north();
+            let mut pointer = Pointer::for_pass(&path, limits, allow_flip);
+
+            assert_eq!(pointer.mode(), PointingMode::Normal);
+            let azimuths: Vec<f64> = path.iter().map(|look| pointer.point(look).0).collect();
+            assert_eq!(azimuths, vec![300.0, 350.0, 10.0, 60.0]);
+        }
+    }
+
+    #[test]
+    fn tracks_a_pass_with_the_fake_rotctld() {
+        let (propagator, observer) = test_support::overhead_pass();
+        let (address, log) = test_support::spawn_fake_daemon(serve_fake_rotctld);
+        let options = TrackingOptions {
+            limits: RotatorLimits::default(),
+            allow_flip: false,
+            park: Some((0.0, 90.0)),
+            horizon: 0.0,
+            pre_position: Duration::zero(),
+            interval: Duration::milliseconds(100),
+            tolerance: 0.0,
+            passes: 1,
+        };
+        let running = AtomicBool::new(true);
+        let mut rotator = Rotator::connect(&address).unwrap();
+
+        let mut expected = Vec::new();
+        let mut in_progress = false;
+        track_passes(
+            &mut rotator,
+            &propagator,
+            &observer,
+            &options,
+            &running,
+            |event| match event {
+                TrackingEvent::NextPass(pass, _) => in_progress = pass.in_progress,
+                TrackingEvent::PrePositioned(azimuth, elevation)
+                | TrackingEvent::Pointed(_, azimuth, elevation) => {
+                    expected.push(format!("P {:.1} {:.1}", azimuth, elevation));
+                    // Stop after pre-positioning and two updates, which parks the rotator
+                    if expected.len() == 3 {
+                        running.store(false, Ordering::SeqCst);
+                    }
+                }
+                TrackingEvent::PassEnded(_) | TrackingEvent::Parked(_) => {}
+            },
+        )
+        .unwrap();
+        assert!(in_progress);
+        assert_eq!(rotator.get_position().unwrap(), (0.0, 90.0));
+        rotator.close();
+
+        expected.extend(["P 0.0 90.0", "p", "q"].map(String::from));
+        assert_eq!(test_support::commands_after_disconnect(&log), expected);
+    }
+}
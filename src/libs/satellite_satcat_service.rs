@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::runtime::Runtime;
+
+use super::satellite_history_service::data_dir;
+
+const DEFAULT_CACHE_MAX_AGE_HOURS: i64 = 24;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    Payload,
+    RocketBody,
+    Debris,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpsStatus {
+    Operational,
+    Nonoperational,
+    PartiallyOperational,
+    Backup,
+    Spare,
+    ExtendedMission,
+    Decayed,
+    Unknown,
+}
+
+// Radar cross section classes as used by Space-Track
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RcsSize {
+    Small,  // < 0.1 m²
+    Medium, // 0.1 - 1 m²
+    Large,  // > 1 m²
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SatcatRecord {
+    pub norad_id: i32,
+    pub object_type: ObjectType,
+    pub owner: Option<String>,
+    pub launch_date: Option<NaiveDate>,
+    pub launch_site: Option<String>,
+    pub ops_status: OpsStatus,
+    pub rcs_size: Option<RcsSize>,
+    pub decay_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedSatcat {
+    #[serde(rename = "fetchedAt")]
+    fetched_at: DateTime<Utc>,
+    records: Vec<SatcatRecord>,
+}
+
+static SATCAT: OnceLock<HashMap<i32, SatcatRecord>> = OnceLock::new();
+
+impl ObjectType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ObjectType::Payload => "Payload",
+            ObjectType::RocketBody => "Rocket Body",
+            ObjectType::Debris => "Debris",
+            ObjectType::Unknown => "Unknown",
+        }
+    }
+
+    // CelesTrak uses PAY / R/B / DEB, Space-Track spells them out
+    pub fn parse(text: &str) -> Option<ObjectType> {
+        match text.trim().to_uppercase().replace(['-', '_'], " ").as_str() {
+            "PAY" | "PAYLOAD" => Some(ObjectType::Payload),
+            "R/B" | "RB" | "ROCKET BODY" => Some(ObjectType::RocketBody),
+            "DEB" | "DEBRIS" => Some(ObjectType::Debris),
+            "UNK" | "UNKNOWN" | "TBA" => Some(ObjectType::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl OpsStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            OpsStatus::Operational => "Operational",
+            OpsStatus::Nonoperational => "Nonoperational",
+            OpsStatus::PartiallyOperational => "Partially Operational",
+            OpsStatus::Backup => "Backup",
+            OpsStatus::Spare => "Spare",
+            OpsStatus::ExtendedMission => "Extended Mission",
+            OpsStatus::Decayed => "Decayed",
+            OpsStatus::Unknown => "Unknown",
+        }
+    }
+
+    // Accepts both the CelesTrak status codes (+, -, P, B, S, X, D, ?) and the spelled out labels
+    pub fn parse(text: &str) -> Option<OpsStatus> {
+        let text = text.trim().to_uppercase();
+        let text = if text.len() > 1 {
+            text.replace(['-', '_'], " ")
+        } else {
+            text
+        };
+        match text.as_str() {
+            "+" | "OPERATIONAL" => Some(OpsStatus::Operational),
+            "-" | "NONOPERATIONAL" | "NON OPERATIONAL" => Some(OpsStatus::Nonoperational),
+            "P" | "PARTIALLY OPERATIONAL" | "PARTIAL" => Some(OpsStatus::PartiallyOperational),
+            "B" | "BACKUP" => Some(OpsStatus::Backup),
+            "S" | "SPARE" => Some(OpsStatus::Spare),
+            "X" | "EXTENDED MISSION" | "EXTENDED" => Some(OpsStatus::ExtendedMission),
+            "D" | "DECAYED" => Some(OpsStatus::Decayed),
+            "?" | "UNKNOWN" => Some(OpsStatus::Unknown),
+            _ => None,
+        }
+    }
+}
+
+impl RcsSize {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RcsSize::Small => "Small",
+            RcsSize::Medium => "Medium",
+            RcsSize::Large => "Large",
+        }
+    }
+
+    // Either a size class (Space-Track) or a cross section in m² (CelesTrak)
+    pub fn parse(text: &str) -> Option<RcsSize> {
+        match text.trim().to_uppercase().as_str() {
+            "SMALL" => Some(RcsSize::Small),
+            "MEDIUM" => Some(RcsSize::Medium),
+            "LARGE" => Some(RcsSize::Large),
+            other => other.parse::<f64>().ok().map(|rcs| {
+                if rcs < 0.1 {
+                    RcsSize::Small
+                } else if rcs <= 1.0 {
+                    RcsSize::Medium
+                } else {
+                    RcsSize::Large
+                }
+            }),
+        }
+    }
+}
+
+// Splits a CSV line, honouring double quoted fields with embedded commas and "" escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim().get(0..10)?, "%Y-%m-%d").ok()
+}
+
+// Builds a record from one row keyed by upper case column name, CelesTrak and Space-Track
+// column names are both understood
+fn record_from_row(row: &HashMap<String, String>) -> Option<SatcatRecord> {
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| row.get(*name))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let decay_date = column(&["DECAY_DATE", "DECAY"]).and_then(parse_date);
+    let ops_status = match row.get("OPS_STATUS_CODE").or_else(|| row.get("OPS_STATUS")) {
+        // A blank status code means nonoperational in the CelesTrak format
+        Some(code) if code.is_empty() => OpsStatus::Nonoperational,
+        Some(code) => OpsStatus::parse(code).unwrap_or(OpsStatus::Unknown),
+        None if decay_date.is_some() => OpsStatus::Decayed,
+        None => OpsStatus::Unknown,
+    };
+
+    Some(SatcatRecord {
+        norad_id: column(&["NORAD_CAT_ID", "NORAD_ID", "SATNO"])?
+            .parse()
+            .ok()?,
+        object_type: column(&["OBJECT_TYPE"])
+            .and_then(ObjectType::parse)
+            .unwrap_or(ObjectType::Unknown),
+        owner: column(&["OWNER", "COUNTRY"]).map(str::to_string),
+        launch_date: column(&["LAUNCH_DATE", "LAUNCH"]).and_then(parse_date),
+        launch_site: column(&["LAUNCH_SITE", "SITE"]).map(str::to_string),
+        ops_status,
+        rcs_size: column(&["RCS", "RCS_SIZE"]).and_then(RcsSize::parse),
+        decay_date,
+    })
+}
+
+fn parse_satcat_csv(contents: &str) -> Result<Vec<SatcatRecord>, String> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = split_csv_line(lines.next().ok_or("The SATCAT file is empty")?)
+        .iter()
+        .map(|name| name.trim().to_uppercase())
+        .collect();
+
+    if !header
+        .iter()
+        .any(|name| name == "NORAD_CAT_ID" || name == "NORAD_ID" || name == "SATNO")
+    {
+        return Err("The SATCAT file has no NORAD_CAT_ID column".to_string());
+    }
+
+    Ok(lines
+        .filter_map(|line| {
+            let row: HashMap<String, String> =
+                header.iter().cloned().zip(split_csv_line(line)).collect();
+            record_from_row(&row)
+        })
+        .collect())
+}
+
+fn parse_satcat_json(contents: &str) -> Result<Vec<SatcatRecord>, String> {
+    let rows: Vec<HashMap<String, serde_json::Value>> =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid SATCAT JSON: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            // Numbers (NORAD_CAT_ID, RCS) and nulls are turned into the same text as in the CSV
+            let row: HashMap<String, String> = row
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(text) => text.clone(),
+                        serde_json::Value::Null => String::new(),
+                        other => other.to_string(),
+                    };
+                    (key.to_uppercase(), value)
+                })
+                .collect();
+            record_from_row(&row)
+        })
+        .collect())
+}
+
+/**
+Parses SATCAT data in the CelesTrak or Space-Track CSV/JSON format
+
+# Arguments
+* `contents` - The file contents, JSON is recognised by its leading `[`
+# Returns
+* The parsed records, or why the data couldn't be read
+*/
+pub fn parse_satcat(contents: &str) -> Result<Vec<SatcatRecord>, String> {
+    if contents.trim_start().starts_with('[') {
+        parse_satcat_json(contents)
+    } else {
+        parse_satcat_csv(contents)
+    }
+}
+
+fn load_satcat_file(path: &Path) -> Vec<SatcatRecord> {
+    match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| parse_satcat(&c))
+    {
+        Ok(records) => records,
+        Err(err) => {
+            eprintln!("Error reading SATCAT file {}: {}", path.display(), err);
+            vec![]
+        }
+    }
+}
+
+// Where `satcat import` keeps its copy
+fn imported_satcat_path() -> PathBuf {
+    data_dir().join("satcat.json")
+}
+
+fn satcat_cache_path() -> PathBuf {
+    data_dir().join("satcat_cache.json")
+}
+
+fn save_records(path: &Path, records: &[SatcatRecord]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let cached = CachedSatcat {
+        fetched_at: Utc::now(),
+        records: records.to_vec(),
+    };
+    let body = serde_json::to_string(&cached).map_err(|e| e.to_string())?;
+    fs::write(path, body).map_err(|e| e.to_string())
+}
+
+fn load_records(path: &Path) -> Option<CachedSatcat> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn fetch_satcat(url: &str) -> Vec<SatcatRecord> {
+    let max_age = env::var("COSMOS_CATALOG_MAX_AGE_HOURS")
+        .ok()
+        .and_then(|hours| hours.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_CACHE_MAX_AGE_HOURS);
+
+    let cached = load_records(&satcat_cache_path());
+    if let Some(cached) = &cached {
+        if Utc::now() - cached.fetched_at < Duration::hours(max_age) {
+            return cached.records.clone();
+        }
+    }
+
+    let rt = Runtime::new().unwrap();
+    let fetched = rt.block_on(async {
+        let response = Client::new()
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        parse_satcat(&body)
+    });
+
+    match fetched {
+        Ok(records) if !records.is_empty() => {
+            if let Err(err) = save_records(&satcat_cache_path(), &records) {
+                eprintln!("Error caching SATCAT: {}", err);
+            }
+            records
+        }
+        result => {
+            if let Err(err) = result {
+                eprintln!("Error fetching SATCAT from {}: {}", url, err);
+            }
+            // A stale copy is still better than no metadata at all
+            cached.map(|cached| cached.records).unwrap_or_default()
+        }
+    }
+}
+
+/**
+Copies a SATCAT CSV/JSON file into the data directory, so it is used without configuration
+
+# Arguments
+* `path` - The file to import
+# Returns
+* The number of imported records
+*/
+pub fn import_satcat(path: &str) -> Result<usize, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path, e))?;
+    let records = parse_satcat(&contents)?;
+    if records.is_empty() {
+        return Err(format!("No SATCAT records found in {}", path));
+    }
+
+    save_records(&imported_satcat_path(), &records)?;
+    Ok(records.len())
+}
+
+/**
+Returns the SATCAT metadata, keyed by NORAD ID
+
+The records are read from the file at `COSMOS_SATCAT_FILE` when set, then from the copy made by
+`satcat import`, and otherwise fetched from `COSMOS_SATCAT_URL` and cached on disk. Without any
+of these the table is empty and no metadata is shown.
+*/
+pub fn get_satcat() -> &'static HashMap<i32, SatcatRecord> {
+    SATCAT.get_or_init(|| {
+        let records = if let Ok(path) = env::var("COSMOS_SATCAT_FILE") {
+            load_satcat_file(Path::new(&path))
+        } else if let Some(imported) = load_records(&imported_satcat_path()) {
+            imported.records
+        } else if let Ok(url) = env::var("COSMOS_SATCAT_URL") {
+            fetch_satcat(&url)
+        } else {
+            vec![]
+        };
+
+        records
+            .into_iter()
+            .map(|record| (record.norad_id, record))
+            .collect()
+    })
+}
+
+pub fn get_record(norad_id: i32) -> Option<&'static SatcatRecord> {
+    get_satcat().get(&norad_id)
+}
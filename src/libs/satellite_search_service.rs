@@ -2,6 +2,49 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tokio::runtime::Runtime;
 
+use super::satellite_catalog_service;
+use super::satellite_history_service::record_history;
+use super::satellite_index_service::designator_from_tle;
+
+const TLE_API_URL: &str = "http://tle.ivanstanojevic.me/api/tle";
+// The TLE API can't look up designators, CelesTrak's GP API can
+const CELESTRAK_GP_URL: &str = "https://celestrak.org/NORAD/elements/gp.php";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SatelliteIdentifier {
+    Norad(i32),
+    Cospar(String), // YYYY-NNN with an optional piece, e.g. 1998-067A
+}
+
+impl std::fmt::Display for SatelliteIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SatelliteIdentifier::Norad(id) => write!(f, "NORAD {}", id),
+            SatelliteIdentifier::Cospar(designator) => write!(f, "COSPAR {}", designator),
+        }
+    }
+}
+
+impl SatelliteIdentifier {
+    // A designator without a piece letter (YYYY-NNN) matches every piece of that launch, one with
+    // a piece only that piece, so 1998-067A doesn't also match 1998-067AA
+    pub fn matches(&self, satellite: &Satellite) -> bool {
+        match self {
+            SatelliteIdentifier::Norad(id) => satellite.id == *id,
+            SatelliteIdentifier::Cospar(designator) => {
+                let wanted = designator.replace('-', "");
+                designator_from_tle(&satellite.line_one).is_some_and(|found| {
+                    if wanted.len() > 7 {
+                        found == wanted
+                    } else {
+                        found.starts_with(&wanted)
+                    }
+                })
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SearchResponse {
     #[serde(rename = "totalItems")]
@@ -68,11 +111,10 @@ pub fn get_satellites(
     page_size: Option<i32>,
 ) -> (Vec<Satellite>, i32) {
     let rt = Runtime::new().unwrap();
-    let base_url = "http://tle.ivanstanojevic.me/api/tle";
 
-    let url = build_query_url(base_url, satellite_name, page, page_size);
+    let url = build_query_url(TLE_API_URL, satellite_name, page, page_size);
 
-    rt.block_on(async {
+    let (satellites, num_pages) = rt.block_on(async {
         match fetch_satellites_data(url).await {
             Ok(body) => parse_satellites_data(&body),
             Err(err) => {
@@ -80,5 +122,117 @@ pub fn get_satellites(
                 (vec![], 0)
             }
         }
+    });
+
+    // Keep every element set we see, not only the latest one
+    record_history(&satellites);
+
+    (satellites, num_pages)
+}
+
+/**
+Recognises NORAD catalog numbers (25544) and COSPAR designators (1998-067A, 98067A or a whole
+launch like 1998-067)
+
+# Arguments
+* `text` - The raw user input
+# Returns
+* The identifier, or None if the input looks like a name
+*/
+pub fn parse_identifier(text: &str) -> Option<SatelliteIdentifier> {
+    let text = text.trim().to_uppercase();
+
+    if !text.is_empty() && text.len() <= 9 && text.chars().all(|c| c.is_ascii_digit()) {
+        return text.parse().ok().map(SatelliteIdentifier::Norad);
+    }
+
+    let (year, rest) = match text.split_once('-') {
+        Some((year, rest)) if year.len() == 4 => (year.parse::<i32>().ok()?, rest.to_string()),
+        // The short TLE form needs a piece letter, otherwise it is a NORAD ID
+        None if text.len() >= 6 && text.chars().last()?.is_ascii_alphabetic() => {
+            let year = text.get(0..2)?.parse::<i32>().ok()?;
+            let year = if year < 57 { 2000 + year } else { 1900 + year };
+            (year, text.get(2..)?.to_string())
+        }
+        _ => return None,
+    };
+
+    let number = rest.get(0..3)?;
+    let piece = rest.get(3..)?;
+    if !number.chars().all(|c| c.is_ascii_digit())
+        || piece.len() > 3
+        || !piece.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        return None;
+    }
+
+    Some(SatelliteIdentifier::Cospar(format!(
+        "{}-{}{}",
+        year, number, piece
+    )))
+}
+
+fn get_satellite_by_norad_id(id: i32) -> Option<Satellite> {
+    let rt = Runtime::new().unwrap();
+    let url = format!("{}/{}", TLE_API_URL, id);
+
+    rt.block_on(async {
+        let response = Client::new().get(&url).send().await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.json::<Satellite>().await.ok()
     })
 }
+
+fn get_satellites_by_designator(designator: &str) -> Vec<Satellite> {
+    let rt = Runtime::new().unwrap();
+    let mut url = reqwest::Url::parse(CELESTRAK_GP_URL).expect("Base URL is invalid");
+    url.query_pairs_mut()
+        .append_pair("INTDES", designator)
+        .append_pair("FORMAT", "TLE");
+
+    rt.block_on(async {
+        match fetch_satellites_data(url.to_string()).await {
+            Ok(body) => satellite_catalog_service::parse_tle_file(&body),
+            Err(err) => {
+                println!("Error fetching satellite data: {}", err);
+                vec![]
+            }
+        }
+    })
+}
+
+/**
+Looks a satellite up directly by its identifier
+
+The catalog is searched first if it has already been loaded, otherwise the object is fetched.
+
+# Arguments
+* `identifier` - A NORAD ID or (possibly partial) COSPAR designator
+# Returns
+* The matching satellites, several for a designator that names a whole launch
+*/
+pub fn lookup_satellites(identifier: &SatelliteIdentifier) -> Vec<Satellite> {
+    if let Some(catalog) = satellite_catalog_service::loaded_catalog() {
+        let found: Vec<Satellite> = catalog
+            .iter()
+            .filter(|s| identifier.matches(s))
+            .cloned()
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+
+    let fetched: Vec<Satellite> = match identifier {
+        SatelliteIdentifier::Norad(id) => get_satellite_by_norad_id(*id).into_iter().collect(),
+        SatelliteIdentifier::Cospar(designator) => get_satellites_by_designator(designator),
+    };
+    record_history(&fetched);
+
+    fetched
+        .into_iter()
+        .filter(|s| identifier.matches(s))
+        .collect()
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use super::orbit::{self, Geodetic, Propagator, EARTH_RADIUS_KM};
+use super::satellite_ephemeris_service;
+
+// Spacing of the points on a footprint outline
+const FOOTPRINT_STEP_DEG: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrackPoint {
+    pub time: DateTime<Utc>,
+    pub position: Geodetic,
+}
+
+// A closed (longitude, latitude) ring in degrees
+pub type Ring = Vec<(f64, f64)>;
+
+pub fn position_at(propagator: &Propagator, time: &DateTime<Utc>) -> Result<TrackPoint, String> {
+    let state = propagator.state_at(time)?;
+    Ok(TrackPoint {
+        time: *time,
+        position: orbit::teme_to_geodetic(&state.position, time),
+    })
+}
+
+/**
+Propagates the sub-satellite point over a time range
+
+# Arguments
+* `elements` - The element set to propagate
+* `start` - The first point of the track
+* `duration` - The length of the track, the end point is always included
+* `step` - The time between two points
+# Returns
+* The track, or the propagation error (e.g. a decayed orbit)
+*/
+pub fn ground_track(
+    elements: &Elements,
+    start: &DateTime<Utc>,
+    duration: Duration,
+    step: Duration,
+) -> Result<Vec<TrackPoint>, String> {
+    let propagator = Propagator::new(elements.clone())?;
+
+    let points = satellite_ephemeris_service::sample_times(start, duration, step)?
+        .iter()
+        .map(|time| position_at(&propagator, time))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(points)
+}
+
+fn interpolate(a: &TrackPoint, b: &TrackPoint, fraction: f64, longitude: f64) -> TrackPoint {
+    let milliseconds = (b.time - a.time).num_milliseconds() as f64 * fraction;
+    TrackPoint {
+        time: a.time + Duration::milliseconds(milliseconds as i64),
+        position: Geodetic {
+            latitude: a.position.latitude + (b.position.latitude - a.position.latitude) * fraction,
+            longitude,
+            altitude_km: a.position.altitude_km
+                + (b.position.altitude_km - a.position.altitude_km) * fraction,
+        },
+    }
+}
+
+// Cuts the track wherever it jumps across ±180°, with an interpolated point on both sides
+pub fn split_at_antimeridian(points: &[TrackPoint]) -> Vec<Vec<TrackPoint>> {
+    let mut segments = Vec::new();
+    let mut segment: Vec<TrackPoint> = Vec::new();
+
+    for point in points {
+        if let Some(previous) = segment.last().copied() {
+            let jump = point.position.longitude - previous.position.longitude;
+            if jump.abs() > 180.0 {
+                // Crossing eastwards when the longitude drops from +180 to -180
+                let edge = if jump < 0.0 { 180.0 } else { -180.0 };
+                let unwrapped = point.position.longitude + 2.0 * edge;
+                let fraction = (edge - previous.position.longitude)
+                    / (unwrapped - previous.position.longitude);
+
+                segment.push(interpolate(&previous, point, fraction, edge));
+                segments.push(std::mem::take(&mut segment));
+                segment.push(interpolate(&previous, point, fraction, -edge));
+            }
+        }
+        segment.push(*point);
+    }
+    if segment.len() > 1 {
+        segments.push(segment);
+    }
+
+    segments
+}
+
+// Keeps the part of the ring on one side of a meridian (Sutherland-Hodgman for a single edge)
+fn clip_ring(ring: &[(f64, f64)], edge: f64, keep_west: bool) -> Ring {
+    let inside = |(longitude, _): &(f64, f64)| {
+        if keep_west {
+            *longitude <= edge
+        } else {
+            *longitude >= edge
+        }
+    };
+    let crossing = |a: &(f64, f64), b: &(f64, f64)| {
+        let fraction = (edge - a.0) / (b.0 - a.0);
+        (edge, a.1 + (b.1 - a.1) * fraction)
+    };
+
+    let mut clipped = Vec::new();
+    for (i, current) in ring.iter().enumerate() {
+        let previous = &ring[(i + ring.len() - 1) % ring.len()];
+        match (inside(previous), inside(current)) {
+            (true, true) => clipped.push(*current),
+            (true, false) => clipped.push(crossing(previous, current)),
+            (false, true) => {
+                clipped.push(crossing(previous, current));
+                clipped.push(*current);
+            }
+            (false, false) => {}
+        }
+    }
+    if let Some(first) = clipped.first().copied() {
+        clipped.push(first);
+    }
+
+    clipped
+}
+
+/**
+Computes the area on the ground that can see the satellite above the horizon
+
+# Arguments
+* `position` - The sub-satellite point and altitude
+# Returns
+* One polygon, or two when the footprint straddles the antimeridian
+*/
+pub fn footprint(position: &Geodetic) -> Vec<Ring> {
+    let radius = (EARTH_RADIUS_KM / (EARTH_RADIUS_KM + position.altitude_km.max(0.0))).acos();
+    let latitude = position.latitude.to_radians();
+
+    let outline: Ring = (0..(360.0 / FOOTPRINT_STEP_DEG) as usize)
+        .map(|i| {
+            let bearing = (i as f64 * FOOTPRINT_STEP_DEG).to_radians();
+            let point_latitude = (latitude.sin() * radius.cos()
+                + latitude.cos() * radius.sin() * bearing.cos())
+            .asin();
+            let offset = (bearing.sin() * radius.sin() * latitude.cos())
+                .atan2(radius.cos() - latitude.sin() * point_latitude.sin());
+            (
+                position.longitude + offset.to_degrees(),
+                point_latitude.to_degrees(),
+            )
+        })
+        .collect();
+
+    // Footprints around a pole wrap all the way around, they are closed along the pole instead
+    if radius.to_degrees() > 90.0 - position.latitude.abs() {
+        let pole = 90.0_f64.copysign(position.latitude);
+        let mut points: Ring = outline
+            .iter()
+            .map(|(longitude, latitude)| ((longitude + 180.0).rem_euclid(360.0) - 180.0, *latitude))
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let (first, last) = (points[0], points[points.len() - 1]);
+        let fraction = (180.0 - last.0) / (first.0 + 360.0 - last.0);
+        let edge_latitude = last.1 + (first.1 - last.1) * fraction;
+
+        let mut ring = vec![(-180.0, pole), (-180.0, edge_latitude)];
+        ring.extend(points);
+        ring.extend([(180.0, edge_latitude), (180.0, pole), (-180.0, pole)]);
+        return vec![ring];
+    }
+
+    let west = outline.iter().map(|p| p.0).fold(f64::MAX, f64::min);
+    let east = outline.iter().map(|p| p.0).fold(f64::MIN, f64::max);
+    let shift =
+        |ring: Ring, by: f64| -> Ring { ring.into_iter().map(|(x, y)| (x + by, y)).collect() };
+
+    if east > 180.0 {
+        vec![
+            clip_ring(&outline, 180.0, true),
+            shift(clip_ring(&outline, 180.0, false), -360.0),
+        ]
+    } else if west < -180.0 {
+        vec![
+            clip_ring(&outline, -180.0, false),
+            shift(clip_ring(&outline, -180.0, true), 360.0),
+        ]
+    } else {
+        let mut ring = outline;
+        ring.push(ring[0]);
+        vec![ring]
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use super::orbit::{self, EclipseState, Geodetic, LookAngles, Propagator};
+use super::satellite_pass_service::wait_until;
+use super::satellite_search_service::Satellite;
+
+// Where one tracked satellite is at one moment
+#[derive(Debug, Clone)]
+pub struct TrackingUpdate {
+    pub norad_id: i32,
+    pub name: String,
+    pub time: DateTime<Utc>,
+    pub position: Geodetic,
+    pub look: Option<LookAngles>,
+    pub sunlit: bool,
+    pub eclipse: EclipseState,
+}
+
+// Propagates a set of satellites, optionally relative to an observer
+pub struct Tracker {
+    targets: Vec<(Satellite, Propagator)>,
+    observer: Option<Geodetic>,
+}
+
+impl Tracker {
+    pub fn new(
+        targets: Vec<(Satellite, Elements)>,
+        observer: Option<Geodetic>,
+    ) -> Result<Tracker, String> {
+        let targets = targets
+            .into_iter()
+            .map(|(satellite, elements)| match Propagator::new(elements) {
+                Ok(propagator) => Ok((satellite, propagator)),
+                Err(err) => Err(format!("Error propagating {}: {}", satellite.name, err)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Tracker { targets, observer })
+    }
+
+    // Every satellite at `time`; one that fails to propagate (e.g. after decay) is left out
+    pub fn update(&self, time: &DateTime<Utc>) -> Vec<TrackingUpdate> {
+        self.targets
+            .iter()
+            .filter_map(|(satellite, propagator)| {
+                let state = propagator.state_at(time).ok()?;
+                Some(TrackingUpdate {
+                    norad_id: satellite.id,
+                    name: satellite.name.clone(),
+                    time: *time,
+                    position: orbit::teme_to_geodetic(&state.position, time),
+                    look: self
+                        .observer
+                        .as_ref()
+                        .map(|observer| orbit::look_angles(observer, &state, time)),
+                    sunlit: orbit::is_sunlit(&state.position, time),
+                    eclipse: orbit::eclipse_state(&state.position, time),
+                })
+            })
+            .collect()
+    }
+}
+
+/**
+Updates a set of satellites at a fixed rate, the loop behind the live map and the position stream
+
+# Arguments
+* `tracker` - The satellites to update
+* `interval` - The time between two updates, the first one is immediate
+* `running` - Cleared by the caller to stop the loop
+* `on_update` - Called with every update, returning false stops the loop (e.g. a client left)
+*/
+pub fn run_tracking_loop<F>(
+    tracker: &Tracker,
+    interval: Duration,
+    running: &AtomicBool,
+    mut on_update: F,
+) where
+    F: FnMut(&[TrackingUpdate]) -> bool,
+{
+    let mut next = Utc::now();
+    while running.load(Ordering::SeqCst) {
+        if !on_update(&tracker.update(&Utc::now())) {
+            break;
+        }
+
+        // Scheduled from the previous tick so slow consumers don't make the rate drift
+        next += interval;
+        if next < Utc::now() {
+            next = Utc::now();
+        }
+        if !wait_until(&next, running) {
+            break;
+        }
+    }
+}
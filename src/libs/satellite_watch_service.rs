@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use super::orbit::Propagator;
+use super::satellite_pass_service::{self, wait_until, Pass, Station, StationPass};
+use super::satellite_search_service::Satellite;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AlertKind {
+    Warning, // The configured lead time before AOS
+    Aos,
+    Tca,
+    Los,
+}
+
+impl AlertKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertKind::Warning => "warning",
+            AlertKind::Aos => "aos",
+            AlertKind::Tca => "tca",
+            AlertKind::Los => "los",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub time: DateTime<Utc>,
+    pub satellite: Satellite,
+    pub station: Station,
+    pub pass: Pass,
+}
+
+// Satellite, station and kind, an alert's identity apart from its time
+type AlertKey = (i32, String, AlertKind);
+
+impl Alert {
+    fn key(&self) -> AlertKey {
+        (self.satellite.id, self.station.name.clone(), self.kind)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub horizon: f64,
+    pub lead: Duration, // How long before AOS the warning fires, zero disables it
+    pub lookahead: Duration, // How far ahead passes are predicted
+    pub refresh: Duration, // How often the TLEs are fetched again
+}
+
+pub enum WatchEvent {
+    Predicted(usize, DateTime<Utc>), // Number of upcoming alerts and the end of the window
+    TleUpdated(Satellite),
+    Alert(Alert),
+    Error(String), // Something failed but watching goes on, e.g. a TLE refresh
+}
+
+// The alerts for one pass
+fn pass_alerts(entry: StationPass, lead: Duration) -> Vec<Alert> {
+    let pass = &entry.pass;
+    let mut kinds = Vec::new();
+    // A pass that was already up when predicting has no real AOS to announce
+    if !pass.in_progress {
+        if lead > Duration::zero() {
+            kinds.push((AlertKind::Warning, pass.aos - lead));
+        }
+        kinds.push((AlertKind::Aos, pass.aos));
+    }
+    kinds.push((AlertKind::Tca, pass.tca));
+    kinds.push((AlertKind::Los, pass.los));
+
+    kinds
+        .into_iter()
+        .map(|(kind, time)| Alert {
+            kind,
+            time,
+            satellite: entry.satellite.clone(),
+            station: entry.station.clone(),
+            pass: pass.clone(),
+        })
+        .collect()
+}
+
+/**
+Predicts the passes of every satellite over every station and turns them into alerts
+
+# Arguments
+* `targets` - The satellites to predict
+* `stations` - The observer locations
+* `from` - The start of the prediction window
+* `options` - The horizon, lead time and window length
+# Returns
+* The alerts in chronological order, and an error message for each satellite that failed
+*/
+pub fn predict_alerts(
+    targets: &[(Satellite, Propagator)],
+    stations: &[Station],
+    from: &DateTime<Utc>,
+    options: &WatchOptions,
+) -> (Vec<Alert>, Vec<String>) {
+    let (passes, errors) = satellite_pass_service::predict_station_passes(
+        targets,
+        stations,
+        from,
+        &(*from + options.lookahead),
+        options.horizon,
+    );
+    let mut alerts: Vec<Alert> = passes
+        .into_iter()
+        .flat_map(|entry| pass_alerts(entry, options.lead))
+        .collect();
+
+    alerts.sort_by(|a, b| a.time.cmp(&b.time).then(a.kind.cmp(&b.kind)));
+    (alerts, errors)
+}
+
+/*
+How far apart two alerts of the same kind can be and still announce the same pass: a quarter of
+an orbit, as a new TLE moves a pass by seconds to minutes while the next pass over the station
+comes about an orbit later
+*/
+fn same_pass_window(targets: &[(Satellite, Propagator)], norad_id: i32) -> Duration {
+    targets
+        .iter()
+        .find(|(satellite, _)| satellite.id == norad_id)
+        .map_or(Duration::zero(), |(_, propagator)| {
+            Duration::milliseconds((86_400_000.0 / propagator.elements.mean_motion / 4.0) as i64)
+        })
+}
+
+fn already_fired(fired: &[(AlertKey, DateTime<Utc>)], alert: &Alert, window: Duration) -> bool {
+    let key = alert.key();
+    fired
+        .iter()
+        .any(|(fired_key, time)| *fired_key == key && (*time - alert.time).abs() < window)
+}
+
+fn build_targets(
+    fetched: Vec<(Satellite, Elements)>,
+    on_error: &mut impl FnMut(String),
+) -> Vec<(Satellite, Propagator)> {
+    fetched
+        .into_iter()
+        .filter_map(|(satellite, elements)| match Propagator::new(elements) {
+            Ok(propagator) => Some((satellite, propagator)),
+            Err(err) => {
+                on_error(format!("Error propagating {}: {}", satellite.name, err));
+                None
+            }
+        })
+        .collect()
+}
+
+/**
+Watches the passes of a set of satellites and reports alerts as they come due
+
+Passes are predicted again whenever a refresh brings a new TLE for any satellite, and
+before the prediction window runs out.
+
+# Arguments
+* `stations` - The observer locations
+* `options` - Horizon, lead time, window length and refresh interval
+* `running` - Cleared by the caller to stop watching
+* `fetch` - Fetches the current TLEs of the watched satellites
+* `on_event` - Called with every alert and status change
+# Returns
+* An error when the first fetch fails, otherwise Ok once stopped
+*/
+pub fn watch_passes<R, F>(
+    stations: &[Station],
+    options: &WatchOptions,
+    running: &AtomicBool,
+    mut fetch: R,
+    mut on_event: F,
+) -> Result<(), String>
+where
+    R: FnMut() -> Result<Vec<(Satellite, Elements)>, String>,
+    F: FnMut(WatchEvent),
+{
+    let fetched = fetch()?;
+    let mut lines: HashMap<i32, (String, String)> = fetched
+        .iter()
+        .map(|(s, _)| (s.id, (s.line_one.clone(), s.line_two.clone())))
+        .collect();
+    let mut report_error = |err: String| on_event(WatchEvent::Error(err));
+    let mut targets = build_targets(fetched, &mut report_error);
+
+    // Warnings that are already overdue at start-up still fire, AOS is still ahead for them
+    let mut handled_until = Utc::now() - options.lead;
+    let mut next_refresh = Utc::now() + options.refresh;
+    let mut alerts: Vec<Alert> = Vec::new();
+    // Fired alerts with their time, so a re-predicted pass doesn't fire them twice
+    let mut fired: Vec<(AlertKey, DateTime<Utc>)> = Vec::new();
+    let mut predicted_until = Utc::now();
+    let mut predict = true;
+
+    while running.load(Ordering::SeqCst) {
+        let now = Utc::now();
+        if predict || predicted_until - now < options.lookahead / 2 {
+            let (predicted, errors) = predict_alerts(&targets, stations, &now, options);
+            errors
+                .into_iter()
+                .for_each(|err| on_event(WatchEvent::Error(err)));
+            alerts = predicted
+                .into_iter()
+                .filter(|alert| {
+                    let window = same_pass_window(&targets, alert.satellite.id);
+                    alert.time > handled_until && !already_fired(&fired, alert, window)
+                })
+                .collect();
+            predicted_until = now + options.lookahead;
+            predict = false;
+            on_event(WatchEvent::Predicted(alerts.len(), predicted_until));
+        }
+
+        let wake = alerts
+            .first()
+            .map_or(next_refresh, |alert| alert.time.min(next_refresh));
+        if !wait_until(&wake, running) {
+            break;
+        }
+
+        let now = Utc::now();
+        let due = alerts.iter().take_while(|alert| alert.time <= now).count();
+        for alert in alerts.drain(..due) {
+            let window = same_pass_window(&targets, alert.satellite.id);
+            if !already_fired(&fired, &alert, window) {
+                fired.push((alert.key(), alert.time));
+                on_event(WatchEvent::Alert(alert));
+            }
+        }
+        handled_until = now;
+        // Alerts further back than the window can't be predicted again
+        fired.retain(|(_, time)| *time + options.lookahead > now);
+
+        if now >= next_refresh {
+            next_refresh = now + options.refresh;
+            match fetch() {
+                Ok(fetched) => {
+                    let updated: Vec<Satellite> = fetched
+                        .iter()
+                        .filter(|(s, _)| {
+                            lines.get(&s.id) != Some(&(s.line_one.clone(), s.line_two.clone()))
+                        })
+                        .map(|(s, _)| s.clone())
+                        .collect();
+                    if !updated.is_empty() {
+                        for satellite in updated {
+                            lines.insert(
+                                satellite.id,
+                                (satellite.line_one.clone(), satellite.line_two.clone()),
+                            );
+                            on_event(WatchEvent::TleUpdated(satellite));
+                        }
+                        let mut report_error = |err: String| on_event(WatchEvent::Error(err));
+                        targets = build_targets(fetched, &mut report_error);
+                        predict = true;
+                    }
+                }
+                // Keep watching with the element sets we have
+                Err(err) => on_event(WatchEvent::Error(format!("Error refreshing TLEs: {}", err))),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::libs::test_support;
+
+    fn tca_alert(station: &str, time: DateTime<Utc>) -> Alert {
+        let (_, location) = test_support::overhead_pass();
+        Alert {
+            kind: AlertKind::Tca,
+            time,
+            satellite: test_support::catalog_entry(99999, "24001A").satellite,
+            station: Station {
+                name: station.to_string(),
+                location,
+            },
+            pass: Pass {
+                aos: time - Duration::minutes(5),
+                tca: time,
+                los: time + Duration::minutes(5),
+                aos_azimuth: 200.0,
+                tca_azimuth: 290.0,
+                los_azimuth: 20.0,
+                max_elevation: 80.0,
+                in_progress: false,
+                revolution: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn knows_a_fired_alert_whose_pass_was_predicted_again() {
+        let (propagator, _) = test_support::overhead_pass();
+        let satellite = test_support::catalog_entry(99999, "24001A").satellite;
+        let window = same_pass_window(&[(satellite, propagator)], 99999);
+
+        let fired_at = Utc::now();
+        let fired = vec![(tca_alert("home", fired_at).key(), fired_at)];
+
+        // A new TLE moved the pass by a few minutes
+        let moved = tca_alert("home", fired_at + Duration::minutes(3));
+        assert!(already_fired(&fired, &moved, window));
+        // The pass an orbit later, and the same pass seen from another station
+        let next = tca_alert("home", fired_at + Duration::minutes(93));
+        assert!(!already_fired(&fired, &next, window));
+        let elsewhere = tca_alert("club", fired_at);
+        assert!(!already_fired(&fired, &elsewhere, window));
+    }
+}
@@ -0,0 +1,129 @@
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
+
+use chrono::{Datelike, Duration, Timelike, Utc};
+use sgp4::Elements;
+
+use super::orbit::{self, Geodetic, Propagator};
+use super::satellite_catalog_service::CatalogEntry;
+use super::satellite_hamlib_service;
+use super::satellite_search_service::Satellite;
+
+// Modulo 10 sum of the digits with 1 for every minus sign, the last column of a TLE line
+fn checksum(line: &str) -> u32 {
+    line.chars()
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+/**
+A low circular orbit with its epoch now, and an observer right under it half a minute from now,
+so a pass is always in progress whenever a test runs
+
+# Returns
+* The propagator and the observer
+*/
+pub fn overhead_pass() -> (Propagator, Geodetic) {
+    let now = Utc::now();
+    let day = now.ordinal() as f64 + now.num_seconds_from_midnight() as f64 / 86400.0;
+    let line_one = format!(
+        "1 99999U 24001A   {:02}{:012.8}  .00000000  00000-0  00000-0 0  999",
+        now.year() % 100,
+        day
+    );
+    let line_two = "2 99999  51.6000 120.0000 0001000  90.0000   0.0000 15.50000000    1";
+    let line_one = format!("{}{}", line_one, checksum(&line_one));
+    let line_two = format!("{}{}", line_two, checksum(line_two));
+
+    let elements = Elements::from_tle(
+        Some("TEST".to_string()),
+        line_one.as_bytes(),
+        line_two.as_bytes(),
+    )
+    .unwrap();
+    let propagator = Propagator::new(elements).unwrap();
+
+    let time = now + Duration::seconds(30);
+    let state = propagator.state_at(&time).unwrap();
+    let mut observer = orbit::teme_to_geodetic(&state.position, &time);
+    observer.altitude_km = 0.0;
+    (propagator, observer)
+}
+
+// A catalog object in a low circular orbit, `designator` is the COSPAR ID without its dash
+pub fn catalog_entry(norad_id: i32, designator: &str) -> CatalogEntry {
+    let line_one = format!(
+        "1 {:05}U {:<8} 24001.00000000  .00000000  00000-0  00000-0 0  999",
+        norad_id, designator
+    );
+    let line_two = format!(
+        "2 {:05}  51.6000 120.0000 0001000  90.0000   0.0000 15.50000000    1",
+        norad_id
+    );
+    let satellite = Satellite {
+        id: norad_id,
+        name: format!("OBJECT {}", norad_id),
+        line_one: format!("{}{}", line_one, checksum(&line_one)),
+        line_two: format!("{}{}", line_two, checksum(&line_two)),
+    };
+    let elements = Elements::from_tle(
+        Some(satellite.name.clone()),
+        satellite.line_one.as_bytes(),
+        satellite.line_two.as_bytes(),
+    )
+    .unwrap();
+    CatalogEntry {
+        satellite,
+        elements,
+    }
+}
+
+pub type DaemonLog = Arc<Mutex<Vec<String>>>;
+
+/**
+Starts a stand-in daemon on a free port in the background
+
+# Arguments
+* `serve` - Runs the daemon, e.g. `satellite_rotator_service::serve_fake_rotctld`
+# Returns
+* The address to connect to and every line the daemon logs
+*/
+pub fn spawn_fake_daemon<F>(serve: F) -> (String, DaemonLog)
+where
+    F: FnOnce(TcpListener, Box<dyn Fn(&str) + Send>) -> Result<(), String> + Send + 'static,
+{
+    let listener = satellite_hamlib_service::listen_locally(0).unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let log: DaemonLog = Arc::default();
+
+    let lines = Arc::clone(&log);
+    thread::spawn(move || {
+        serve(
+            listener,
+            Box::new(move |line: &str| lines.lock().unwrap().push(line.to_string())),
+        )
+    });
+    (address, log)
+}
+
+// The commands a client sent, once it has disconnected
+pub fn commands_after_disconnect(log: &DaemonLog) -> Vec<String> {
+    let deadline = Instant::now() + StdDuration::from_secs(5);
+    loop {
+        let lines = log.lock().unwrap().clone();
+        if lines.iter().any(|line| line.ends_with("disconnected")) {
+            return lines
+                .into_iter()
+                .filter(|line| !line.ends_with("connected"))
+                .collect();
+        }
+        assert!(Instant::now() < deadline, "The client never disconnected");
+        thread::sleep(StdDuration::from_millis(20));
+    }
+}
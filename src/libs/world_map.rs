@@ -0,0 +1,245 @@
+use std::f64::consts::FRAC_PI_4;
+use std::sync::OnceLock;
+
+// Coastlines, lakes and country borders, see the header of the file for their source and format
+const OUTLINES: &str = include_str!("../../assets/world_outlines.txt");
+
+pub const LAND: char = '@';
+pub const BORDER: char = '.';
+pub const WATER: char = ' ';
+
+// Mercator stretches towards the poles without end, the map is cut at this latitude
+const MERCATOR_MAX_LATITUDE: f64 = 80.0;
+
+static OUTLINE_SEGMENTS: OnceLock<Vec<Segment>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Projection {
+    Equirectangular,
+    Mercator,
+    Miller,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentKind {
+    Coast,
+    Lake,
+    Border,
+}
+
+// One outline from the embedded file, points as (longitude, latitude)
+#[derive(Debug, Clone)]
+struct Segment {
+    kind: SegmentKind,
+    points: Vec<(f64, f64)>,
+    latitudes: (f64, f64), // Southernmost and northernmost point, to skip rings far from a cell
+}
+
+impl Projection {
+    pub fn parse(text: &str) -> Option<Projection> {
+        match text.trim().to_lowercase().as_str() {
+            "equirectangular" | "plate-carree" => Some(Projection::Equirectangular),
+            "mercator" => Some(Projection::Mercator),
+            "miller" => Some(Projection::Miller),
+            _ => None,
+        }
+    }
+
+    // How far from the equator a latitude is drawn, in the projection's own units
+    fn stretch(&self, latitude: f64) -> f64 {
+        let latitude = latitude.to_radians();
+        match self {
+            Projection::Equirectangular => latitude,
+            Projection::Mercator => (FRAC_PI_4 + latitude / 2.0).tan().ln(),
+            Projection::Miller => 1.25 * (FRAC_PI_4 + 0.4 * latitude).tan().ln(),
+        }
+    }
+
+    fn unstretch(&self, value: f64) -> f64 {
+        let latitude = match self {
+            Projection::Equirectangular => value,
+            Projection::Mercator => value.sinh().atan(),
+            Projection::Miller => 2.5 * ((0.8 * value).exp().atan() - FRAC_PI_4),
+        };
+        latitude.to_degrees()
+    }
+
+    fn max_latitude(&self) -> f64 {
+        match self {
+            Projection::Mercator => MERCATOR_MAX_LATITUDE,
+            _ => 90.0,
+        }
+    }
+
+    // Position on the map from 0 to 1, left to right and top to bottom
+    fn project(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let limit = self.max_latitude();
+        let top = self.stretch(limit);
+        let y = (top - self.stretch(latitude.clamp(-limit, limit))) / (2.0 * top);
+        ((longitude + 180.0) / 360.0, y)
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        let top = self.stretch(self.max_latitude());
+        (self.unstretch(top - y * 2.0 * top), x * 360.0 - 180.0)
+    }
+
+    /**
+    Finds the cell of a point on a map drawn in this projection
+
+    # Arguments
+    * `latitude` - The latitude in degrees, clamped to the edge of the map
+    * `longitude` - The longitude in degrees, -180 to 180
+    * `width` - The number of columns of the map
+    * `height` - The number of rows of the map
+    # Returns
+    * The column and row of the point
+    */
+    pub fn cell(&self, latitude: f64, longitude: f64, width: u32, height: u32) -> (u32, u32) {
+        let (x, y) = self.project(latitude, longitude);
+        let column = (x * width as f64).floor().clamp(0.0, (width - 1) as f64);
+        let row = (y * height as f64).floor().clamp(0.0, (height - 1) as f64);
+        (column as u32, row as u32)
+    }
+}
+
+fn parse_outlines(text: &str) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('>') {
+            let kind = match header.split_whitespace().next() {
+                Some("lake") => SegmentKind::Lake,
+                Some("border") => SegmentKind::Border,
+                _ => SegmentKind::Coast,
+            };
+            segments.push(Segment {
+                kind,
+                points: Vec::new(),
+                latitudes: (f64::INFINITY, f64::NEG_INFINITY),
+            });
+            continue;
+        }
+
+        let mut values = line.split_whitespace().map(str::parse::<f64>);
+        if let (Some(segment), Some(Ok(longitude)), Some(Ok(latitude))) =
+            (segments.last_mut(), values.next(), values.next())
+        {
+            segment.points.push((longitude, latitude));
+            segment.latitudes = (
+                segment.latitudes.0.min(latitude),
+                segment.latitudes.1.max(latitude),
+            );
+        }
+    }
+
+    segments
+}
+
+fn outline_segments() -> &'static [Segment] {
+    OUTLINE_SEGMENTS.get_or_init(|| parse_outlines(OUTLINES))
+}
+
+// Even-odd rule, so lakes inside a continent count as water
+fn is_land(segments: &[Segment], latitude: f64, longitude: f64) -> bool {
+    let mut inside = false;
+    let crossing = |s: &&Segment| latitude >= s.latitudes.0 && latitude <= s.latitudes.1;
+    for segment in segments
+        .iter()
+        .filter(|s| s.kind != SegmentKind::Border)
+        .filter(crossing)
+    {
+        let points = &segment.points;
+        for (i, &(x1, y1)) in points.iter().enumerate() {
+            let (x2, y2) = points[(i + 1) % points.len()];
+            if (y1 > latitude) != (y2 > latitude)
+                && longitude < x1 + (latitude - y1) / (y2 - y1) * (x2 - x1)
+            {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// Rings are cut along the antimeridian and the South Pole (Natural Earth closes Antarctica that
+// way), those edges aren't coastline. An edge jumping across the whole map is a ring crossing the
+// antimeridian without a cut, and isn't drawn either.
+fn on_map_edge(from: (f64, f64), to: (f64, f64)) -> bool {
+    (from.0.abs() == 180.0 && to.0.abs() == 180.0)
+        || (from.1 == -90.0 && to.1 == -90.0)
+        || (from.0 - to.0).abs() > 180.0
+}
+
+/**
+Rasterises the embedded world outlines into an ASCII map
+
+Land is filled by testing the centre of every cell, then every coastline is traced so islands
+smaller than a cell still show up, and the borders are drawn over the land.
+
+# Arguments
+* `projection` - The projection of the map
+* `width` - The number of columns
+* `height` - The number of rows
+# Returns
+* The rows of the map joined by newlines, the same shape as a converted image
+*/
+pub fn render_outlines(projection: Projection, width: u32, height: u32) -> String {
+    let segments = outline_segments();
+    let mut grid = vec![vec![WATER; width as usize]; height as usize];
+
+    for (row, cells) in grid.iter_mut().enumerate() {
+        for (column, cell) in cells.iter_mut().enumerate() {
+            let (latitude, longitude) = projection.unproject(
+                (column as f64 + 0.5) / width as f64,
+                (row as f64 + 0.5) / height as f64,
+            );
+            if is_land(segments, latitude, longitude) {
+                *cell = LAND;
+            }
+        }
+    }
+
+    for segment in segments {
+        let (glyph, closed) = match segment.kind {
+            SegmentKind::Coast | SegmentKind::Lake => (LAND, true),
+            SegmentKind::Border => (BORDER, false),
+        };
+        let points = &segment.points;
+        let edges = if closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        };
+
+        for i in 0..edges {
+            let from = points[i];
+            let to = points[(i + 1) % points.len()];
+            if on_map_edge(from, to) {
+                continue;
+            }
+
+            // Enough steps to touch every cell the edge crosses
+            let start = projection.cell(from.1, from.0, width, height);
+            let end = projection.cell(to.1, to.0, width, height);
+            let steps = (start.0.abs_diff(end.0).max(start.1.abs_diff(end.1)) * 2).max(1);
+            for step in 0..=steps {
+                let t = step as f64 / steps as f64;
+                let (column, row) = projection.cell(
+                    from.1 + (to.1 - from.1) * t,
+                    from.0 + (to.0 - from.0) * t,
+                    width,
+                    height,
+                );
+                grid[row as usize][column as usize] = glyph;
+            }
+        }
+    }
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
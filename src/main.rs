@@ -3,5 +3,15 @@ mod libs;
 
 fn main() {
     dotenvy::dotenv().ok();
-    cli::commands::main::main_menu();
+    if let Err(err) = cli::theme::init() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        cli::commands::main::main_menu();
+    } else {
+        cli::commands::main::run_command(&args);
+    }
 }